@@ -307,8 +307,8 @@ class WebSocketListener:
                         # Pass JSON string directly to Rust parser
                         result = phantom_core.extract_swap_token(response.text)
                         if result:
-                            # result is tuple (mint, symbol, amount_usd)
-                            token_mint, token_symbol, _ = result
+                            # result is tuple (mint, symbol, amount_usd, amount_sol, direction)
+                            token_mint, token_symbol = result[0], result[1]
                             if token_mint:
                                 # If missing symbol, try registry
                                 if not token_symbol:
@@ -1,4 +1,5 @@
-from dataclasses import dataclass
+from dataclasses import dataclass, field
+from typing import Dict
 
 
 @dataclass
@@ -14,3 +15,5 @@ class ScorerConfig:
     jito_tip_usd: float
     dex_fee_bps: int
     default_trade_size_usd: float
+    dex_fee_overrides: Dict[str, int] = field(default_factory=dict)
+    max_stale_slots: int = 20
@@ -3,8 +3,11 @@
 // Phase 1: Constant Product AMM (Raydium V4)
 // Phase 2: CLMM (Orca Whirlpool / Raydium CLMM)
 // Phase 3: DLMM (Meteora)
+// Phase 4: StableSwap (Curve invariant)
+// Phase 5: Unified quoting across pool kinds
 // ------------------------------------------------------------------------
 
+use primitive_types::U256;
 use pyo3::prelude::*;
 use pyo3::wrap_pyfunction;
 
@@ -38,6 +41,11 @@ pub fn compute_amm_out(
         return Ok(0);
     }
     
+    // A 100% fee leaves nothing to swap
+    if fee_bps >= 10000 {
+        return Ok(0);
+    }
+    
     // Use u128 for intermediate calculations to prevent overflow
     let amount_in_128 = amount_in as u128;
     let reserve_in_128 = reserve_in as u128;
@@ -75,16 +83,19 @@ pub fn compute_amm_out(
 /// * `reserve_in` - Pool reserve of input token
 /// * `reserve_out` - Pool reserve of output token
 /// * `fee_bps` - Fee in basis points
+/// * `round_up` - Ceil (minimum input guaranteeing the output) when true,
+///   floor (tight lower bound for leg sizing) when false
 /// 
 /// # Returns
 /// Required input amount (in smallest unit)
 #[pyfunction]
-#[pyo3(signature = (amount_out, reserve_in, reserve_out, fee_bps=25))]
+#[pyo3(signature = (amount_out, reserve_in, reserve_out, fee_bps=25, round_up=true))]
 pub fn compute_amm_in(
     amount_out: u64,
     reserve_in: u64,
     reserve_out: u64,
     fee_bps: u64,
+    round_up: bool,
 ) -> PyResult<u64> {
     // Guard against invalid inputs
     if reserve_in == 0 || reserve_out == 0 || amount_out == 0 {
@@ -98,6 +109,13 @@ pub fn compute_amm_in(
         ));
     }
     
+    // No input can buy any output through a 100% fee
+    if fee_bps >= 10000 {
+        return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+            "fee_bps must be below 10000"
+        ));
+    }
+    
     let amount_out_128 = amount_out as u128;
     let reserve_in_128 = reserve_in as u128;
     let reserve_out_128 = reserve_out as u128;
@@ -120,8 +138,12 @@ pub fn compute_amm_in(
         ));
     }
     
-    // Round up to ensure we get at least the desired output
-    let amount_in = (numerator + denominator - 1) / denominator;
+    let amount_in = if round_up {
+        // Round up to ensure we get at least the desired output
+        numerator.div_ceil(denominator)
+    } else {
+        numerator / denominator
+    };
     
     Ok(amount_in.min(u64::MAX as u128) as u64)
 }
@@ -156,6 +178,36 @@ pub fn compute_amm_out_batch(
     Ok(results)
 }
 
+/// Batches at least this large are computed in parallel; below it the
+/// Rayon dispatch costs more than the math.
+const PARALLEL_BATCH_MIN: usize = 1024;
+
+/// Calculate output amounts for many heterogeneous swaps in one FFI call.
+/// 
+/// Unlike `compute_amm_out_batch`, every entry carries its own reserves and
+/// fee, so mixed-fee pools (25, 30, 100 bps) can be scanned together.
+/// Large batches are computed in parallel with Rayon.
+/// 
+/// # Arguments
+/// * `entries` - Vector of (amount_in, reserve_in, reserve_out, fee_bps) tuples
+/// 
+/// # Returns
+/// Vector of output amounts, aligned with `entries`
+#[pyfunction]
+pub fn compute_amm_out_batch_full(entries: Vec<(u64, u64, u64, u64)>) -> PyResult<Vec<u64>> {
+    use rayon::prelude::*;
+
+    let quote = |&(amount_in, reserve_in, reserve_out, fee_bps): &(u64, u64, u64, u64)| {
+        compute_amm_out(amount_in, reserve_in, reserve_out, fee_bps)
+    };
+
+    if entries.len() >= PARALLEL_BATCH_MIN {
+        entries.par_iter().map(quote).collect()
+    } else {
+        entries.iter().map(quote).collect()
+    }
+}
+
 /// Calculate price impact for a swap.
 /// 
 /// # Returns
@@ -187,6 +239,89 @@ pub fn compute_price_impact(
     Ok(impact.max(0.0)) // Clamp to positive
 }
 
+/// Compute output for a swap capped at a maximum price impact.
+/// 
+/// If the pool cannot absorb the full size within the cap, binary-searches
+/// the largest input that keeps price impact at or under `max_price_impact_pct`.
+/// 
+/// # Arguments
+/// * `amount_in` - Desired input amount
+/// * `reserve_in` - Pool reserve of input token
+/// * `reserve_out` - Pool reserve of output token
+/// * `fee_bps` - Fee in basis points
+/// * `max_price_impact_pct` - Impact cap as a percentage (e.g., 1.0 = 1%)
+/// 
+/// # Returns
+/// Tuple of (amount_out, amount_in_consumed, hit_cap)
+#[pyfunction]
+#[pyo3(signature = (amount_in, reserve_in, reserve_out, fee_bps=25, max_price_impact_pct=1.0))]
+pub fn compute_amm_out_capped(
+    amount_in: u64,
+    reserve_in: u64,
+    reserve_out: u64,
+    fee_bps: u64,
+    max_price_impact_pct: f64,
+) -> PyResult<(u64, u64, bool)> {
+    if reserve_in == 0 || reserve_out == 0 || amount_in == 0 {
+        return Ok((0, 0, false));
+    }
+    
+    let within_cap = |size: u64| -> PyResult<bool> {
+        Ok(compute_price_impact(size, reserve_in, reserve_out, fee_bps)? <= max_price_impact_pct)
+    };
+    
+    // Full size fits under the cap
+    if within_cap(amount_in)? {
+        let out = compute_amm_out(amount_in, reserve_in, reserve_out, fee_bps)?;
+        return Ok((out, amount_in, false));
+    }
+    
+    // Invariant: `lo` is within the cap, `hi` is not. Start from zero rather
+    // than probing 1 unit, which integer rounding always shows as 100% impact.
+    let mut lo = 0u64;
+    let mut hi = amount_in;
+    while hi - lo > 1 {
+        let mid = lo + (hi - lo) / 2;
+        if within_cap(mid)? {
+            lo = mid;
+        } else {
+            hi = mid;
+        }
+    }
+    
+    // Even the smallest unit breaches the cap
+    if lo == 0 {
+        return Ok((0, 0, true));
+    }
+    
+    let out = compute_amm_out(lo, reserve_in, reserve_out, fee_bps)?;
+    Ok((out, lo, true))
+}
+
+/// Calculate price impact for many heterogeneous pools in one FFI call.
+/// 
+/// Entries are computed in parallel with Rayon. Invalid entries (zero
+/// reserves, overflow) yield 0.0 instead of failing the whole batch.
+/// 
+/// # Arguments
+/// * `pools` - Vector of (amount_in, reserve_in, reserve_out, fee_bps) tuples
+/// 
+/// # Returns
+/// Vector of price impacts as percentages, aligned with `pools`
+#[pyfunction]
+pub fn compute_price_impact_batch(pools: Vec<(u64, u64, u64, u64)>) -> PyResult<Vec<f64>> {
+    use rayon::prelude::*;
+
+    let impacts = pools
+        .par_iter()
+        .map(|&(amount_in, reserve_in, reserve_out, fee_bps)| {
+            compute_price_impact(amount_in, reserve_in, reserve_out, fee_bps).unwrap_or(0.0)
+        })
+        .collect();
+
+    Ok(impacts)
+}
+
 // ============================================================================
 // PHASE 2: CLMM (Concentrated Liquidity Market Maker)
 // Supports: Orca Whirlpool, Raydium CLMM
@@ -281,9 +416,10 @@ fn compute_b_from_sqrt_price_change(
     liquidity: u128,
 ) -> PyResult<u64> {
     // amount_b = liquidity * (sqrt_price_old - sqrt_price_new) / Q64
+    // liquidity * delta can exceed u128 for large positions, so multiply in U256
     let delta = sqrt_price_old.saturating_sub(sqrt_price_new);
-    let amount = (liquidity * delta) / Q64;
-    Ok(amount.min(u64::MAX as u128) as u64)
+    let amount = (U256::from(liquidity) * U256::from(delta)) >> 64;
+    Ok(amount.min(U256::from(u64::MAX)).as_u64())
 }
 
 /// Helper: Compute amount of token A received from a sqrt_price increase (B->A swap)
@@ -298,22 +434,120 @@ fn compute_a_from_sqrt_price_change(
     if sqrt_price_old == 0 || sqrt_price_new == 0 {
         return Ok(0);
     }
-    
+
     let delta = sqrt_price_new.saturating_sub(sqrt_price_old);
-    
-    // Use u128 arithmetic carefully to avoid overflow
-    // amount = liquidity * delta / sqrt_price_old
-    // (simplified since we're dividing by Q64 implicitly in the price representation)
-    let amount = (liquidity * delta) / sqrt_price_old;
-    let amount = amount / sqrt_price_new * Q64; // Normalize
-    
-    Ok(amount.min(u64::MAX as u128) as u64)
+
+    // Mainnet liquidity routinely exceeds 2^80, so liquidity * Q64 * delta
+    // does not fit in u128. Do the whole expression in U256 and divide once.
+    let numerator = (U256::from(liquidity) << 64)
+        .checked_mul(U256::from(delta))
+        .ok_or_else(|| PyErr::new::<pyo3::exceptions::PyOverflowError, _>("Overflow in numerator"))?;
+
+    // sqrt_price_old * sqrt_price_new is at most 256 bits, never overflows
+    let denominator = U256::from(sqrt_price_old) * U256::from(sqrt_price_new);
+
+    let amount = numerator / denominator;
+
+    Ok(amount.min(U256::from(u64::MAX)).as_u64())
+}
+
+/// Token amounts held by a CLMM position at the current price.
+///
+/// Standard concentrated-liquidity formulas (amounts rounded down):
+/// - price at or below the range: all token0, `L * (1/sqrt_lower - 1/sqrt_upper)`
+/// - price at or above the range: all token1, `L * (sqrt_upper - sqrt_lower)`
+/// - in range: token0 for `[price, upper]` plus token1 for `[lower, price]`
+///
+/// # Arguments
+/// * `sqrt_price_x64` - Current pool sqrt price (Q64.64)
+/// * `sqrt_price_lower_x64` - sqrt price at the position's lower tick
+/// * `sqrt_price_upper_x64` - sqrt price at the position's upper tick
+/// * `liquidity` - Position liquidity
+///
+/// # Returns
+/// Tuple of (amount_token0, amount_token1)
+#[pyfunction]
+pub fn clmm_position_amounts(
+    sqrt_price_x64: u128,
+    sqrt_price_lower_x64: u128,
+    sqrt_price_upper_x64: u128,
+    liquidity: u128,
+) -> PyResult<(u64, u64)> {
+    if sqrt_price_lower_x64 == 0 || sqrt_price_lower_x64 >= sqrt_price_upper_x64 {
+        return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+            "Invalid position range: sqrt_price_lower {} must be positive and below sqrt_price_upper {}",
+            sqrt_price_lower_x64, sqrt_price_upper_x64
+        )));
+    }
+
+    // Clamping the price into the range covers all three cases
+    let sqrt_price = sqrt_price_x64.clamp(sqrt_price_lower_x64, sqrt_price_upper_x64);
+    let amount_0 = compute_a_from_sqrt_price_change(sqrt_price, sqrt_price_upper_x64, liquidity)?;
+    let amount_1 = compute_b_from_sqrt_price_change(sqrt_price, sqrt_price_lower_x64, liquidity)?;
+
+    Ok((amount_0, amount_1))
+}
+
+/// Tick range supported by Orca Whirlpool / Raydium CLMM
+pub const MIN_TICK: i32 = -443636;
+pub const MAX_TICK: i32 = 443636;
+
+/// Q128 multipliers for the bit-shift tick method: entry i is
+/// 2^128 / sqrt(1.0001)^(2^i) (identical to Uniswap V3 TickMath).
+const TICK_RATIO_Q128: [u128; 19] = [
+    0xfffcb933bd6fad37aa2d162d1a594001,
+    0xfff97272373d413259a46990580e213a,
+    0xfff2e50f5f656932ef12357cf3c7fdcc,
+    0xffe5caca7e10e4e61c3624eaa0941cd0,
+    0xffcb9843d60f6159c9db58835c926644,
+    0xff973b41fa98c081472e6896dfb254c0,
+    0xff2ea16466c96a3843ec78b326b52861,
+    0xfe5dee046a99a2a811c461f1969c3053,
+    0xfcbe86c7900a88aedcffc83b479aa3a4,
+    0xf987a7253ac413176f2b074cf7815e54,
+    0xf3392b0822b70005940c7a398e4b70f3,
+    0xe7159475a2c29b7443b29c7fa6e889d9,
+    0xd097f3bdfd2022b8845ad8f792aa5825,
+    0xa9f746462d870fdf8a65dc1f90e061e5,
+    0x70d869a156d2a1b890bb3df62baf32f7,
+    0x31be135f97d08fd981231505542fcfa6,
+    0x09aa508b5b7a84e1c677de54f3e99bc9,
+    0x005d6af8dedb81196699c329225ee604,
+    0x00002216e584f5fa1ea926041bedfe98,
+];
+
+/// Exact sqrt price for an in-range tick (caller checks the range).
+fn sqrt_price_at_tick(tick: i32) -> u128 {
+    let abs_tick = tick.unsigned_abs();
+
+    // Multiply together 1/sqrt(1.0001)^(2^i) for every set bit of |tick|
+    let mut ratio = if abs_tick & 1 != 0 {
+        U256::from(TICK_RATIO_Q128[0])
+    } else {
+        U256::one() << 128
+    };
+    for (bit, &multiplier) in TICK_RATIO_Q128.iter().enumerate().skip(1) {
+        if abs_tick & (1 << bit) != 0 {
+            ratio = (ratio * U256::from(multiplier)) >> 128;
+        }
+    }
+
+    // Positive ticks are the reciprocal
+    if tick > 0 {
+        ratio = U256::MAX / ratio;
+    }
+
+    // Q128.128 -> Q64.64
+    (ratio >> 64).as_u128()
 }
 
 /// Convert a tick index to sqrt_price_x64.
 /// 
 /// Formula: sqrt_price = 1.0001^(tick/2) * 2^64
 /// 
+/// Uses the Uniswap V3 integer bit-shift method, so the result is exact to
+/// the Q64.64 representation across the whole tick range.
+/// 
 /// # Arguments
 /// * `tick` - The tick index (can be negative)
 /// 
@@ -321,22 +555,20 @@ fn compute_a_from_sqrt_price_change(
 /// sqrt_price as Q64.64 fixed point
 #[pyfunction]
 pub fn sqrt_price_from_tick(tick: i32) -> PyResult<u128> {
-    // sqrt(1.0001^tick) = 1.0001^(tick/2)
-    // We compute this using: e^(tick * ln(1.0001) / 2)
-    
-    let tick_f64 = tick as f64;
-    let ln_1_0001 = 0.00009999500033330834f64; // ln(1.0001)
-    let exponent = tick_f64 * ln_1_0001 / 2.0;
-    let sqrt_price = exponent.exp();
-    
-    // Convert to Q64.64
-    let sqrt_price_x64 = (sqrt_price * (Q64 as f64)) as u128;
+    if !(MIN_TICK..=MAX_TICK).contains(&tick) {
+        return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+            format!("tick {} outside [{}, {}]", tick, MIN_TICK, MAX_TICK)
+        ));
+    }
     
-    Ok(sqrt_price_x64)
+    Ok(sqrt_price_at_tick(tick))
 }
 
 /// Convert sqrt_price_x64 back to a tick index.
 /// 
+/// The f64 log estimate is corrected against `sqrt_price_from_tick`, so this
+/// is its exact inverse: the greatest tick whose sqrt price is <= the input.
+/// 
 /// # Arguments
 /// * `sqrt_price_x64` - sqrt price as Q64.64 fixed point
 /// 
@@ -350,14 +582,29 @@ pub fn tick_from_sqrt_price(sqrt_price_x64: u128) -> PyResult<i32> {
         ));
     }
     
+    if sqrt_price_x64 < sqrt_price_at_tick(MIN_TICK) || sqrt_price_x64 > sqrt_price_at_tick(MAX_TICK) {
+        return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+            "sqrt_price outside the supported tick range"
+        ));
+    }
+    
     // Convert from Q64.64 to f64
     let sqrt_price = (sqrt_price_x64 as f64) / (Q64 as f64);
     
     // tick = 2 * log(sqrt_price) / log(1.0001)
     let ln_1_0001 = 0.00009999500033330834f64;
-    let tick = (2.0 * sqrt_price.ln()) / ln_1_0001;
+    let estimate = (2.0 * sqrt_price.ln()) / ln_1_0001;
+    let mut tick = (estimate.floor() as i32).clamp(MIN_TICK, MAX_TICK);
     
-    Ok(tick.floor() as i32)
+    // The estimate can be off by one near the extremes; step to the exact tick
+    while tick > MIN_TICK && sqrt_price_at_tick(tick) > sqrt_price_x64 {
+        tick -= 1;
+    }
+    while tick < MAX_TICK && sqrt_price_at_tick(tick + 1) <= sqrt_price_x64 {
+        tick += 1;
+    }
+    
+    Ok(tick)
 }
 
 /// Get the current price from sqrt_price_x64.
@@ -455,40 +702,45 @@ pub fn compute_dlmm_swap_single_bin(
         return Ok((0, 0, false));
     }
     
+    // A 100% fee leaves nothing to swap
+    if fee_rate_bps >= 10000 {
+        return Ok((0, 0, false));
+    }
+    
     // Calculate price for this bin
     let price = dlmm_price_from_bin(bin_id, bin_step)?;
+    let fee_factor = 10000u128 - fee_rate_bps as u128;
     
-    // Apply fee
-    let fee_factor = (10000u64 - fee_rate_bps) as f64 / 10000.0;
-    let amount_in_after_fee = (amount_in as f64) * fee_factor;
-    
+    // How much post-fee input does it take to drain this bin?
     // In DLMM, within a bin, swap is at constant price
     // amount_out = amount_in * price (for X->Y) or amount_in / price (for Y->X)
-    let amount_out_f64 = if swap_for_y {
-        amount_in_after_fee * price
+    let net_needed_f64 = if swap_for_y {
+        (bin_reserve_out as f64) / price
     } else {
-        amount_in_after_fee / price
+        (bin_reserve_out as f64) * price
     };
+    let net_needed = net_needed_f64.ceil() as u128;
     
-    // Check if we can fully satisfy from this bin
-    let amount_out = amount_out_f64 as u64;
+    // Gross it up by the fee: gross = ceil(net * 10000 / (10000 - fee))
+    let gross_needed = (net_needed * 10000).div_ceil(fee_factor);
     
-    if amount_out <= bin_reserve_out {
-        // Fully satisfied within this bin
-        Ok((amount_out, amount_in, false))
-    } else {
-        // Need to cross to next bin
-        // How much input does it take to drain this bin?
-        let max_out = bin_reserve_out;
-        let input_needed_f64 = if swap_for_y {
-            (max_out as f64) / price / fee_factor
+    if (amount_in as u128) < gross_needed {
+        // Fully satisfied within this bin: fee is charged on the whole input
+        let fee = (amount_in as u128 * fee_rate_bps as u128).div_ceil(10000);
+        let amount_in_after_fee = (amount_in as u128 - fee) as f64;
+        
+        let amount_out_f64 = if swap_for_y {
+            amount_in_after_fee * price
         } else {
-            (max_out as f64) * price / fee_factor
+            amount_in_after_fee / price
         };
+        let amount_out = (amount_out_f64 as u64).min(bin_reserve_out);
         
-        let input_consumed = (input_needed_f64.ceil() as u64).min(amount_in);
-        
-        Ok((max_out, input_consumed, true))
+        Ok((amount_out, amount_in, false))
+    } else {
+        // Bin drained: only the gross input needed for it is consumed,
+        // the rest carries over to the next bin
+        Ok((bin_reserve_out, gross_needed as u64, true))
     }
 }
 
@@ -503,11 +755,13 @@ pub fn compute_dlmm_swap_single_bin(
 /// * `bin_reserves` - Vec of (bin_id, reserve_x, reserve_y) tuples, sorted by bin_id
 /// * `fee_rate_bps` - Fee rate in basis points
 /// * `swap_for_y` - True if swapping X for Y
+/// * `per_bin_fees` - Optional fee (bps) per bin, aligned with `bin_reserves`.
+///   Overrides `fee_rate_bps` to model Meteora's volatility-driven dynamic fees.
 /// 
 /// # Returns
 /// Tuple of (total_amount_out, final_bin_id)
 #[pyfunction]
-#[pyo3(signature = (amount_in, active_bin_id, bin_step, bin_reserves, fee_rate_bps=25, swap_for_y=true))]
+#[pyo3(signature = (amount_in, active_bin_id, bin_step, bin_reserves, fee_rate_bps=25, swap_for_y=true, per_bin_fees=None))]
 pub fn compute_dlmm_swap(
     amount_in: u64,
     active_bin_id: i32,
@@ -515,38 +769,49 @@ pub fn compute_dlmm_swap(
     bin_reserves: Vec<(i32, u64, u64)>, // (bin_id, reserve_x, reserve_y)
     fee_rate_bps: u64,
     swap_for_y: bool,
+    per_bin_fees: Option<Vec<u64>>,
 ) -> PyResult<(u64, i32)> {
     if amount_in == 0 || bin_reserves.is_empty() {
         return Ok((0, active_bin_id));
     }
     
+    if let Some(fees) = &per_bin_fees {
+        if fees.len() != bin_reserves.len() {
+            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+                "per_bin_fees must be the same length as bin_reserves"
+            ));
+        }
+    }
+    
     let mut remaining_in = amount_in;
     let mut total_out = 0u64;
     let mut current_bin_id = active_bin_id;
     
-    // Sort bins in the direction we're traversing
-    let mut sorted_bins = bin_reserves.clone();
+    // Pair each bin with its fee before sorting so they stay aligned
+    let mut sorted_bins: Vec<((i32, u64, u64), u64)> = match per_bin_fees {
+        Some(fees) => bin_reserves.into_iter().zip(fees).collect(),
+        None => bin_reserves.into_iter().map(|bin| (bin, fee_rate_bps)).collect(),
+    };
     if swap_for_y {
         // Swapping X for Y: price decreases, traverse bins downward
-        sorted_bins.sort_by(|a, b| b.0.cmp(&a.0)); // Descending
+        sorted_bins.sort_by_key(|&((bid, _, _), _)| std::cmp::Reverse(bid)); // Descending
     } else {
         // Swapping Y for X: price increases, traverse bins upward
-        sorted_bins.sort_by(|a, b| a.0.cmp(&b.0)); // Ascending
+        sorted_bins.sort_by_key(|&((bid, _, _), _)| bid); // Ascending
     }
     
     // Find starting position
-    let start_idx = sorted_bins.iter().position(|(bid, _, _)| *bid == active_bin_id);
+    let start_idx = sorted_bins.iter().position(|((bid, _, _), _)| *bid == active_bin_id);
     let start_idx = match start_idx {
         Some(idx) => idx,
         None => return Ok((0, active_bin_id)), // Active bin not found
     };
     
-    for i in start_idx..sorted_bins.len() {
+    for &((bin_id, reserve_x, reserve_y), bin_fee_bps) in &sorted_bins[start_idx..] {
         if remaining_in == 0 {
             break;
         }
         
-        let (bin_id, reserve_x, reserve_y) = sorted_bins[i];
         current_bin_id = bin_id;
         
         // Determine reserves based on swap direction
@@ -562,7 +827,7 @@ pub fn compute_dlmm_swap(
             reserve_out,
             bin_id,
             bin_step,
-            fee_rate_bps,
+            bin_fee_bps,
             swap_for_y,
         )?;
         
@@ -598,6 +863,235 @@ pub fn dlmm_get_effective_fee(
     Ok(effective_fee.min(1000))
 }
 
+// ============================================================================
+// PHASE 4: STABLESWAP (Curve Invariant - USDC/USDT style pools)
+// ============================================================================
+
+/// Number of coins in a StableSwap pool (pairs only)
+const STABLE_N_COINS: u128 = 2;
+
+/// Newton iteration cap for D and y (matches Curve's reference implementation)
+const STABLE_MAX_ITERATIONS: usize = 255;
+
+/// Compute the StableSwap invariant D for a two-coin pool via Newton's method.
+///
+/// Invariant: A * n^n * sum(x) + D = A * D * n^n + D^(n+1) / (n^n * prod(x))
+///
+/// Returns None on overflow.
+fn stableswap_compute_d(amp: u128, reserve_x: u128, reserve_y: u128) -> Option<u128> {
+    let sum = reserve_x.checked_add(reserve_y)?;
+    if sum == 0 {
+        return Some(0);
+    }
+
+    let ann = amp.checked_mul(STABLE_N_COINS)?;
+    let mut d = sum;
+
+    for _ in 0..STABLE_MAX_ITERATIONS {
+        // d_p = D^3 / (n^n * x * y)
+        let d_p = d
+            .checked_mul(d)?
+            .checked_div(reserve_x.checked_mul(STABLE_N_COINS)?)?
+            .checked_mul(d)?
+            .checked_div(reserve_y.checked_mul(STABLE_N_COINS)?)?;
+
+        let d_prev = d;
+        let numerator = ann
+            .checked_mul(sum)?
+            .checked_add(d_p.checked_mul(STABLE_N_COINS)?)?
+            .checked_mul(d)?;
+        let denominator = (ann - 1)
+            .checked_mul(d)?
+            .checked_add(d_p.checked_mul(STABLE_N_COINS + 1)?)?;
+        d = numerator.checked_div(denominator)?;
+
+        if d.abs_diff(d_prev) <= 1 {
+            break;
+        }
+    }
+
+    Some(d)
+}
+
+/// Solve the StableSwap invariant for the new output reserve y, given the
+/// new input reserve x and the invariant D. Returns None on overflow.
+fn stableswap_compute_y(amp: u128, new_reserve_x: u128, d: u128) -> Option<u128> {
+    let ann = amp.checked_mul(STABLE_N_COINS)?;
+
+    // c = D^3 / (n^n * x * Ann), b = x + D / Ann
+    let c = d
+        .checked_mul(d)?
+        .checked_div(new_reserve_x.checked_mul(STABLE_N_COINS)?)?
+        .checked_mul(d)?
+        .checked_div(ann.checked_mul(STABLE_N_COINS)?)?;
+    let b = new_reserve_x.checked_add(d / ann)?;
+
+    let mut y = d;
+    for _ in 0..STABLE_MAX_ITERATIONS {
+        let y_prev = y;
+        // y = (y^2 + c) / (2y + b - D)
+        let numerator = y.checked_mul(y)?.checked_add(c)?;
+        let denominator = y.checked_mul(2)?.checked_add(b)?.checked_sub(d)?;
+        y = numerator.checked_div(denominator)?;
+
+        if y.abs_diff(y_prev) <= 1 {
+            break;
+        }
+    }
+
+    Some(y)
+}
+
+/// Compute output amount for a StableSwap (Curve invariant) swap.
+///
+/// Near peg the invariant behaves like constant sum (almost no slippage);
+/// as reserves diverge or A shrinks it degrades toward constant product.
+///
+/// # Arguments
+/// * `amount_in` - Input token amount (in smallest unit)
+/// * `reserve_in` - Pool reserve of input token
+/// * `reserve_out` - Pool reserve of output token
+/// * `amp_coefficient` - Amplification coefficient A (e.g., 100 for USDC/USDT)
+/// * `fee_bps` - Fee in basis points, charged on the output
+///
+/// # Returns
+/// Output amount after swap (in smallest unit)
+#[pyfunction]
+#[pyo3(signature = (amount_in, reserve_in, reserve_out, amp_coefficient=100, fee_bps=4))]
+pub fn compute_stableswap_out(
+    amount_in: u64,
+    reserve_in: u64,
+    reserve_out: u64,
+    amp_coefficient: u64,
+    fee_bps: u64,
+) -> PyResult<u64> {
+    if reserve_in == 0 || reserve_out == 0 || amount_in == 0 {
+        return Ok(0);
+    }
+
+    if amp_coefficient == 0 {
+        return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+            "amp_coefficient must be at least 1"
+        ));
+    }
+
+    let amp = amp_coefficient as u128;
+    let reserve_in_128 = reserve_in as u128;
+    let reserve_out_128 = reserve_out as u128;
+
+    let d = stableswap_compute_d(amp, reserve_in_128, reserve_out_128)
+        .ok_or_else(|| PyErr::new::<pyo3::exceptions::PyOverflowError, _>("Overflow computing invariant D"))?;
+
+    let new_reserve_in = reserve_in_128 + amount_in as u128;
+    let new_reserve_out = stableswap_compute_y(amp, new_reserve_in, d)
+        .ok_or_else(|| PyErr::new::<pyo3::exceptions::PyOverflowError, _>("Overflow computing output reserve"))?;
+
+    // Subtract 1 to round against the trader, as Curve does
+    let gross_out = reserve_out_128
+        .saturating_sub(new_reserve_out)
+        .saturating_sub(1);
+    let fee = gross_out * fee_bps.min(10000) as u128 / 10000;
+
+    Ok((gross_out - fee).min(u64::MAX as u128) as u64)
+}
+
+// ============================================================================
+// PHASE 5: UNIFIED QUOTING
+// ============================================================================
+
+/// Fetch a parameter the selected pool kind requires.
+fn required<T>(value: Option<T>, pool_kind: &str, name: &str) -> PyResult<T> {
+    value.ok_or_else(|| {
+        PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+            "{} quote requires {}",
+            pool_kind, name
+        ))
+    })
+}
+
+/// Quote a swap on any supported pool kind, dispatching to the matching math.
+/// 
+/// Only the parameters for the selected kind are read:
+/// * `"cpmm"` - `reserve_in`, `reserve_out` (see `compute_amm_out`)
+/// * `"clmm"` - `sqrt_price_x64`, `liquidity`, `a_to_b` (see `compute_clmm_swap`)
+/// * `"dlmm"` - `active_bin_id`, `bin_step`, `bin_reserves`, `a_to_b` as
+///   `swap_for_y` (see `compute_dlmm_swap`)
+/// * `"stable"` - `reserve_in`, `reserve_out`, `amp_coefficient`
+///   (see `compute_stableswap_out`)
+/// 
+/// `fee_bps` defaults to each function's own default when omitted.
+/// 
+/// # Returns
+/// Output amount after swap (in smallest unit)
+#[pyfunction]
+#[pyo3(signature = (
+    pool_kind,
+    amount_in,
+    fee_bps=None,
+    reserve_in=None,
+    reserve_out=None,
+    sqrt_price_x64=None,
+    liquidity=None,
+    a_to_b=true,
+    active_bin_id=None,
+    bin_step=None,
+    bin_reserves=None,
+    amp_coefficient=100
+))]
+#[allow(clippy::too_many_arguments)]
+pub fn quote_swap(
+    pool_kind: &str,
+    amount_in: u64,
+    fee_bps: Option<u64>,
+    reserve_in: Option<u64>,
+    reserve_out: Option<u64>,
+    sqrt_price_x64: Option<u128>,
+    liquidity: Option<u128>,
+    a_to_b: bool,
+    active_bin_id: Option<i32>,
+    bin_step: Option<u16>,
+    bin_reserves: Option<Vec<(i32, u64, u64)>>,
+    amp_coefficient: u64,
+) -> PyResult<u64> {
+    match pool_kind {
+        "cpmm" => compute_amm_out(
+            amount_in,
+            required(reserve_in, pool_kind, "reserve_in")?,
+            required(reserve_out, pool_kind, "reserve_out")?,
+            fee_bps.unwrap_or(25),
+        ),
+        "clmm" => compute_clmm_swap(
+            amount_in,
+            required(sqrt_price_x64, pool_kind, "sqrt_price_x64")?,
+            required(liquidity, pool_kind, "liquidity")?,
+            a_to_b,
+            fee_bps.unwrap_or(30),
+        )
+        .map(|(amount_out, _)| amount_out),
+        "dlmm" => compute_dlmm_swap(
+            amount_in,
+            required(active_bin_id, pool_kind, "active_bin_id")?,
+            required(bin_step, pool_kind, "bin_step")?,
+            required(bin_reserves, pool_kind, "bin_reserves")?,
+            fee_bps.unwrap_or(25),
+            a_to_b,
+            None,
+        )
+        .map(|(amount_out, _)| amount_out),
+        "stable" => compute_stableswap_out(
+            amount_in,
+            required(reserve_in, pool_kind, "reserve_in")?,
+            required(reserve_out, pool_kind, "reserve_out")?,
+            amp_coefficient,
+            fee_bps.unwrap_or(4),
+        ),
+        _ => Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+            "Unknown pool kind '{}' (expected cpmm, clmm, dlmm or stable)",
+            pool_kind
+        ))),
+    }
+}
+
 // ============================================================================
 // MODULE EXPORTS
 // ============================================================================
@@ -607,13 +1101,17 @@ pub fn register_amm_functions(m: &PyModule) -> PyResult<()> {
     m.add_function(wrap_pyfunction!(compute_amm_out, m)?)?;
     m.add_function(wrap_pyfunction!(compute_amm_in, m)?)?;
     m.add_function(wrap_pyfunction!(compute_amm_out_batch, m)?)?;
+    m.add_function(wrap_pyfunction!(compute_amm_out_batch_full, m)?)?;
     m.add_function(wrap_pyfunction!(compute_price_impact, m)?)?;
+    m.add_function(wrap_pyfunction!(compute_price_impact_batch, m)?)?;
+    m.add_function(wrap_pyfunction!(compute_amm_out_capped, m)?)?;
     
     // Phase 2: CLMM
     m.add_function(wrap_pyfunction!(compute_clmm_swap, m)?)?;
     m.add_function(wrap_pyfunction!(sqrt_price_from_tick, m)?)?;
     m.add_function(wrap_pyfunction!(tick_from_sqrt_price, m)?)?;
     m.add_function(wrap_pyfunction!(price_from_sqrt_price, m)?)?;
+    m.add_function(wrap_pyfunction!(clmm_position_amounts, m)?)?;
     
     // Phase 3: DLMM
     m.add_function(wrap_pyfunction!(dlmm_price_from_bin, m)?)?;
@@ -621,7 +1119,13 @@ pub fn register_amm_functions(m: &PyModule) -> PyResult<()> {
     m.add_function(wrap_pyfunction!(compute_dlmm_swap_single_bin, m)?)?;
     m.add_function(wrap_pyfunction!(compute_dlmm_swap, m)?)?;
     m.add_function(wrap_pyfunction!(dlmm_get_effective_fee, m)?)?;
-    
+
+    // Phase 4: StableSwap
+    m.add_function(wrap_pyfunction!(compute_stableswap_out, m)?)?;
+
+    // Phase 5: Unified quoting
+    m.add_function(wrap_pyfunction!(quote_swap, m)?)?;
+
     Ok(())
 }
 
@@ -643,18 +1147,41 @@ mod tests {
     #[test]
     fn test_compute_amm_in_basic() {
         // Inverse: If I want 99 USDC, how much SOL do I need?
-        let in_amt = compute_amm_in(99_000_000, 1000_000_000_000, 100000_000_000, 25).unwrap();
+        let in_amt = compute_amm_in(99_000_000, 1000_000_000_000, 100000_000_000, 25, true).unwrap();
         
         // Should be approximately 1 SOL
         assert!(in_amt > 900_000_000 && in_amt < 1_100_000_000);
     }
 
+    #[test]
+    fn test_compute_amm_in_rounding_modes() {
+        let ceil = compute_amm_in(99_000_000, 1_000_000_000_000, 100_000_000_000, 25, true).unwrap();
+        let floor = compute_amm_in(99_000_000, 1_000_000_000_000, 100_000_000_000, 25, false).unwrap();
+
+        assert!(ceil >= floor);
+        assert!(ceil - floor <= 1);
+    }
+
     #[test]
     fn test_zero_input() {
         let out = compute_amm_out(0, 1000, 1000, 25).unwrap();
         assert_eq!(out, 0);
     }
 
+    #[test]
+    fn test_amm_fee_at_or_above_100_percent() {
+        assert_eq!(compute_amm_out(1_000, 1_000_000, 1_000_000, 10000).unwrap(), 0);
+        assert_eq!(compute_amm_out(1_000, 1_000_000, 1_000_000, 20000).unwrap(), 0);
+        assert!(compute_amm_in(1_000, 1_000_000, 1_000_000, 20000, true).is_err());
+
+        // One bad entry must not panic the parallel batch
+        let mut entries = vec![(1_000u64, 1_000_000u64, 1_000_000u64, 25u64); PARALLEL_BATCH_MIN];
+        entries[7].3 = 20000;
+        let outs = compute_amm_out_batch_full(entries).unwrap();
+        assert_eq!(outs[7], 0);
+        assert!(outs[0] > 0);
+    }
+
     #[test]
     fn test_price_impact() {
         // Large trade should have meaningful impact
@@ -663,4 +1190,316 @@ mod tests {
         // 10% of pool should have noticeable impact
         assert!(impact > 5.0);
     }
+
+    #[test]
+    fn test_price_impact_batch() {
+        let pools = vec![
+            (100_000_000_000, 1_000_000_000_000, 100_000_000_000, 25),
+            (1_000, 0, 1_000_000, 25), // Zero reserve: 0.0, not an error
+            (1_000_000, 1_000_000_000, 1_000_000_000, 30),
+        ];
+
+        let impacts = compute_price_impact_batch(pools.clone()).unwrap();
+
+        assert_eq!(impacts.len(), 3);
+        assert_eq!(impacts[1], 0.0);
+        for (i, &(a, r_in, r_out, fee)) in pools.iter().enumerate() {
+            assert_eq!(impacts[i], compute_price_impact(a, r_in, r_out, fee).unwrap());
+        }
+    }
+
+    #[test]
+    fn test_amm_out_capped() {
+        let (r_in, r_out) = (1_000_000_000_000u64, 100_000_000_000u64);
+
+        // Small trade: well under a 1% cap, filled in full
+        let (out, consumed, hit) = compute_amm_out_capped(1_000_000_000, r_in, r_out, 25, 1.0).unwrap();
+        assert!(!hit);
+        assert_eq!(consumed, 1_000_000_000);
+        assert_eq!(out, compute_amm_out(1_000_000_000, r_in, r_out, 25).unwrap());
+
+        // 10% of the pool: must be trimmed to the largest size under the cap
+        let (out, consumed, hit) = compute_amm_out_capped(100_000_000_000, r_in, r_out, 25, 1.0).unwrap();
+        assert!(hit);
+        assert!(consumed > 0 && consumed < 100_000_000_000);
+        assert!(out > 0);
+        assert!(compute_price_impact(consumed, r_in, r_out, 25).unwrap() <= 1.0);
+        assert!(compute_price_impact(consumed + 1, r_in, r_out, 25).unwrap() > 1.0);
+
+        // Fee alone exceeds the cap: nothing can be filled
+        assert_eq!(compute_amm_out_capped(1_000_000, r_in, r_out, 25, 0.1).unwrap(), (0, 0, true));
+    }
+
+    #[test]
+    fn test_dlmm_swap_per_bin_fees() {
+        let bin_step = 10;
+        let reserve = 1_000_000u64;
+        let bins = vec![
+            (DLMM_BIN_OFFSET - 2, 0, reserve),
+            (DLMM_BIN_OFFSET - 1, 0, reserve),
+            (DLMM_BIN_OFFSET, 0, reserve),
+        ];
+        // Active bin is cheap, fees climb as we walk away from it
+        let fees = vec![100, 50, 10];
+        let amount_in = 2_500_000u64;
+
+        let (out, final_bin) = compute_dlmm_swap(
+            amount_in, DLMM_BIN_OFFSET, bin_step, bins.clone(), 25, true, Some(fees),
+        ).unwrap();
+
+        // First two bins drained, third partially filled
+        assert_eq!(final_bin, DLMM_BIN_OFFSET - 2);
+        assert!(out > 2 * reserve && out < 3 * reserve);
+
+        // Input left for the third bin after grossing up the first two by their own fees
+        let gross = |net: f64, fee: f64| (net / (1.0 - fee / 10000.0)).ceil();
+        let price_1 = dlmm_price_from_bin(DLMM_BIN_OFFSET - 1, bin_step).unwrap();
+        let price_2 = dlmm_price_from_bin(DLMM_BIN_OFFSET - 2, bin_step).unwrap();
+        let used = gross(reserve as f64, 10.0) + gross(reserve as f64 / price_1, 50.0);
+        let expected_tail = (amount_in as f64 - used) * (1.0 - 100.0 / 10000.0) * price_2;
+        let tail = (out - 2 * reserve) as f64;
+        assert!((tail - expected_tail).abs() < 3.0);
+
+        // A flat 10 bps fee must yield strictly more output
+        let (flat_out, _) = compute_dlmm_swap(
+            amount_in, DLMM_BIN_OFFSET, bin_step, bins, 10, true, None,
+        ).unwrap();
+        assert!(flat_out > out);
+    }
+
+    #[test]
+    fn test_dlmm_swap_per_bin_fees_length_mismatch() {
+        let bins = vec![(DLMM_BIN_OFFSET, 0, 1_000_000)];
+        assert!(compute_dlmm_swap(1_000, DLMM_BIN_OFFSET, 10, bins, 25, true, Some(vec![])).is_err());
+    }
+
+    #[test]
+    fn test_sqrt_price_from_tick_anchors() {
+        assert_eq!(sqrt_price_from_tick(0).unwrap(), Q64);
+        assert!(sqrt_price_from_tick(MAX_TICK + 1).is_err());
+        assert!(sqrt_price_from_tick(MIN_TICK - 1).is_err());
+
+        // Extremes land at ~2^96 and ~2^32 in Q64.64
+        let max = sqrt_price_from_tick(MAX_TICK).unwrap() as f64;
+        let min = sqrt_price_from_tick(MIN_TICK).unwrap() as f64;
+        assert!((max / 2f64.powi(96) - 1.0).abs() < 0.01);
+        assert!((min / 2f64.powi(32) - 1.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_tick_roundtrip_full_range() {
+        // Stride through the whole range, plus every tick near both extremes
+        // where the f64 estimate drifts the most
+        let ticks = (MIN_TICK..=MAX_TICK)
+            .step_by(37)
+            .chain(MIN_TICK..MIN_TICK + 1000)
+            .chain(MAX_TICK - 1000..=MAX_TICK);
+
+        for tick in ticks {
+            let sqrt_price = sqrt_price_from_tick(tick).unwrap();
+            assert_eq!(tick_from_sqrt_price(sqrt_price).unwrap(), tick);
+
+            // Exactness also requires strict monotonicity between neighbours
+            if tick < MAX_TICK {
+                assert!(sqrt_price_from_tick(tick + 1).unwrap() > sqrt_price);
+                assert_eq!(tick_from_sqrt_price(sqrt_price_from_tick(tick + 1).unwrap() - 1).unwrap(), tick);
+            }
+        }
+    }
+
+    #[test]
+    fn test_a_from_sqrt_price_change_large_liquidity() {
+        // Realistic mainnet liquidity with sqrt prices around 2^64 (price ~1.0)
+        let liquidity: u128 = 5_000_000_000_000_000_000;
+        let sqrt_old: u128 = Q64;
+        let sqrt_new: u128 = Q64 + (Q64 >> 6);
+
+        let amount = compute_a_from_sqrt_price_change(sqrt_old, sqrt_new, liquidity).unwrap();
+
+        // Reference: L * (1/sqrt_old - 1/sqrt_new) with prices normalized to 1.0
+        let old_f = sqrt_old as f64 / Q64 as f64;
+        let new_f = sqrt_new as f64 / Q64 as f64;
+        let expected = liquidity as f64 * (1.0 / old_f - 1.0 / new_f);
+
+        assert!(amount > 0);
+        assert!((amount as f64 - expected).abs() <= expected * 1e-12);
+    }
+
+    /// Float reference for a position's (token0, token1) at sqrt price `s` in [sa, sb]
+    fn position_reference(liquidity: f64, s: f64, sa: f64, sb: f64) -> (f64, f64) {
+        let s = s.clamp(sa, sb);
+        (liquidity * (1.0 / s - 1.0 / sb), liquidity * (s - sa))
+    }
+
+    fn sqrt_f(sqrt_price_x64: u128) -> f64 {
+        sqrt_price_x64 as f64 / Q64 as f64
+    }
+
+    #[test]
+    fn test_clmm_position_amounts_in_range() {
+        let lower = sqrt_price_from_tick(-1000).unwrap();
+        let upper = sqrt_price_from_tick(1000).unwrap();
+        let liquidity: u128 = 5_000_000_000_000_000_000;
+
+        // Price 1.0 in a symmetric range holds both tokens, about equally
+        let (amount_0, amount_1) = clmm_position_amounts(Q64, lower, upper, liquidity).unwrap();
+        let (ref_0, ref_1) = position_reference(liquidity as f64, 1.0, sqrt_f(lower), sqrt_f(upper));
+        assert!(amount_0 > 0 && amount_1 > 0);
+        assert!((amount_0 as f64 - ref_0).abs() <= ref_0 * 1e-9);
+        assert!((amount_1 as f64 - ref_1).abs() <= ref_1 * 1e-9);
+        assert!((amount_0 as f64 / amount_1 as f64 - 1.0).abs() < 1e-6);
+
+        // Moving the price up converts token0 into token1
+        let higher = sqrt_price_from_tick(500).unwrap();
+        let (up_0, up_1) = clmm_position_amounts(higher, lower, upper, liquidity).unwrap();
+        assert!(up_0 < amount_0 && up_1 > amount_1);
+    }
+
+    #[test]
+    fn test_clmm_position_amounts_below_range_is_all_token0() {
+        let lower = sqrt_price_from_tick(1000).unwrap();
+        let upper = sqrt_price_from_tick(2000).unwrap();
+        let liquidity: u128 = 1_000_000_000_000;
+
+        let (amount_0, amount_1) = clmm_position_amounts(Q64, lower, upper, liquidity).unwrap();
+        let (ref_0, _) = position_reference(liquidity as f64, 1.0, sqrt_f(lower), sqrt_f(upper));
+        assert_eq!(amount_1, 0);
+        assert!((amount_0 as f64 - ref_0).abs() <= ref_0 * 1e-9);
+
+        // At the lower bound the position is still entirely token0
+        assert_eq!(clmm_position_amounts(lower, lower, upper, liquidity).unwrap(), (amount_0, 0));
+    }
+
+    #[test]
+    fn test_clmm_position_amounts_above_range_is_all_token1() {
+        let lower = sqrt_price_from_tick(-2000).unwrap();
+        let upper = sqrt_price_from_tick(-1000).unwrap();
+        // Large enough that liquidity * delta overflows u128
+        let liquidity: u128 = 1u128 << 100;
+
+        let (amount_0, amount_1) = clmm_position_amounts(Q64, lower, upper, liquidity >> 40).unwrap();
+        let (_, ref_1) = position_reference((liquidity >> 40) as f64, 1.0, sqrt_f(lower), sqrt_f(upper));
+        assert_eq!(amount_0, 0);
+        assert!((amount_1 as f64 - ref_1).abs() <= ref_1 * 1e-9);
+
+        let (_, capped) = clmm_position_amounts(Q64, lower, upper, liquidity).unwrap();
+        assert_eq!(capped, u64::MAX);
+
+        assert!(clmm_position_amounts(Q64, upper, lower, 1).is_err());
+        assert!(clmm_position_amounts(Q64, 0, upper, 1).is_err());
+    }
+
+    #[test]
+    fn test_stableswap_low_slippage_at_peg() {
+        // Balanced 1M/1M stable pool, 1% trade, no fees to isolate the curve
+        let reserve = 1_000_000_000_000u64;
+        let amount_in = reserve / 100;
+
+        let stable_out = compute_stableswap_out(amount_in, reserve, reserve, 100, 0).unwrap();
+        let cp_out = compute_amm_out(amount_in, reserve, reserve, 0).unwrap();
+
+        let stable_slippage = (amount_in - stable_out) as f64 / amount_in as f64;
+        let cp_slippage = (amount_in - cp_out) as f64 / amount_in as f64;
+
+        assert!(stable_out > cp_out);
+        assert!(stable_slippage < cp_slippage / 10.0);
+    }
+
+    #[test]
+    fn test_stableswap_degrades_toward_constant_product() {
+        let reserve = 1_000_000_000_000u64;
+        let amount_in = reserve / 100;
+
+        let cp_out = compute_amm_out(amount_in, reserve, reserve, 0).unwrap();
+        let slippage = |amp: u64| {
+            let out = compute_stableswap_out(amount_in, reserve, reserve, amp, 0).unwrap();
+            (amount_in - out) as f64 / amount_in as f64
+        };
+        let cp_slippage = (amount_in - cp_out) as f64 / amount_in as f64;
+
+        // Lower A means more slippage, approaching (but never exceeding) constant product
+        assert!(slippage(1) > slippage(10));
+        assert!(slippage(10) > slippage(100));
+        assert!(slippage(1) > cp_slippage * 0.4);
+        assert!(slippage(1) < cp_slippage);
+    }
+
+    #[test]
+    fn test_stableswap_rejects_zero_amp() {
+        assert!(compute_stableswap_out(1_000, 1_000_000, 1_000_000, 0, 4).is_err());
+    }
+
+    #[test]
+    fn test_quote_swap_matches_underlying_math() {
+        let cpmm = quote_swap(
+            "cpmm", 1_000_000, None, Some(50_000_000), Some(7_000_000_000),
+            None, None, true, None, None, None, 100,
+        )
+        .unwrap();
+        assert_eq!(cpmm, compute_amm_out(1_000_000, 50_000_000, 7_000_000_000, 25).unwrap());
+
+        let sqrt_price = 1u128 << 64;
+        let liquidity = 1_000_000_000_000u128;
+        for a_to_b in [true, false] {
+            let clmm = quote_swap(
+                "clmm", 1_000_000, Some(30), None, None,
+                Some(sqrt_price), Some(liquidity), a_to_b, None, None, None, 100,
+            )
+            .unwrap();
+            let (expected, _) = compute_clmm_swap(1_000_000, sqrt_price, liquidity, a_to_b, 30).unwrap();
+            assert_eq!(clmm, expected);
+            assert!(clmm > 0);
+        }
+
+        let active = DLMM_BIN_OFFSET;
+        let bins = vec![(active - 1, 1_000_000, 1_000_000), (active, 1_000_000, 1_000_000)];
+        let dlmm = quote_swap(
+            "dlmm", 1_500_000, None, None, None,
+            None, None, true, Some(active), Some(10), Some(bins.clone()), 100,
+        )
+        .unwrap();
+        assert_eq!(dlmm, compute_dlmm_swap(1_500_000, active, 10, bins, 25, true, None).unwrap().0);
+        assert!(dlmm > 0);
+
+        let stable = quote_swap(
+            "stable", 1_000, None, Some(1_000_000), Some(1_000_000),
+            None, None, true, None, None, None, 100,
+        )
+        .unwrap();
+        assert_eq!(stable, compute_stableswap_out(1_000, 1_000_000, 1_000_000, 100, 4).unwrap());
+    }
+
+    #[test]
+    fn test_quote_swap_rejects_unknown_kind_and_missing_params() {
+        let no_params = |kind| quote_swap(kind, 1_000, None, None, None, None, None, true, None, None, None, 100);
+        assert!(no_params("orderbook").is_err());
+        for kind in ["cpmm", "clmm", "dlmm", "stable"] {
+            assert!(no_params(kind).is_err(), "{} accepted missing params", kind);
+        }
+    }
+
+    #[test]
+    fn test_amm_out_batch_full_mixes_fee_tiers() {
+        let pools = [
+            (1_000_000_000_000u64, 100_000_000_000u64, 25u64),
+            (50_000_000_000, 7_000_000_000_000, 30),
+            (2_000_000_000, 2_000_000_000, 100),
+        ];
+        let entries: Vec<(u64, u64, u64, u64)> = (0..3000u64)
+            .map(|i| {
+                let (reserve_in, reserve_out, fee_bps) = pools[i as usize % pools.len()];
+                (1_000 + i * 997, reserve_in, reserve_out, fee_bps)
+            })
+            .collect();
+
+        // Both the sequential (small) and parallel (large) paths
+        for batch in [&entries[..10], &entries[..]] {
+            let outs = compute_amm_out_batch_full(batch.to_vec()).unwrap();
+            assert_eq!(outs.len(), batch.len());
+            for (&(amount_in, reserve_in, reserve_out, fee_bps), out) in batch.iter().zip(outs) {
+                assert_eq!(out, compute_amm_out(amount_in, reserve_in, reserve_out, fee_bps).unwrap());
+            }
+        }
+        assert!(compute_amm_out_batch_full(Vec::new()).unwrap().is_empty());
+    }
 }
@@ -13,10 +13,14 @@
 // Therefore, finding a negative cycle = finding a profitable arbitrage path.
 // ------------------------------------------------------------------------
 
+use crate::amm_math::compute_amm_out;
 use crate::graph::HopGraph;
 use pyo3::prelude::*;
 use std::collections::HashMap;
 
+/// Sized simulation runs in micro-USD so integer AMM math keeps precision
+const SIZED_USD_SCALE: f64 = 1_000_000.0;
+
 /// A profitable arbitrage cycle detected by the algorithm.
 #[pyclass]
 #[derive(Clone, Debug)]
@@ -29,6 +33,9 @@ pub struct HopCycle {
     #[pyo3(get)]
     pub pool_addresses: Vec<String>,
 
+    /// DEX of each pool, parallel to `pool_addresses` (see `get_dexes`)
+    pub dexes: Vec<String>,
+
     /// Theoretical profit percentage (e.g., 0.5 = 0.5%)
     #[pyo3(get)]
     pub theoretical_profit_pct: f64,
@@ -57,10 +64,9 @@ impl HopCycle {
         self.theoretical_profit_pct >= min_profit_pct
     }
 
-    /// Get the DEXes involved in this cycle
+    /// Get the DEXes involved in this cycle (one per hop)
     pub fn get_dexes(&self) -> Vec<String> {
-        // Would need to store DEX info - for now return empty
-        Vec::new()
+        self.dexes.clone()
     }
 
     /// String representation
@@ -125,9 +131,7 @@ impl CycleFinder {
         }
 
         // State for DFS: (current_path, current_pools, total_weight, min_liquidity, total_fees)
-        let initial_edges = graph.get_outbound(start_mint);
-
-        for edge in initial_edges {
+        for edge in graph.outbound_ref(start_mint) {
             // Skip edges below liquidity threshold
             if edge.liquidity_usd < self.min_liquidity_usd {
                 continue;
@@ -139,6 +143,7 @@ impl CycleFinder {
                 &edge.target_mint,
                 vec![start_mint.to_string(), edge.target_mint.clone()],
                 vec![edge.pool_address.clone()],
+                vec![edge.dex.clone()],
                 edge.weight,
                 edge.liquidity_usd,
                 edge.fee_bps as u32,
@@ -157,8 +162,63 @@ impl CycleFinder {
         cycles
     }
 
+    /// Find cycles from several base tokens (e.g. SOL, USDC, USDT) in parallel.
+    /// The same loop reached from different bases appears once.
+    pub fn find_cycles_multi(&self, graph: &HopGraph, start_mints: Vec<String>) -> Vec<HopCycle> {
+        use rayon::prelude::*;
+
+        // Graph is read-only for the whole scan, so each start gets its own DFS
+        let per_start: Vec<Vec<HopCycle>> = start_mints
+            .par_iter()
+            .map(|start| self.find_cycles(graph, start))
+            .collect();
+
+        let mut seen = std::collections::HashSet::new();
+        let mut cycles: Vec<HopCycle> = per_start
+            .into_iter()
+            .flatten()
+            .filter(|cycle| seen.insert(Self::canonical_rotation(&cycle.pool_addresses)))
+            .collect();
+
+        cycles.sort_by(|a, b| {
+            b.theoretical_profit_pct
+                .partial_cmp(&a.theoretical_profit_pct)
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+
+        cycles
+    }
+
+    /// Find cycles and rank them by realized output for a fixed probe size.
+    ///
+    /// `probe_amount` is the trade size in USD. Each hop is simulated with
+    /// `compute_amm_out` against a constant-product pool holding half of the
+    /// edge's `liquidity_usd` on each side, so thin pools pay their slippage.
+    ///
+    /// Returns (cycle, realized output/input ratio), best first. A ratio
+    /// above 1.0 means the probe comes back larger than it left.
+    pub fn find_cycles_sized(
+        &self,
+        graph: &HopGraph,
+        start_mint: &str,
+        probe_amount: f64,
+    ) -> Vec<(HopCycle, f64)> {
+        let mut sized: Vec<(HopCycle, f64)> = self
+            .find_cycles(graph, start_mint)
+            .into_iter()
+            .map(|cycle| {
+                let ratio = Self::simulate_cycle_ratio(graph, &cycle, probe_amount);
+                (cycle, ratio)
+            })
+            .collect();
+
+        sized.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+        sized
+    }
+
     /// Validate that a specific path is still profitable.
-    /// Returns None if path is no longer valid or profitable.
+    /// Uses the best-rate pool for each hop; the chosen pools and DEXes are
+    /// in the returned cycle. Returns None if path is no longer valid or profitable.
     pub fn validate_path(&self, graph: &HopGraph, path: Vec<String>) -> Option<HopCycle> {
         if path.len() < 3 || path.first() != path.last() {
             return None; // Invalid cycle structure
@@ -168,19 +228,30 @@ impl CycleFinder {
         let mut min_liquidity = u64::MAX;
         let mut total_fees: u32 = 0;
         let mut pool_addresses = Vec::new();
+        let mut dexes = Vec::new();
 
         for i in 0..path.len() - 1 {
             let source = &path[i];
             let target = &path[i + 1];
 
-            // Find edge from source to target
-            let edges = graph.get_outbound(source);
-            let edge = edges.iter().find(|e| e.target_mint == *target)?;
+            // Several pools can serve the same pair: take the best rate
+            // (lowest weight), breaking ties by deeper liquidity
+            let edge = graph
+                .outbound_ref(source)
+                .iter()
+                .filter(|e| e.target_mint == *target)
+                .min_by(|a, b| {
+                    a.weight
+                        .partial_cmp(&b.weight)
+                        .unwrap_or(std::cmp::Ordering::Equal)
+                        .then(b.liquidity_usd.cmp(&a.liquidity_usd))
+                })?;
 
             total_weight += edge.weight;
             min_liquidity = min_liquidity.min(edge.liquidity_usd);
             total_fees += edge.fee_bps as u32;
             pool_addresses.push(edge.pool_address.clone());
+            dexes.push(edge.dex.clone());
         }
 
         // Calculate profit: if weight < 0, cycle is profitable
@@ -194,6 +265,7 @@ impl CycleFinder {
         Some(HopCycle {
             path: path.to_vec(),
             pool_addresses,
+            dexes,
             theoretical_profit_pct: profit_pct,
             min_liquidity_usd: min_liquidity,
             total_fee_bps: total_fees.min(u16::MAX as u32) as u16,
@@ -219,6 +291,49 @@ impl CycleFinder {
 }
 
 impl CycleFinder {
+    /// Rotation of `pools` starting at its smallest pool address, so
+    /// A→B→C and B→C→A share a key (direction still matters).
+    fn canonical_rotation(pools: &[String]) -> Vec<String> {
+        let start = pools
+            .iter()
+            .enumerate()
+            .min_by(|a, b| a.1.cmp(b.1))
+            .map(|(i, _)| i)
+            .unwrap_or(0);
+        pools[start..].iter().chain(&pools[..start]).cloned().collect()
+    }
+
+    /// Push `probe_usd` through every hop of `cycle`; returns output/input.
+    /// Spot rates carry the price ratio, the simulated USD value carries fees
+    /// and slippage.
+    fn simulate_cycle_ratio(graph: &HopGraph, cycle: &HopCycle, probe_usd: f64) -> f64 {
+        let probe_scaled = (probe_usd * SIZED_USD_SCALE) as u64;
+        if probe_scaled == 0 {
+            return 0.0;
+        }
+
+        let mut value = probe_scaled;
+        let mut spot_product = 1.0;
+
+        for (source, pool) in cycle.path.iter().zip(&cycle.pool_addresses) {
+            // Match the source too: both directions of a pool share its address
+            let edge = match graph
+                .outbound_ref(source)
+                .iter()
+                .find(|e| e.pool_address == *pool)
+            {
+                Some(edge) => edge,
+                None => return 0.0, // Pool vanished mid-scan
+            };
+
+            let half_depth = (edge.liquidity_usd as f64 / 2.0 * SIZED_USD_SCALE) as u64;
+            value = compute_amm_out(value, half_depth, half_depth, edge.fee_bps as u64).unwrap_or(0);
+            spot_product *= edge.exchange_rate;
+        }
+
+        spot_product * value as f64 / probe_scaled as f64
+    }
+
     /// Recursive DFS to find cycles back to start.
     #[allow(clippy::too_many_arguments)]
     fn dfs_find_cycles(
@@ -228,6 +343,7 @@ impl CycleFinder {
         current_mint: &str,
         path: Vec<String>,
         pools: Vec<String>,
+        dexes: Vec<String>,
         total_weight: f64,
         min_liquidity: u64,
         total_fees: u32,
@@ -235,9 +351,7 @@ impl CycleFinder {
         results: &mut Vec<HopCycle>,
     ) {
         // Get outbound edges from current node
-        let edges = graph.get_outbound(current_mint);
-
-        for edge in edges {
+        for edge in graph.outbound_ref(current_mint) {
             // Skip edges below liquidity threshold
             if edge.liquidity_usd < self.min_liquidity_usd {
                 continue;
@@ -265,9 +379,13 @@ impl CycleFinder {
                     let mut cycle_pools = pools.clone();
                     cycle_pools.push(edge.pool_address.clone());
 
+                    let mut cycle_dexes = dexes.clone();
+                    cycle_dexes.push(edge.dex.clone());
+
                     results.push(HopCycle {
                         path: cycle_path,
                         pool_addresses: cycle_pools,
+                        dexes: cycle_dexes,
                         theoretical_profit_pct: profit_pct,
                         min_liquidity_usd: new_liquidity,
                         total_fee_bps: new_fees.min(u16::MAX as u32) as u16,
@@ -286,12 +404,16 @@ impl CycleFinder {
                 let mut new_pools = pools.clone();
                 new_pools.push(edge.pool_address.clone());
 
+                let mut new_dexes = dexes.clone();
+                new_dexes.push(edge.dex.clone());
+
                 self.dfs_find_cycles(
                     graph,
                     start_mint,
                     &edge.target_mint,
                     new_path,
                     new_pools,
+                    new_dexes,
                     new_weight,
                     new_liquidity,
                     new_fees,
@@ -384,6 +506,11 @@ mod tests {
             best_cycle.path.last(),
             "Should start and end at same token"
         );
+        assert_eq!(
+            best_cycle.pool_addresses,
+            vec!["pool_sol_usdc", "pool_usdc_bonk", "pool_bonk_sol"]
+        );
+        assert_eq!(best_cycle.get_dexes(), vec!["RAYDIUM", "ORCA", "RAYDIUM"]);
     }
 
     #[test]
@@ -403,6 +530,82 @@ mod tests {
 
         let cycle = result.unwrap();
         assert_eq!(cycle.pool_addresses.len(), 3);
+        assert_eq!(cycle.dexes, vec!["RAYDIUM", "ORCA", "RAYDIUM"]);
+    }
+
+    #[test]
+    fn test_sized_ranks_deep_cycle_above_thin_one() {
+        let mut graph = HopGraph::new();
+        let edge = |src: &str, dst: &str, pool: &str, rate: f64, liquidity: u64| {
+            PoolEdge::new(src.to_string(), dst.to_string(), pool.to_string(), rate, 25, liquidity, 1000, "TEST")
+        };
+
+        // Deep cycle: +1% on paper, $10M per pool
+        graph.update_edge(edge("SOL", "USDC", "deep_1", 100.0, 10_000_000));
+        graph.update_edge(edge("USDC", "BONK", "deep_2", 10_000.0, 10_000_000));
+        graph.update_edge(edge("BONK", "SOL", "deep_3", 0.00000101, 10_000_000));
+
+        // Thin cycle: +5% on paper, $2k per pool
+        graph.update_edge(edge("SOL", "JUP", "thin_1", 200.0, 2_000));
+        graph.update_edge(edge("JUP", "WIF", "thin_2", 0.5, 2_000));
+        graph.update_edge(edge("WIF", "SOL", "thin_3", 0.0105, 2_000));
+
+        let finder = CycleFinder::new(4, 0.001, 1000);
+
+        // By log-rate alone the thin cycle looks best
+        let theoretical = finder.find_cycles(&graph, "SOL");
+        assert_eq!(theoretical[0].pool_addresses[0], "thin_1");
+
+        // A $1k probe eats most of the thin pools
+        let sized = finder.find_cycles_sized(&graph, "SOL", 1_000.0);
+        assert_eq!(sized.len(), 2);
+        assert_eq!(sized[0].0.pool_addresses[0], "deep_1");
+        assert!(sized[0].1 > 1.0, "deep cycle should stay profitable: {}", sized[0].1);
+        assert!(sized[1].1 < 1.0, "thin cycle should lose money: {}", sized[1].1);
+    }
+
+    #[test]
+    fn test_multi_start_dedups_rotations() {
+        let graph = create_test_graph();
+        let finder = CycleFinder::new(4, 0.001, 1000);
+
+        // SOL and USDC both reach the SOL -> USDC -> BONK triangle
+        let cycles = finder.find_cycles_multi(&graph, vec!["SOL".to_string(), "USDC".to_string()]);
+        assert_eq!(cycles.len(), 1);
+        assert_eq!(cycles[0].hop_count, 3);
+
+        assert_eq!(finder.find_cycles(&graph, "USDC").len(), 1);
+    }
+
+    #[test]
+    fn test_validate_path_picks_best_pool() {
+        let mut graph = create_test_graph();
+
+        // Second SOL -> USDC pool with a better rate, inserted after the first
+        graph.update_edge(PoolEdge::new(
+            "SOL".to_string(),
+            "USDC".to_string(),
+            "pool_sol_usdc_orca".to_string(),
+            101.0,
+            25,
+            1000000,
+            1000,
+            "ORCA",
+        ));
+
+        let finder = CycleFinder::new(4, 0.001, 1000);
+        let path = vec![
+            "SOL".to_string(),
+            "USDC".to_string(),
+            "BONK".to_string(),
+            "SOL".to_string(),
+        ];
+
+        let cycle = finder.validate_path(&graph, path).unwrap();
+        assert_eq!(cycle.pool_addresses[0], "pool_sol_usdc_orca");
+        assert_eq!(cycle.dexes[0], "ORCA");
+        // 1 SOL -> 101 USDC -> 1,010,000 BONK -> 1.0302 SOL
+        assert!((cycle.theoretical_profit_pct - 3.02).abs() < 1e-6);
     }
 
     #[test]
@@ -4,10 +4,11 @@ use bytemuck::{Pod, Zeroable};
 use std::fs::OpenOptions;
 use std::path::Path;
 use std::mem::size_of;
+use std::sync::atomic::{AtomicU64, Ordering};
 
 /// Memory Layout:
 /// [Header (64 bytes)]
-///   - Write Cursor (u64)
+///   - Write Cursor (u64, atomic: published with Release after each record)
 ///   - Magic/Version (u64)
 ///   - Reserved (48 bytes)
 /// [Ring Buffer Data]
@@ -15,33 +16,94 @@ use std::mem::size_of;
 
 const CACHE_FILE_SIZE: u64 = 10 * 1024 * 1024; // 10 MB (Plenty for tick buffer)
 const HEADER_SIZE: usize = 64;
-const MAGIC: u64 = 0xDEAD_BEEF;
+// Bump whenever the PriceUpdate layout changes so stale files are reset
+const MAGIC: u64 = 0xDEAD_BEF0;
 
 #[repr(C)]
 #[derive(Clone, Copy, Pod, Zeroable)]
 pub struct PriceUpdate {
-    // 64-byte aligned struct for Zero-Copy
-    // 8 (price) + 8 (slot) + 8 (ts) + 4 (liq) + 1 (dec) + 3 (pad) + 32 (mint) = 64 bytes
+    // 8-byte aligned struct for Zero-Copy
+    // 8 (price) + 8 (slot) + 8 (ts) + 4 (liq) + 4 (flow) + 32 (mint) + 1 (dec) + 7 (pad) = 72 bytes
     pub price: f64,         // 0-7
     pub slot: u64,          // 8-15
     pub timestamp: u64,     // 16-23
     pub liquidity: f32,     // 24-27
     pub trade_flow: f32,    // 28-31 (Signed Volume: +Buy, -Sell, 0=None)
     pub mint: [u8; 32],     // 32-63
+    pub decimals: u8,       // 64
+    pub _pad: [u8; 7],      // 65-71
+}
+
+/// A fully decoded ring-buffer record, as returned by `poll_updates_full`.
+#[pyclass]
+#[derive(Clone, Debug)]
+pub struct CachedPrice {
+    #[pyo3(get)]
+    pub mint: String,
+    #[pyo3(get)]
+    pub price: f64,
+    #[pyo3(get)]
+    pub slot: u64,
+    /// Unix milliseconds at write time
+    #[pyo3(get)]
+    pub timestamp: u64,
+    #[pyo3(get)]
+    pub liquidity: f32,
+    #[pyo3(get)]
+    pub trade_flow: f32,
+    #[pyo3(get)]
+    pub decimals: u8,
+}
+
+impl From<&PriceUpdate> for CachedPrice {
+    fn from(item: &PriceUpdate) -> Self {
+        CachedPrice {
+            mint: bs58::encode(item.mint).into_string(),
+            price: item.price,
+            slot: item.slot,
+            timestamp: item.timestamp,
+            liquidity: item.liquidity,
+            trade_flow: item.trade_flow,
+            decimals: item.decimals,
+        }
+    }
+}
+
+#[pymethods]
+impl CachedPrice {
+    fn __repr__(&self) -> String {
+        format!(
+            "CachedPrice(mint={}, price={}, slot={}, liquidity={}, decimals={})",
+            self.mint, self.price, self.slot, self.liquidity, self.decimals
+        )
+    }
 }
 
 #[repr(C)]
 #[derive(Clone, Copy, Pod, Zeroable)]
 struct CacheHeader {
+    /// Accessed only through `header_cursor` once the cache is live
     cursor: u64,
     magic: u64,
     _pad: [u8; 48],
 }
 
+/// View the header's write cursor as an atomic shared across processes.
+///
+/// # Safety
+/// `header` must point at the start of a live cache mapping. The mapping is
+/// page-aligned, so the leading u64 is suitably aligned for `AtomicU64`, and
+/// the returned reference must not outlive the mapping.
+unsafe fn header_cursor<'a>(header: *mut u8) -> &'a AtomicU64 {
+    AtomicU64::from_ptr(header as *mut u64)
+}
+
 #[pyclass]
 pub struct FlashCacheWriter {
     mmap: MmapMut,
     capacity: usize,
+    /// Updates dropped because the mint was not a valid 32-byte base58 key
+    rejected: AtomicU64,
 }
 
 #[pymethods]
@@ -74,9 +136,18 @@ impl FlashCacheWriter {
         let item_size = size_of::<PriceUpdate>();
         let capacity = (CACHE_FILE_SIZE as usize - HEADER_SIZE) / item_size;
 
-        Ok(FlashCacheWriter { mmap, capacity })
+        Ok(FlashCacheWriter {
+            mmap,
+            capacity,
+            rejected: AtomicU64::new(0),
+        })
     }
 
+    /// Write a price update to the ring buffer.
+    /// Returns true if written, false if rejected for an invalid mint
+    /// (counted in `rejected_count`).
+    /// `decimals` is the mint's token decimals (9 = SOL-native, 6 = USDC).
+    #[pyo3(signature = (mint_str, price, slot, liquidity, trade_flow, decimals=9))]
     fn push_update(
         &mut self,
         mint_str: String,
@@ -84,49 +155,66 @@ impl FlashCacheWriter {
         slot: u64,
         liquidity: f32,
         trade_flow: f32,
-    ) -> PyResult<()> {
-        let mut mint_bytes = [0u8; 32];
-        
-        let vec = bs58::decode(mint_str)
+        decimals: u8,
+    ) -> bool {
+        let mint_bytes: [u8; 32] = match bs58::decode(mint_str)
             .into_vec()
-            .map_err(|e| PyErr::new::<pyo3::exceptions::PyValueError, _>(e.to_string()))?;
-            
-        if vec.len() == 32 {
-            mint_bytes.copy_from_slice(&vec);
-        } else {
-             return Ok(());
-        }
+            .ok()
+            .and_then(|vec| vec.try_into().ok())
+        {
+            Some(bytes) => bytes,
+            None => {
+                self.rejected.fetch_add(1, Ordering::Relaxed);
+                return false;
+            }
+        };
 
-        // Fix: Use split_at_mut to avoid double mutable borrow of mmap
-        let (header_slice, data_slice) = self.mmap.split_at_mut(HEADER_SIZE);
-        let header: &mut CacheHeader = bytemuck::from_bytes_mut(header_slice);
-        
-        let cursor = header.cursor;
-        
-        let idx = (cursor as usize) % self.capacity;
-        // Offset is now relative to the data_slice, not the start of mmap
-        let offset = idx * size_of::<PriceUpdate>();
-        
         use std::time::{SystemTime, UNIX_EPOCH};
         let ts = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_millis() as u64;
 
-        let update = PriceUpdate {
+        self.write_record(&PriceUpdate {
             mint: mint_bytes,
             price,
             slot,
             timestamp: ts,
             liquidity,
             trade_flow,
-        };
+            decimals,
+            _pad: [0; 7],
+        });
+
+        true
+    }
+
+    /// Number of updates rejected for an invalid mint.
+    #[getter]
+    fn rejected_count(&self) -> u64 {
+        self.rejected.load(Ordering::Relaxed)
+    }
+}
+
+impl FlashCacheWriter {
+    /// Append a record to the ring and publish it to readers.
+    fn write_record(&mut self, update: &PriceUpdate) {
+        // Fix: Use split_at_mut to avoid double mutable borrow of mmap
+        let (header_slice, data_slice) = self.mmap.split_at_mut(HEADER_SIZE);
+        // Safety: header_slice is the start of our mapping, which outlives `cursor_atomic`
+        let cursor_atomic = unsafe { header_cursor(header_slice.as_mut_ptr()) };
+        
+        // Single writer: nobody else advances the cursor
+        let cursor = cursor_atomic.load(Ordering::Relaxed);
+        
+        let idx = (cursor as usize) % self.capacity;
+        // Offset is now relative to the data_slice, not the start of mmap
+        let offset = idx * size_of::<PriceUpdate>();
 
         if offset + size_of::<PriceUpdate>() <= data_slice.len() {
              let dest = &mut data_slice[offset..offset + size_of::<PriceUpdate>()];
-             dest.copy_from_slice(bytemuck::bytes_of(&update));
+             dest.copy_from_slice(bytemuck::bytes_of(update));
         }
 
-        header.cursor = cursor + 1;
-
-        Ok(())
+        // Publish only after the record is fully written; pairs with the reader's Acquire
+        cursor_atomic.store(cursor + 1, Ordering::Release);
     }
 }
 
@@ -170,16 +258,34 @@ impl FlashCacheReader {
     /// Read all new updates since last poll.
     /// Returns: List of (mint, price, slot, liquidity, trade_flow)
     fn poll_updates(&mut self) -> PyResult<Vec<(String, f64, u64, f32, f32)>> {
-        let header_slice = &self.mmap[0..size_of::<CacheHeader>()];
-        let header: &CacheHeader = bytemuck::from_bytes(header_slice);
-        
-        let current_cursor = header.cursor;
-        
-        if current_cursor == self.last_cursor {
-            return Ok(Vec::new());
-        }
+        Ok(self
+            .drain_new()
+            .map(|item| {
+                // Decode mint
+                let mint_str = bs58::encode(item.mint).into_string();
+                // Return 5-tuple: (mint, price, slot, liquidity, trade_flow)
+                (mint_str, item.price, item.slot, item.liquidity, item.trade_flow)
+            })
+            .collect())
+    }
+
+    /// Read all new updates since last poll with every stored field,
+    /// including timestamp and decimals.
+    fn poll_updates_full(&mut self) -> PyResult<Vec<CachedPrice>> {
+        Ok(self.drain_new().map(CachedPrice::from).collect())
+    }
+}
+
+impl FlashCacheReader {
+    /// Advance past every record published since the last poll and yield them
+    /// in write order.
+    fn drain_new(&mut self) -> impl Iterator<Item = &PriceUpdate> + '_ {
+        // Safety: the mapping starts with the header and outlives this call.
+        // Acquire pairs with the writer's Release so every record below the
+        // cursor is fully visible.
+        let current_cursor =
+            unsafe { header_cursor(self.mmap.as_mut_ptr()) }.load(Ordering::Acquire);
 
-        let mut updates = Vec::new();
         // Don't read more than capacity (if we lagged too far, just read last capacity)
         let backlog = current_cursor - self.last_cursor;
         let start_read = if backlog > self.capacity as u64 {
@@ -187,20 +293,142 @@ impl FlashCacheReader {
         } else {
             self.last_cursor
         };
+        self.last_cursor = current_cursor;
 
-        for i in start_read..current_cursor {
-            let idx = (i as usize) % self.capacity;
+        let capacity = self.capacity;
+        let mmap = &self.mmap;
+        (start_read..current_cursor).map(move |i| {
+            let idx = (i as usize) % capacity;
             let offset = HEADER_SIZE + (idx * size_of::<PriceUpdate>());
-            let item_slice = &self.mmap[offset..offset + size_of::<PriceUpdate>()];
-            let item: &PriceUpdate = bytemuck::from_bytes(item_slice);
+            bytemuck::from_bytes(&mmap[offset..offset + size_of::<PriceUpdate>()])
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_cache_path(name: &str) -> String {
+        let path = std::env::temp_dir().join(format!(
+            "phantom_flash_cache_{}_{}.bin",
+            name,
+            std::process::id()
+        ));
+        let _ = std::fs::remove_file(&path);
+        path.to_string_lossy().into_owned()
+    }
 
-            // Decode mint
-            let mint_str = bs58::encode(item.mint).into_string();
-            // Return 5-tuple: (mint, price, slot, liquidity, trade_flow)
-            updates.push((mint_str, item.price, item.slot, item.liquidity, item.trade_flow));
+    #[test]
+    fn test_concurrent_reader_never_sees_torn_update() {
+        let path = temp_cache_path("stress");
+        let mut writer = FlashCacheWriter::new(path.clone()).unwrap();
+        let mut reader = FlashCacheReader::new(path.clone()).unwrap();
+        let mint = bs58::encode([7u8; 32]).into_string();
+
+        // Stay under capacity so the writer never laps the reader
+        const N: u64 = 50_000;
+        let writer_thread = std::thread::spawn(move || {
+            for slot in 1..=N {
+                // Every field derives from `slot` so a torn record is detectable
+                assert!(writer.push_update(
+                    mint.clone(),
+                    slot as f64,
+                    slot,
+                    slot as f32,
+                    -(slot as f32),
+                    9
+                ));
+            }
+        });
+
+        let mut seen = 0u64;
+        while seen < N {
+            for (mint, price, slot, liquidity, trade_flow) in reader.poll_updates().unwrap() {
+                seen += 1;
+                assert_eq!(slot, seen, "records must arrive in order");
+                assert_eq!(price, slot as f64);
+                assert_eq!(liquidity, slot as f32);
+                assert_eq!(trade_flow, -(slot as f32));
+                assert_eq!(mint, bs58::encode([7u8; 32]).into_string());
+            }
         }
 
-        self.last_cursor = current_cursor;
-        Ok(updates)
+        writer_thread.join().unwrap();
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_invalid_mint_is_rejected_and_counted() {
+        let path = temp_cache_path("rejected");
+        let mut writer = FlashCacheWriter::new(path.clone()).unwrap();
+        let mut reader = FlashCacheReader::new(path.clone()).unwrap();
+        let mint = bs58::encode([3u8; 32]).into_string();
+
+        assert!(writer.push_update(mint.clone(), 1.5, 10, 0.0, 0.0, 9));
+        // Not base58 at all, and valid base58 of the wrong length
+        assert!(!writer.push_update("not-a-mint!".to_string(), 2.0, 11, 0.0, 0.0, 9));
+        assert!(!writer.push_update("abc".to_string(), 2.0, 12, 0.0, 0.0, 9));
+
+        assert_eq!(writer.rejected_count(), 2);
+        let updates = reader.poll_updates().unwrap();
+        assert_eq!(updates.len(), 1);
+        assert_eq!(updates[0].0, mint);
+
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_full_poll_round_trips_every_field() {
+        let path = temp_cache_path("full");
+        let mut writer = FlashCacheWriter::new(path.clone()).unwrap();
+        let mut reader = FlashCacheReader::new(path.clone()).unwrap();
+        let mint = [9u8; 32];
+
+        writer.write_record(&PriceUpdate {
+            price: 0.9998,
+            slot: 42,
+            timestamp: 1_700_000_000_123,
+            liquidity: 12_345.5,
+            trade_flow: -250.0,
+            mint,
+            decimals: 6,
+            _pad: [0; 7],
+        });
+
+        let updates = reader.poll_updates_full().unwrap();
+        assert_eq!(updates.len(), 1);
+        let got = &updates[0];
+        assert_eq!(got.mint, bs58::encode(mint).into_string());
+        assert_eq!(got.price, 0.9998);
+        assert_eq!(got.slot, 42);
+        assert_eq!(got.timestamp, 1_700_000_000_123);
+        assert_eq!(got.liquidity, 12_345.5);
+        assert_eq!(got.trade_flow, -250.0);
+        assert_eq!(got.decimals, 6);
+
+        // Both poll flavours share the cursor
+        assert!(reader.poll_updates().unwrap().is_empty());
+
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_push_update_stores_caller_decimals() {
+        let path = temp_cache_path("decimals");
+        let mut writer = FlashCacheWriter::new(path.clone()).unwrap();
+        let mut reader = FlashCacheReader::new(path.clone()).unwrap();
+        let usdc = bs58::encode([5u8; 32]).into_string();
+        let sol = bs58::encode([6u8; 32]).into_string();
+
+        assert!(writer.push_update(usdc.clone(), 1.0, 100, 5_000.0, 0.0, 6));
+        assert!(writer.push_update(sol.clone(), 150.0, 101, 9_000.0, 0.0, 9));
+
+        let updates = reader.poll_updates_full().unwrap();
+        assert_eq!(updates.len(), 2);
+        assert_eq!((updates[0].mint.as_str(), updates[0].decimals), (usdc.as_str(), 6));
+        assert_eq!((updates[1].mint.as_str(), updates[1].decimals), (sol.as_str(), 9));
+
+        let _ = std::fs::remove_file(&path);
     }
 }
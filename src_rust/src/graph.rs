@@ -12,7 +12,11 @@
 // ------------------------------------------------------------------------
 
 use pyo3::prelude::*;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+/// pool_index key suffix for the b→a edge of a bidirectional pool
+const REVERSE_KEY_SUFFIX: &str = ":rev";
 
 /// Represents a directed edge (pool) in the token graph.
 /// Each edge connects two tokens via a liquidity pool.
@@ -55,6 +59,11 @@ pub struct PoolEdge {
     /// DEX identifier (e.g., "RAYDIUM", "ORCA", "METEORA")
     #[pyo3(get, set)]
     pub dex: String,
+
+    /// True for the b→a edge inserted by `update_pool_bidirectional`;
+    /// it shares `pool_address` with the a→b edge
+    #[pyo3(get)]
+    pub is_reverse: bool,
 }
 
 #[pymethods]
@@ -99,6 +108,7 @@ impl PoolEdge {
             liquidity_usd,
             last_update_slot,
             dex: dex.to_string(),
+            is_reverse: false,
         }
     }
 
@@ -135,20 +145,26 @@ impl PoolEdge {
 /// - Fast edge updates (O(1) average via HashMap)
 /// - Fast outbound edge lookup (O(1) for adjacency list)
 /// - Memory efficiency (edges stored once, not duplicated)
+/// - O(1) snapshots: state is Arc-shared and copied on write, per source
 #[pyclass]
+#[derive(Clone)]
 pub struct HopGraph {
     /// Adjacency list: source_mint -> Vec<PoolEdge>
-    edges: HashMap<String, Vec<PoolEdge>>,
+    edges: Arc<HashMap<String, Arc<Vec<PoolEdge>>>>,
 
     /// Pool lookup: pool_address -> (source_mint, index in edges vec)
-    /// Enables O(1) updates when a pool price changes
-    pool_index: HashMap<String, (String, usize)>,
+    /// Enables O(1) updates when a pool price changes. Reverse edges of
+    /// bidirectional pools are keyed "<pool_address>:rev".
+    pool_index: Arc<HashMap<String, (String, usize)>>,
 
     /// All unique token mints (nodes)
-    nodes: std::collections::HashSet<String>,
+    nodes: Arc<HashSet<String>>,
 
     /// Total edge count (for stats)
     edge_count: usize,
+
+    /// Set on snapshots; Python-side mutation raises
+    frozen: bool,
 }
 
 #[pymethods]
@@ -156,52 +172,22 @@ impl HopGraph {
     #[new]
     pub fn new() -> Self {
         Self {
-            edges: HashMap::new(),
-            pool_index: HashMap::new(),
-            nodes: std::collections::HashSet::new(),
+            edges: Arc::new(HashMap::new()),
+            pool_index: Arc::new(HashMap::new()),
+            nodes: Arc::new(HashSet::new()),
             edge_count: 0,
+            frozen: false,
         }
     }
 
-    /// Update or insert an edge from WSS price feed.
-    /// If the pool already exists, update it in place. Otherwise, add new edge.
-    pub fn update_edge(&mut self, edge: PoolEdge) {
-        // Track nodes
-        self.nodes.insert(edge.source_mint.clone());
-        self.nodes.insert(edge.target_mint.clone());
-
-        // Check if pool already exists
-        if let Some((source, idx)) = self.pool_index.get(&edge.pool_address) {
-            // Update existing edge
-            if let Some(edges) = self.edges.get_mut(source) {
-                if let Some(existing) = edges.get_mut(*idx) {
-                    existing.exchange_rate = edge.exchange_rate;
-                    existing.weight = edge.weight;
-                    existing.liquidity_usd = edge.liquidity_usd;
-                    existing.last_update_slot = edge.last_update_slot;
-                    existing.fee_bps = edge.fee_bps;
-                    return;
-                }
-            }
-        }
-
-        // New edge - add to adjacency list
-        let source = edge.source_mint.clone();
-        let pool_addr = edge.pool_address.clone();
-
-        let edges_vec = self.edges.entry(source.clone()).or_insert_with(Vec::new);
-        let idx = edges_vec.len();
-        edges_vec.push(edge);
-
-        // Update pool index
-        self.pool_index.insert(pool_addr, (source, idx));
-        self.edge_count += 1;
-    }
-
     /// Get all outbound edges from a token.
     /// Returns empty vec if token not in graph.
+    /// Clones every edge; Rust callers should use `outbound_ref`.
     pub fn get_outbound(&self, mint: &str) -> Vec<PoolEdge> {
-        self.edges.get(mint).cloned().unwrap_or_default()
+        self.edges
+            .get(mint)
+            .map(|edges| edges.to_vec())
+            .unwrap_or_default()
     }
 
     /// Get a specific edge by pool address.
@@ -242,53 +228,122 @@ impl HopGraph {
             .unwrap_or_default()
     }
 
-    /// Prune stale edges older than the given slot threshold.
-    /// Returns the number of edges pruned.
-    pub fn prune_stale(&mut self, min_slot: u64) -> usize {
-        let mut pruned = 0;
-        let mut pools_to_remove: Vec<String> = Vec::new();
+    /// Get every pool connecting `source_mint` to `target_mint`, across all
+    /// DEXes, sorted by exchange rate (best first).
+    pub fn get_edges_between(&self, source_mint: &str, target_mint: &str) -> Vec<PoolEdge> {
+        let mut edges: Vec<PoolEdge> = self
+            .outbound_ref(source_mint)
+            .iter()
+            .filter(|e| e.target_mint == target_mint)
+            .cloned()
+            .collect();
+        edges.sort_by(|a, b| b.exchange_rate.total_cmp(&a.exchange_rate));
+        edges
+    }
 
-        for (source, edges) in self.edges.iter_mut() {
-            let before_len = edges.len();
+    /// Edge freshness as (min_slot, max_slot, avg_slot) over all edges.
+    /// All zero for an empty graph.
+    pub fn freshness_stats(&self) -> (u64, u64, f64) {
+        let mut min_slot = u64::MAX;
+        let mut max_slot = 0;
+        let mut sum: u128 = 0;
+        let mut count: u64 = 0;
+        for edge in self.edges.values().flat_map(|edges| edges.iter()) {
+            min_slot = min_slot.min(edge.last_update_slot);
+            max_slot = max_slot.max(edge.last_update_slot);
+            sum += edge.last_update_slot as u128;
+            count += 1;
+        }
 
-            // Collect pool addresses of stale edges
-            for edge in edges.iter() {
-                if edge.is_stale(min_slot) {
-                    pools_to_remove.push(edge.pool_address.clone());
-                }
-            }
+        if count == 0 {
+            return (0, 0, 0.0);
+        }
+        (min_slot, max_slot, sum as f64 / count as f64)
+    }
 
-            // Remove stale edges
-            edges.retain(|e| !e.is_stale(min_slot));
+    /// Number of edges `prune_stale(min_slot)` would remove, without removing them.
+    pub fn count_stale(&self, min_slot: u64) -> usize {
+        self.edges
+            .values()
+            .flat_map(|edges| edges.iter())
+            .filter(|e| e.is_stale(min_slot))
+            .count()
+    }
 
-            let removed = before_len - edges.len();
-            pruned += removed;
-            self.edge_count -= removed;
+    /// Frozen copy of the graph for a consistent scan.
+    /// O(1): state is shared with the live graph and copied on write, so
+    /// updates to the live graph during a scan never show through. The
+    /// snapshot itself rejects mutation from Python.
+    pub fn snapshot(&self) -> HopGraph {
+        let mut frozen = self.clone();
+        frozen.frozen = true;
+        frozen
+    }
 
-            // Update pool index for remaining edges (indices may have shifted)
-            for (idx, edge) in edges.iter().enumerate() {
-                self.pool_index
-                    .insert(edge.pool_address.clone(), (source.clone(), idx));
-            }
-        }
+    /// True for graphs returned by `snapshot`.
+    #[getter]
+    pub fn is_frozen(&self) -> bool {
+        self.frozen
+    }
 
-        // Remove pool index entries for pruned edges
-        for pool_addr in pools_to_remove {
-            self.pool_index.remove(&pool_addr);
-        }
+    /// Update or insert an edge from WSS price feed.
+    #[pyo3(name = "update_edge")]
+    fn py_update_edge(&mut self, edge: PoolEdge) -> PyResult<()> {
+        self.ensure_mutable()?;
+        self.update_edge(edge);
+        Ok(())
+    }
 
-        // Clean up empty source entries
-        self.edges.retain(|_, edges| !edges.is_empty());
+    /// Insert or update both directions of an AMM pool.
+    #[pyo3(name = "update_pool_bidirectional", signature = (
+        mint_a,
+        mint_b,
+        pool_address,
+        rate_a_to_b,
+        fee_bps = 25,
+        liquidity_usd = 0,
+        slot = 0,
+        dex = "UNKNOWN"
+    ))]
+    #[allow(clippy::too_many_arguments)]
+    fn py_update_pool_bidirectional(
+        &mut self,
+        mint_a: String,
+        mint_b: String,
+        pool_address: String,
+        rate_a_to_b: f64,
+        fee_bps: u16,
+        liquidity_usd: u64,
+        slot: u64,
+        dex: &str,
+    ) -> PyResult<()> {
+        self.ensure_mutable()?;
+        self.update_pool_bidirectional(
+            mint_a,
+            mint_b,
+            pool_address,
+            rate_a_to_b,
+            fee_bps,
+            liquidity_usd,
+            slot,
+            dex,
+        );
+        Ok(())
+    }
 
-        pruned
+    /// Prune stale edges older than the given slot threshold.
+    #[pyo3(name = "prune_stale")]
+    fn py_prune_stale(&mut self, min_slot: u64) -> PyResult<usize> {
+        self.ensure_mutable()?;
+        Ok(self.prune_stale(min_slot))
     }
 
     /// Clear all edges and nodes.
-    pub fn clear(&mut self) {
-        self.edges.clear();
-        self.pool_index.clear();
-        self.nodes.clear();
-        self.edge_count = 0;
+    #[pyo3(name = "clear")]
+    fn py_clear(&mut self) -> PyResult<()> {
+        self.ensure_mutable()?;
+        self.clear();
+        Ok(())
     }
 
     /// Get statistics about the graph.
@@ -311,6 +366,177 @@ impl HopGraph {
     }
 }
 
+impl HopGraph {
+    /// Update or insert an edge from WSS price feed.
+    /// If the pool already exists, update it in place. Otherwise, add new edge.
+    pub fn update_edge(&mut self, edge: PoolEdge) {
+        // Track nodes
+        let nodes = Arc::make_mut(&mut self.nodes);
+        nodes.insert(edge.source_mint.clone());
+        nodes.insert(edge.target_mint.clone());
+
+        let key = index_key(&edge);
+
+        // Check if pool already exists
+        if let Some((source, idx)) = self.pool_index.get(&key) {
+            // Update existing edge
+            if let Some(edges) = Arc::make_mut(&mut self.edges).get_mut(source) {
+                if let Some(existing) = Arc::make_mut(edges).get_mut(*idx) {
+                    existing.exchange_rate = edge.exchange_rate;
+                    existing.weight = edge.weight;
+                    existing.liquidity_usd = edge.liquidity_usd;
+                    existing.last_update_slot = edge.last_update_slot;
+                    existing.fee_bps = edge.fee_bps;
+                    return;
+                }
+            }
+        }
+
+        // New edge - add to adjacency list
+        let source = edge.source_mint.clone();
+
+        let edges_vec = Arc::make_mut(
+            Arc::make_mut(&mut self.edges)
+                .entry(source.clone())
+                .or_default(),
+        );
+        let idx = edges_vec.len();
+        edges_vec.push(edge);
+
+        // Update pool index
+        Arc::make_mut(&mut self.pool_index).insert(key, (source, idx));
+        self.edge_count += 1;
+    }
+
+    /// Insert or update both directions of an AMM pool.
+    /// `rate_a_to_b` is the post-fee rate, as in `PoolEdge.exchange_rate`;
+    /// the b→a rate is the inverse of the implied pre-fee price, with the fee
+    /// applied again, so a round trip loses the fee twice.
+    /// The reverse edge is retrievable via `get_edge("<pool_address>:rev")`.
+    #[allow(clippy::too_many_arguments)]
+    pub fn update_pool_bidirectional(
+        &mut self,
+        mint_a: String,
+        mint_b: String,
+        pool_address: String,
+        rate_a_to_b: f64,
+        fee_bps: u16,
+        liquidity_usd: u64,
+        slot: u64,
+        dex: &str,
+    ) {
+        let fee_factor = 1.0 - fee_bps as f64 / 10_000.0;
+        let rate_b_to_a = if rate_a_to_b > 0.0 {
+            fee_factor * fee_factor / rate_a_to_b
+        } else {
+            0.0 // Dead pool: PoolEdge::new gives both directions infinite weight
+        };
+
+        let forward = PoolEdge::new(
+            mint_a.clone(),
+            mint_b.clone(),
+            pool_address.clone(),
+            rate_a_to_b,
+            fee_bps,
+            liquidity_usd,
+            slot,
+            dex,
+        );
+        let mut reverse = PoolEdge::new(
+            mint_b,
+            mint_a,
+            pool_address,
+            rate_b_to_a,
+            fee_bps,
+            liquidity_usd,
+            slot,
+            dex,
+        );
+        reverse.is_reverse = true;
+
+        self.update_edge(forward);
+        self.update_edge(reverse);
+    }
+
+    /// Prune stale edges older than the given slot threshold.
+    /// Returns the number of edges pruned.
+    pub fn prune_stale(&mut self, min_slot: u64) -> usize {
+        let mut pruned = 0;
+
+        // Remove stale edges, copying only sources a snapshot still shares
+        if !self
+            .edges
+            .values()
+            .flat_map(|edges| edges.iter())
+            .any(|e| e.is_stale(min_slot))
+        {
+            return 0;
+        }
+        let all_edges = Arc::make_mut(&mut self.edges);
+        for edges in all_edges.values_mut() {
+            if edges.iter().any(|e| e.is_stale(min_slot)) {
+                let edges = Arc::make_mut(edges);
+                let before_len = edges.len();
+                edges.retain(|e| !e.is_stale(min_slot));
+                pruned += before_len - edges.len();
+            }
+        }
+        self.edge_count -= pruned;
+
+        // Clean up empty source entries
+        all_edges.retain(|_, edges| !edges.is_empty());
+
+        // Indices shift on retain, so rebuild the pool index once from scratch
+        self.rebuild_pool_index();
+
+        pruned
+    }
+
+    /// Clear all edges and nodes.
+    pub fn clear(&mut self) {
+        // Fresh maps rather than clearing in place, which would copy shared state
+        self.edges = Arc::default();
+        self.pool_index = Arc::default();
+        self.nodes = Arc::default();
+        self.edge_count = 0;
+    }
+
+    fn ensure_mutable(&self) -> PyResult<()> {
+        if self.frozen {
+            return Err(PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(
+                "HopGraph snapshot is read-only",
+            ));
+        }
+        Ok(())
+    }
+
+    /// Borrow the outbound edges from a token without cloning them.
+    /// Empty slice if token not in graph.
+    pub fn outbound_ref(&self, mint: &str) -> &[PoolEdge] {
+        self.edges.get(mint).map_or(&[], |edges| edges.as_slice())
+    }
+
+    /// Recompute pool_address -> (source_mint, index) from the adjacency list.
+    fn rebuild_pool_index(&mut self) {
+        let mut pool_index = HashMap::with_capacity(self.edge_count);
+        for (source, edges) in self.edges.iter() {
+            for (idx, edge) in edges.iter().enumerate() {
+                pool_index.insert(index_key(edge), (source.clone(), idx));
+            }
+        }
+        self.pool_index = Arc::new(pool_index);
+    }
+}
+
+/// Key under which `edge` is stored in `HopGraph.pool_index`
+fn index_key(edge: &PoolEdge) -> String {
+    if edge.is_reverse {
+        format!("{}{}", edge.pool_address, REVERSE_KEY_SUFFIX)
+    } else {
+        edge.pool_address.clone()
+    }
+}
+
 impl Default for HopGraph {
     fn default() -> Self {
         Self::new()
@@ -447,4 +673,301 @@ mod tests {
         assert!(graph.get_edge("pool1").is_none());
         assert!(graph.get_edge("pool2").is_some());
     }
+
+    #[test]
+    fn test_prune_middle_edge_keeps_pool_index_consistent() {
+        let mut graph = HopGraph::new();
+        let slots = [
+            ("pool_a", 2000),
+            ("pool_b", 1000),
+            ("pool_c", 2000),
+            ("pool_d", 2000),
+        ];
+        for (i, (pool, slot)) in slots.iter().enumerate() {
+            graph.update_edge(PoolEdge::new(
+                "SOL".to_string(),
+                format!("TOKEN{}", i),
+                pool.to_string(),
+                1.0 + i as f64,
+                25,
+                1000,
+                *slot,
+                "RAYDIUM",
+            ));
+        }
+        // A second source whose edges must be untouched
+        graph.update_edge(PoolEdge::new(
+            "USDC".to_string(),
+            "SOL".to_string(),
+            "pool_e".to_string(),
+            0.01,
+            25,
+            1000,
+            1000,
+            "ORCA",
+        ));
+        graph.update_edge(PoolEdge::new(
+            "USDC".to_string(),
+            "BONK".to_string(),
+            "pool_f".to_string(),
+            50.0,
+            25,
+            1000,
+            2000,
+            "ORCA",
+        ));
+
+        assert_eq!(graph.prune_stale(1500), 2);
+        assert_eq!(graph.edge_count(), 4);
+        assert!(graph.get_edge("pool_b").is_none());
+        assert!(graph.get_edge("pool_e").is_none());
+
+        for (pool, target, rate) in [
+            ("pool_a", "TOKEN0", 1.0),
+            ("pool_c", "TOKEN2", 3.0),
+            ("pool_d", "TOKEN3", 4.0),
+            ("pool_f", "BONK", 50.0),
+        ] {
+            let edge = graph.get_edge(pool).unwrap();
+            assert_eq!(edge.pool_address, pool);
+            assert_eq!(edge.target_mint, target);
+            assert_eq!(edge.exchange_rate, rate);
+        }
+
+        // Updates after pruning still land on the right edge
+        graph.update_edge(PoolEdge::new(
+            "SOL".to_string(),
+            "TOKEN3".to_string(),
+            "pool_d".to_string(),
+            9.0,
+            25,
+            1000,
+            3000,
+            "RAYDIUM",
+        ));
+        assert_eq!(graph.get_edge("pool_d").unwrap().exchange_rate, 9.0);
+        assert_eq!(graph.get_edge("pool_c").unwrap().exchange_rate, 3.0);
+        assert_eq!(graph.edge_count(), 4);
+    }
+
+    #[test]
+    fn test_bidirectional_pool_inserts_both_directions() {
+        let mut graph = HopGraph::new();
+        graph.update_pool_bidirectional(
+            "SOL".to_string(),
+            "USDC".to_string(),
+            "pool1".to_string(),
+            150.0,
+            30,
+            1_000_000,
+            1000,
+            "RAYDIUM",
+        );
+
+        assert_eq!(graph.edge_count(), 2);
+        let forward = graph.get_edge("pool1").unwrap();
+        let reverse = graph.get_edge("pool1:rev").unwrap();
+        assert_eq!(
+            (forward.source_mint.as_str(), forward.target_mint.as_str()),
+            ("SOL", "USDC")
+        );
+        assert_eq!(
+            (reverse.source_mint.as_str(), reverse.target_mint.as_str()),
+            ("USDC", "SOL")
+        );
+        assert_eq!(reverse.pool_address, "pool1");
+        assert!(reverse.is_reverse && !forward.is_reverse);
+
+        // Round trip pays the 0.3% fee in each direction
+        let round_trip = forward.exchange_rate * reverse.exchange_rate;
+        assert!((round_trip - 0.997 * 0.997).abs() < 1e-12);
+
+        // Re-quoting updates both edges in place
+        graph.update_pool_bidirectional(
+            "SOL".to_string(),
+            "USDC".to_string(),
+            "pool1".to_string(),
+            160.0,
+            30,
+            1_000_000,
+            1001,
+            "RAYDIUM",
+        );
+        assert_eq!(graph.edge_count(), 2);
+        assert_eq!(graph.get_edge("pool1:rev").unwrap().last_update_slot, 1001);
+
+        // The two-hop loop through the single pool is visible to cycle validation
+        let finder = crate::cycle_finder::CycleFinder::new(3, -1.0, 0);
+        let cycle = finder
+            .validate_path(&graph, vec!["SOL".into(), "USDC".into(), "SOL".into()])
+            .unwrap();
+        assert_eq!(cycle.pool_addresses, ["pool1", "pool1"]);
+        assert!((cycle.theoretical_profit_pct - (0.997f64 * 0.997 - 1.0) * 100.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_outbound_ref_scans_large_graph_without_copying() {
+        // 50 tokens in a ring, each with 10 parallel pools to the next: 500 edges
+        let mut graph = HopGraph::new();
+        for i in 0..50 {
+            for p in 0..10 {
+                graph.update_edge(PoolEdge::new(
+                    format!("T{}", i),
+                    format!("T{}", (i + 1) % 50),
+                    format!("pool_{}_{}", i, p),
+                    1.0,
+                    25,
+                    10_000,
+                    1000,
+                    "RAYDIUM",
+                ));
+            }
+        }
+        assert_eq!(graph.edge_count(), 500);
+
+        // Every lookup borrows the graph's own storage: no per-call allocation
+        let mints: Vec<String> = (0..50).map(|i| format!("T{}", i)).collect();
+        let first = graph.outbound_ref("T0").as_ptr();
+        let mut visited = 0;
+        for _ in 0..1_000 {
+            for mint in &mints {
+                visited += graph.outbound_ref(mint).len();
+            }
+            assert_eq!(graph.outbound_ref("T0").as_ptr(), first);
+        }
+        assert_eq!(visited, 500 * 1_000);
+
+        // Same edges as the cloning Python accessor
+        let cloned = graph.get_outbound("T7");
+        let borrowed = graph.outbound_ref("T7");
+        assert_eq!(cloned.len(), borrowed.len());
+        for (a, b) in cloned.iter().zip(borrowed) {
+            assert_eq!(a.pool_address, b.pool_address);
+        }
+        assert!(graph.outbound_ref("MISSING").is_empty());
+    }
+
+    #[test]
+    fn test_snapshot_is_isolated_from_live_updates() {
+        use crate::cycle_finder::CycleFinder;
+
+        let edge = |from: &str, to: &str, pool: &str, rate: f64| {
+            PoolEdge::new(
+                from.into(),
+                to.into(),
+                pool.into(),
+                rate,
+                25,
+                1_000_000,
+                1000,
+                "RAYDIUM",
+            )
+        };
+        let mut graph = HopGraph::new();
+        graph.update_edge(edge("SOL", "USDC", "pool_a", 100.0));
+        graph.update_edge(edge("USDC", "BONK", "pool_b", 50_000.0));
+        graph.update_edge(edge("BONK", "SOL", "pool_c", 0.000000202));
+
+        let snapshot = graph.snapshot();
+        assert!(snapshot.is_frozen() && !graph.is_frozen());
+        let finder = CycleFinder::new(3, 0.001, 1000);
+        let before: Vec<(Vec<String>, f64)> = finder
+            .find_cycles(&snapshot, "SOL")
+            .into_iter()
+            .map(|c| (c.pool_addresses, c.theoretical_profit_pct))
+            .collect();
+        assert_eq!(before.len(), 1);
+
+        // Live feed keeps moving: kill the arb, add a new route, prune
+        graph.update_edge(edge("BONK", "SOL", "pool_c", 0.000000190));
+        graph.update_edge(edge("USDC", "JUP", "pool_d", 2.0));
+        graph.update_edge(edge("JUP", "SOL", "pool_e", 0.004));
+        graph.prune_stale(0);
+        assert!(CycleFinder::new(3, 0.001, 1000)
+            .find_cycles(&graph, "SOL")
+            .is_empty());
+
+        let after: Vec<(Vec<String>, f64)> = finder
+            .find_cycles(&snapshot, "SOL")
+            .into_iter()
+            .map(|c| (c.pool_addresses, c.theoretical_profit_pct))
+            .collect();
+        assert_eq!(after, before);
+        assert_eq!(snapshot.edge_count(), 3);
+        assert_eq!(
+            snapshot.get_edge("pool_c").unwrap().exchange_rate,
+            0.000000202
+        );
+
+        // Snapshots are read-only from Python
+        let mut snapshot = snapshot;
+        assert!(snapshot
+            .py_update_edge(edge("SOL", "USDC", "pool_a", 1.0))
+            .is_err());
+        assert!(snapshot.py_clear().is_err());
+        assert!(graph
+            .py_update_edge(edge("SOL", "USDC", "pool_a", 1.0))
+            .is_ok());
+    }
+
+    #[test]
+    fn test_freshness_stats_and_count_stale() {
+        let mut graph = HopGraph::new();
+        assert_eq!(graph.freshness_stats(), (0, 0, 0.0));
+
+        for (i, slot) in [100, 200, 300].into_iter().enumerate() {
+            graph.update_edge(PoolEdge::new(
+                "SOL".to_string(),
+                format!("TOKEN{}", i),
+                format!("pool{}", i),
+                1.0,
+                25,
+                1000,
+                slot,
+                "RAYDIUM",
+            ));
+        }
+
+        assert_eq!(graph.freshness_stats(), (100, 300, 200.0));
+        assert_eq!(graph.count_stale(100), 0);
+        assert_eq!(graph.count_stale(250), 2);
+        assert_eq!(graph.count_stale(301), 3);
+        // Counting doesn't prune
+        assert_eq!(graph.edge_count(), 3);
+        assert_eq!(graph.prune_stale(250), 2);
+    }
+
+    #[test]
+    fn test_get_edges_between_returns_all_venues_by_rate() {
+        let mut graph = HopGraph::new();
+        for (pool, rate, dex) in [("ray_pool", 99.5, "RAYDIUM"), ("orca_pool", 100.2, "ORCA")] {
+            graph.update_edge(PoolEdge::new(
+                "SOL".to_string(),
+                "USDC".to_string(),
+                pool.to_string(),
+                rate,
+                25,
+                1000,
+                10,
+                dex,
+            ));
+        }
+        // Unrelated pair from the same source is excluded
+        graph.update_edge(PoolEdge::new(
+            "SOL".to_string(),
+            "BONK".to_string(),
+            "bonk_pool".to_string(),
+            1e6,
+            25,
+            1000,
+            10,
+            "RAYDIUM",
+        ));
+
+        let edges = graph.get_edges_between("SOL", "USDC");
+        let pools: Vec<&str> = edges.iter().map(|e| e.pool_address.as_str()).collect();
+        assert_eq!(pools, vec!["orca_pool", "ray_pool"]);
+        assert!(graph.get_edges_between("USDC", "SOL").is_empty());
+        assert!(graph.get_edges_between("MISSING", "USDC").is_empty());
+    }
 }
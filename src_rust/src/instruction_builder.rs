@@ -4,6 +4,7 @@
 // Phase 1: Raydium AMM V4
 // Phase 2: Orca Whirlpool
 // Phase 3: Meteora DLMM
+// Phase 5: Token account helpers (wrap/unwrap SOL)
 // ------------------------------------------------------------------------
 
 use pyo3::prelude::*;
@@ -26,9 +27,21 @@ const ORCA_WHIRLPOOL: &str = "whirLbMiicVdio4qvUfM5KAg6Ct8VwpYzGff3uctyCc";
 /// Meteora DLMM Program ID
 const METEORA_DLMM: &str = "LBUZKhRxPF3XUpBCjp4YzTKgLccjZhTSDM9YuVaPwxo";
 
+/// Phoenix Program ID
+const PHOENIX: &str = "PhoeNiXZ8ByJGLkxNfZRnkUfjvmuYqLR89jjFHGqdXY";
+
+/// OpenBook V2 Program ID
+const OPENBOOK_V2: &str = "opnb2LAfJYbRMAHHvqjCwQxanZn7ReEHp1k81EohpZb";
+
+/// Jupiter Aggregator V6 Program ID
+const JUPITER_V6: &str = "JUP6LkbZbjS1jKKwapdHNy74zcZ3tLUZoi5QNyVTaV4";
+
 /// SPL Token Program ID
 const TOKEN_PROGRAM: &str = "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA";
 
+/// Associated Token Account Program ID
+const ASSOCIATED_TOKEN_PROGRAM: &str = "ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL";
+
 // ============================================================================
 // PHASE 1: RAYDIUM AMM V4 SWAP
 // ============================================================================
@@ -178,11 +191,84 @@ pub fn build_raydium_swap_data(amount_in: u64, minimum_amount_out: u64) -> PyRes
     Ok(data)
 }
 
+/// Raydium V4 `SwapBaseInV2` instruction tag: swap_base_in without OpenBook accounts
+const RAYDIUM_SWAP_BASE_IN_V2: u8 = 16;
+
+/// Build a Raydium AMM V4 swap instruction for pools that route without an
+/// OpenBook market, using `SwapBaseInV2`.
+/// 
+/// # Arguments
+/// * `amm_id` - The AMM pool address
+/// * `amm_authority` - The AMM authority PDA
+/// * `pool_coin_token` - Pool's coin token account
+/// * `pool_pc_token` - Pool's PC (quote) token account
+/// * `user_source` - User's source token account
+/// * `user_destination` - User's destination token account
+/// * `user_owner` - User's wallet (signer)
+/// * `amount_in` - Amount of tokens to swap
+/// * `minimum_amount_out` - Minimum tokens to receive (slippage protection)
+#[pyfunction]
+#[pyo3(signature = (
+    amm_id,
+    amm_authority,
+    pool_coin_token,
+    pool_pc_token,
+    user_source,
+    user_destination,
+    user_owner,
+    amount_in,
+    minimum_amount_out
+))]
+#[allow(clippy::too_many_arguments)]
+pub fn build_raydium_swap_ix_no_market(
+    amm_id: &str,
+    amm_authority: &str,
+    pool_coin_token: &str,
+    pool_pc_token: &str,
+    user_source: &str,
+    user_destination: &str,
+    user_owner: &str,
+    amount_in: u64,
+    minimum_amount_out: u64,
+) -> PyResult<Vec<u8>> {
+    let token_program = Pubkey::from_str(TOKEN_PROGRAM)
+        .map_err(|e| PyErr::new::<pyo3::exceptions::PyValueError, _>(e.to_string()))?;
+    let raydium_program = Pubkey::from_str(RAYDIUM_AMM_V4)
+        .map_err(|e| PyErr::new::<pyo3::exceptions::PyValueError, _>(e.to_string()))?;
+    
+    let accounts = vec![
+        AccountMeta::new_readonly(token_program, false),                 // 0
+        AccountMeta::new(parse_pubkey(amm_id)?, false),                  // 1
+        AccountMeta::new_readonly(parse_pubkey(amm_authority)?, false),  // 2
+        AccountMeta::new(parse_pubkey(pool_coin_token)?, false),         // 3
+        AccountMeta::new(parse_pubkey(pool_pc_token)?, false),           // 4
+        AccountMeta::new(parse_pubkey(user_source)?, false),             // 5
+        AccountMeta::new(parse_pubkey(user_destination)?, false),        // 6
+        AccountMeta::new_readonly(parse_pubkey(user_owner)?, true),      // 7 (signer)
+    ];
+    
+    // Raydium V4 SwapBaseInV2: [16, amount_in (8 bytes LE), minimum_amount_out (8 bytes LE)]
+    let mut data = Vec::with_capacity(17);
+    data.push(RAYDIUM_SWAP_BASE_IN_V2);
+    data.extend_from_slice(&amount_in.to_le_bytes());
+    data.extend_from_slice(&minimum_amount_out.to_le_bytes());
+    
+    let ix = Instruction {
+        program_id: raydium_program,
+        accounts,
+        data,
+    };
+    
+    bincode::serialize(&ix)
+        .map_err(|e| PyErr::new::<pyo3::exceptions::PyValueError, _>(e.to_string()))
+}
+
 // ============================================================================
 // PHASE 2: ORCA WHIRLPOOL SWAP
 // ============================================================================
 
 /// Anchor discriminator for Whirlpool `swap` instruction
+/// Calculated as: sha256("global:swap")[0..8]
 const WHIRLPOOL_SWAP_DISCRIMINATOR: [u8; 8] = [0xf8, 0xc6, 0x9e, 0x91, 0xe1, 0x75, 0x87, 0xc8];
 
 /// Build Orca Whirlpool swap instruction data.
@@ -301,8 +387,13 @@ pub fn build_whirlpool_swap_ix(
 // ============================================================================
 
 /// Anchor discriminator for Meteora DLMM `swap` instruction
+/// Calculated as: sha256("global:swap")[0..8] - the same bytes as Whirlpool's,
+/// since Anchor derives discriminators from the instruction name alone
 const DLMM_SWAP_DISCRIMINATOR: [u8; 8] = [0xf8, 0xc6, 0x9e, 0x91, 0xe1, 0x75, 0x87, 0xc8];
 
+/// Meteora DLMM event authority PDA seed (Anchor `#[event_cpi]`)
+const DLMM_EVENT_AUTHORITY_SEED: &[u8] = b"__event_authority";
+
 /// Build Meteora DLMM swap instruction data.
 /// 
 /// # Arguments
@@ -326,6 +417,11 @@ pub fn build_dlmm_swap_data(amount_in: u64, min_amount_out: u64) -> PyResult<Vec
 }
 
 /// Build complete DLMM swap instruction with accounts.
+///
+/// Account order follows the lb_clmm IDL `swap` instruction. The optional
+/// `bin_array_bitmap_extension` and `host_fee_in` accounts may be None, which
+/// Anchor expects as the program ID in their slot. Bin arrays follow as
+/// remaining accounts.
 #[pyfunction]
 #[pyo3(signature = (
     lb_pair,
@@ -345,7 +441,7 @@ pub fn build_dlmm_swap_data(amount_in: u64, min_amount_out: u64) -> PyResult<Vec
 ))]
 pub fn build_dlmm_swap_ix(
     lb_pair: &str,
-    bin_array_bitmap_extension: &str,
+    bin_array_bitmap_extension: Option<&str>,
     reserve_x: &str,
     reserve_y: &str,
     user_token_in: &str,
@@ -353,7 +449,7 @@ pub fn build_dlmm_swap_ix(
     token_x_mint: &str,
     token_y_mint: &str,
     oracle: &str,
-    host_fee_in: &str,
+    host_fee_in: Option<&str>,
     user: &str,
     bin_arrays: Vec<String>,
     amount_in: u64,
@@ -364,19 +460,25 @@ pub fn build_dlmm_swap_ix(
     let token_program = Pubkey::from_str(TOKEN_PROGRAM)
         .map_err(|e| PyErr::new::<pyo3::exceptions::PyValueError, _>(e.to_string()))?;
     
+    let (event_authority, _) = Pubkey::find_program_address(&[DLMM_EVENT_AUTHORITY_SEED], &dlmm_program);
+    let optional = |account: Option<&str>| account.map_or(Ok(dlmm_program), parse_pubkey);
+    
     let mut accounts = vec![
-        AccountMeta::new(parse_pubkey(lb_pair)?, false),
-        AccountMeta::new_readonly(parse_pubkey(bin_array_bitmap_extension)?, false),
-        AccountMeta::new(parse_pubkey(reserve_x)?, false),
-        AccountMeta::new(parse_pubkey(reserve_y)?, false),
-        AccountMeta::new(parse_pubkey(user_token_in)?, false),
-        AccountMeta::new(parse_pubkey(user_token_out)?, false),
-        AccountMeta::new_readonly(parse_pubkey(token_x_mint)?, false),
-        AccountMeta::new_readonly(parse_pubkey(token_y_mint)?, false),
-        AccountMeta::new_readonly(parse_pubkey(oracle)?, false),
-        AccountMeta::new(parse_pubkey(host_fee_in)?, false),
-        AccountMeta::new_readonly(parse_pubkey(user)?, true), // signer
-        AccountMeta::new_readonly(token_program, false),
+        AccountMeta::new(parse_pubkey(lb_pair)?, false),                          // 0: lb_pair
+        AccountMeta::new_readonly(optional(bin_array_bitmap_extension)?, false),  // 1: bin_array_bitmap_extension
+        AccountMeta::new(parse_pubkey(reserve_x)?, false),                        // 2: reserve_x
+        AccountMeta::new(parse_pubkey(reserve_y)?, false),                        // 3: reserve_y
+        AccountMeta::new(parse_pubkey(user_token_in)?, false),                    // 4: user_token_in
+        AccountMeta::new(parse_pubkey(user_token_out)?, false),                   // 5: user_token_out
+        AccountMeta::new_readonly(parse_pubkey(token_x_mint)?, false),            // 6: token_x_mint
+        AccountMeta::new_readonly(parse_pubkey(token_y_mint)?, false),            // 7: token_y_mint
+        AccountMeta::new(parse_pubkey(oracle)?, false),                           // 8: oracle
+        AccountMeta::new(optional(host_fee_in)?, false),                          // 9: host_fee_in
+        AccountMeta::new_readonly(parse_pubkey(user)?, true),                     // 10: user (signer)
+        AccountMeta::new_readonly(token_program, false),                          // 11: token_x_program
+        AccountMeta::new_readonly(token_program, false),                          // 12: token_y_program
+        AccountMeta::new_readonly(event_authority, false),                        // 13: event_authority
+        AccountMeta::new_readonly(dlmm_program, false),                           // 14: program
     ];
     
     // Add bin arrays (variable number)
@@ -411,11 +513,11 @@ const MEMO_PROGRAM: &str = "MemoSq4gqABAXKb96qnH8TysNcWxMyWCqXgDLGmfcHr";
 
 /// Anchor discriminator for Raydium CLMM `swap` instruction (SPL-only legacy)
 /// Calculated as: sha256("global:swap")[0..8]
-const RAYDIUM_CLMM_SWAP_DISCRIMINATOR: [u8; 8] = [248, 198, 244, 225, 115, 175, 175, 192];
+const RAYDIUM_CLMM_SWAP_DISCRIMINATOR: [u8; 8] = [248, 198, 158, 145, 225, 117, 135, 200];
 
 /// Anchor discriminator for Raydium CLMM `swapV2` instruction (SPL + Token-2022)
 /// Calculated as: sha256("global:swap_v2")[0..8]
-const RAYDIUM_CLMM_SWAP_V2_DISCRIMINATOR: [u8; 8] = [0x2b, 0x04, 0xed, 0x0b, 0x1a, 0xc9, 0x1e, 0xb8];
+const RAYDIUM_CLMM_SWAP_V2_DISCRIMINATOR: [u8; 8] = [0x2b, 0x04, 0xed, 0x0b, 0x1a, 0xc9, 0x1e, 0x62];
 
 /// Build Raydium CLMM swap instruction data (Legacy SPL-only).
 /// Use this for older CLMM pools that don't support Token-2022.
@@ -593,6 +695,130 @@ pub fn build_raydium_clmm_swap_ix(
         .map_err(|e| PyErr::new::<pyo3::exceptions::PyValueError, _>(e.to_string()))
 }
 
+// ============================================================================
+// PHASE 5: TOKEN ACCOUNT HELPERS (wrap/unwrap SOL)
+// ============================================================================
+
+/// ATA program `CreateIdempotent` instruction tag (no-op if the ATA exists)
+const ATA_CREATE_IDEMPOTENT: u8 = 1;
+
+/// SPL Token `CloseAccount` instruction tag
+const TOKEN_CLOSE_ACCOUNT: u8 = 9;
+
+/// SPL Token `SyncNative` instruction tag
+const TOKEN_SYNC_NATIVE: u8 = 17;
+
+/// Build an idempotent create-ATA instruction for `owner`'s `mint` account.
+/// Set `token_2022` for Token-2022 mints (changes both the ATA address and the token program).
+#[pyfunction]
+#[pyo3(signature = (payer, owner, mint, token_2022=false))]
+pub fn build_create_ata_ix(payer: &str, owner: &str, mint: &str, token_2022: bool) -> PyResult<Vec<u8>> {
+    let ata_program = parse_pubkey(ASSOCIATED_TOKEN_PROGRAM)?;
+    let token_program = parse_pubkey(if token_2022 { TOKEN_2022_PROGRAM } else { TOKEN_PROGRAM })?;
+    let owner_pk = parse_pubkey(owner)?;
+    let mint_pk = parse_pubkey(mint)?;
+    
+    let (ata, _) = Pubkey::find_program_address(
+        &[owner_pk.as_ref(), token_program.as_ref(), mint_pk.as_ref()],
+        &ata_program,
+    );
+    
+    let accounts = vec![
+        AccountMeta::new(parse_pubkey(payer)?, true),                     // 0: payer (signer)
+        AccountMeta::new(ata, false),                                     // 1: associated token account
+        AccountMeta::new_readonly(owner_pk, false),                       // 2: wallet
+        AccountMeta::new_readonly(mint_pk, false),                        // 3: mint
+        AccountMeta::new_readonly(solana_sdk::system_program::id(), false), // 4: system program
+        AccountMeta::new_readonly(token_program, false),                  // 5: token program
+    ];
+    
+    let ix = Instruction {
+        program_id: ata_program,
+        accounts,
+        data: vec![ATA_CREATE_IDEMPOTENT],
+    };
+    
+    bincode::serialize(&ix)
+        .map_err(|e| PyErr::new::<pyo3::exceptions::PyValueError, _>(e.to_string()))
+}
+
+/// Build an SPL Token CloseAccount instruction; remaining lamports go to `destination`.
+/// Closing a wSOL account unwraps it.
+#[pyfunction]
+pub fn build_close_account_ix(account: &str, destination: &str, owner: &str) -> PyResult<Vec<u8>> {
+    let ix = Instruction {
+        program_id: parse_pubkey(TOKEN_PROGRAM)?,
+        accounts: vec![
+            AccountMeta::new(parse_pubkey(account)?, false),
+            AccountMeta::new(parse_pubkey(destination)?, false),
+            AccountMeta::new_readonly(parse_pubkey(owner)?, true), // signer
+        ],
+        data: vec![TOKEN_CLOSE_ACCOUNT],
+    };
+    
+    bincode::serialize(&ix)
+        .map_err(|e| PyErr::new::<pyo3::exceptions::PyValueError, _>(e.to_string()))
+}
+
+/// Build an SPL Token SyncNative instruction, crediting lamports transferred
+/// into a wSOL account to its token balance.
+#[pyfunction]
+pub fn build_sync_native_ix(wsol_account: &str) -> PyResult<Vec<u8>> {
+    let ix = Instruction {
+        program_id: parse_pubkey(TOKEN_PROGRAM)?,
+        accounts: vec![AccountMeta::new(parse_pubkey(wsol_account)?, false)],
+        data: vec![TOKEN_SYNC_NATIVE],
+    };
+    
+    bincode::serialize(&ix)
+        .map_err(|e| PyErr::new::<pyo3::exceptions::PyValueError, _>(e.to_string()))
+}
+
+// ============================================================================
+// MULTI-INSTRUCTION BUNDLES
+// ============================================================================
+
+/// Prefix marking a serialized instruction list, so bundles and single
+/// bincode `Instruction`s (which start with the program id) can share a field
+const INSTRUCTION_BUNDLE_MAGIC: [u8; 8] = *b"PHIXBNDL";
+
+/// Decode either a single bincode `Instruction` or a `serialize_instructions` bundle.
+pub(crate) fn decode_instructions(bytes: &[u8]) -> Result<Vec<Instruction>, String> {
+    match bytes.strip_prefix(&INSTRUCTION_BUNDLE_MAGIC) {
+        Some(list) => bincode::deserialize(list).map_err(|e| e.to_string()),
+        None => bincode::deserialize(bytes).map(|ix| vec![ix]).map_err(|e| e.to_string()),
+    }
+}
+
+/// Pack several serialized instructions (e.g. create ATA + swap + close) into
+/// one payload that a `SwapLeg` can carry. Nested bundles are flattened.
+#[pyfunction]
+pub fn serialize_instructions(ixs: Vec<Vec<u8>>) -> PyResult<Vec<u8>> {
+    let mut instructions = Vec::with_capacity(ixs.len());
+    for (i, bytes) in ixs.iter().enumerate() {
+        let decoded = decode_instructions(bytes).map_err(|e| {
+            PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Invalid instruction {}: {}", i, e))
+        })?;
+        instructions.extend(decoded);
+    }
+    
+    let mut data = INSTRUCTION_BUNDLE_MAGIC.to_vec();
+    bincode::serialize_into(&mut data, &instructions)
+        .map_err(|e| PyErr::new::<pyo3::exceptions::PyValueError, _>(e.to_string()))?;
+    Ok(data)
+}
+
+/// Split a payload from `serialize_instructions` back into single serialized
+/// instructions. A plain serialized instruction comes back as a list of one.
+#[pyfunction]
+pub fn deserialize_instructions(data: Vec<u8>) -> PyResult<Vec<Vec<u8>>> {
+    decode_instructions(&data)
+        .map_err(PyErr::new::<pyo3::exceptions::PyValueError, _>)?
+        .iter()
+        .map(|ix| bincode::serialize(ix).map_err(|e| PyErr::new::<pyo3::exceptions::PyValueError, _>(e.to_string())))
+        .collect()
+}
+
 // ============================================================================
 // HELPERS
 
@@ -611,9 +837,13 @@ pub fn get_dex_program_ids() -> PyResult<Vec<(String, String)>> {
         ("RAYDIUM_CLMM".to_string(), RAYDIUM_CLMM.to_string()),
         ("ORCA_WHIRLPOOL".to_string(), ORCA_WHIRLPOOL.to_string()),
         ("METEORA_DLMM".to_string(), METEORA_DLMM.to_string()),
+        ("PHOENIX".to_string(), PHOENIX.to_string()),
+        ("OPENBOOK_V2".to_string(), OPENBOOK_V2.to_string()),
+        ("JUPITER_V6".to_string(), JUPITER_V6.to_string()),
         ("TOKEN_PROGRAM".to_string(), TOKEN_PROGRAM.to_string()),
         ("TOKEN_2022_PROGRAM".to_string(), TOKEN_2022_PROGRAM.to_string()),
         ("MEMO_PROGRAM".to_string(), MEMO_PROGRAM.to_string()),
+        ("ASSOCIATED_TOKEN_PROGRAM".to_string(), ASSOCIATED_TOKEN_PROGRAM.to_string()),
     ])
 }
 
@@ -624,6 +854,7 @@ pub fn get_dex_program_ids() -> PyResult<Vec<(String, String)>> {
 pub fn register_instruction_functions(m: &PyModule) -> PyResult<()> {
     // Raydium AMM V4
     m.add_function(wrap_pyfunction!(build_raydium_swap_ix, m)?)?;
+    m.add_function(wrap_pyfunction!(build_raydium_swap_ix_no_market, m)?)?;
     m.add_function(wrap_pyfunction!(build_raydium_swap_data, m)?)?;
     
     // Raydium CLMM (Concentrated Liquidity)
@@ -639,8 +870,218 @@ pub fn register_instruction_functions(m: &PyModule) -> PyResult<()> {
     m.add_function(wrap_pyfunction!(build_dlmm_swap_data, m)?)?;
     m.add_function(wrap_pyfunction!(build_dlmm_swap_ix, m)?)?;
     
+    // Token account helpers
+    m.add_function(wrap_pyfunction!(build_create_ata_ix, m)?)?;
+    m.add_function(wrap_pyfunction!(build_close_account_ix, m)?)?;
+    m.add_function(wrap_pyfunction!(build_sync_native_ix, m)?)?;
+    
+    // Multi-instruction bundles
+    m.add_function(wrap_pyfunction!(serialize_instructions, m)?)?;
+    m.add_function(wrap_pyfunction!(deserialize_instructions, m)?)?;
+    
     // Helpers
     m.add_function(wrap_pyfunction!(get_dex_program_ids, m)?)?;
     
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::hash::hashv;
+
+    /// IDL discriminator: sha256("global:<ix_name>")[0..8]
+    fn anchor_discriminator(ix_name: &str) -> [u8; 8] {
+        hashv(&[format!("global:{}", ix_name).as_bytes()]).to_bytes()[..8].try_into().unwrap()
+    }
+
+    fn key(seed: u8) -> String {
+        Pubkey::new_from_array([seed; 32]).to_string()
+    }
+
+    #[test]
+    fn test_swap_data_discriminators_match_idl() {
+        let whirlpool = build_whirlpool_swap_data(1_000, 990, 0, true, true).unwrap();
+        assert_eq!(whirlpool[..8], anchor_discriminator("swap"));
+        assert_eq!(whirlpool.len(), 8 + 8 + 8 + 16 + 1 + 1);
+
+        let dlmm = build_dlmm_swap_data(1_000, 990).unwrap();
+        assert_eq!(dlmm[..8], anchor_discriminator("swap"));
+        assert_eq!(dlmm[8..16], 1_000u64.to_le_bytes());
+        assert_eq!(dlmm[16..24], 990u64.to_le_bytes());
+        assert_eq!(dlmm.len(), 24);
+
+        let clmm_legacy = build_raydium_clmm_swap_legacy_data(1_000, 990, 0, true).unwrap();
+        assert_eq!(clmm_legacy[..8], anchor_discriminator("swap"));
+        let clmm_v2 = build_raydium_clmm_swap_data(1_000, 990, 0, true).unwrap();
+        assert_eq!(clmm_v2[..8], anchor_discriminator("swap_v2"));
+    }
+
+    #[test]
+    fn test_dlmm_swap_ix_accounts_match_idl() {
+        let bin_arrays = vec![key(20), key(21)];
+        let bytes = build_dlmm_swap_ix(
+            &key(1),
+            None,
+            &key(3),
+            &key(4),
+            &key(5),
+            &key(6),
+            &key(7),
+            &key(8),
+            &key(9),
+            None,
+            &key(11),
+            bin_arrays,
+            1_000,
+            990,
+        )
+        .unwrap();
+        let ix: Instruction = bincode::deserialize(&bytes).unwrap();
+        let program = Pubkey::from_str(METEORA_DLMM).unwrap();
+
+        assert_eq!(ix.program_id, program);
+        assert_eq!(ix.data[..8], anchor_discriminator("swap"));
+        assert_eq!(ix.accounts.len(), 15 + 2);
+
+        // Omitted optional accounts are passed as the program ID
+        assert_eq!(ix.accounts[1].pubkey, program);
+        assert_eq!(ix.accounts[9].pubkey, program);
+        assert!(ix.accounts[8].is_writable, "oracle is mutable");
+        assert!(ix.accounts[10].is_signer);
+        assert_eq!(
+            ix.accounts[13].pubkey,
+            Pubkey::from_str("D1ZN9Wj1fRSUQfCjhvnu1hqDMT7hzjzBBpi12nVniYD6").unwrap()
+        );
+        assert_eq!(ix.accounts[14].pubkey, program);
+        assert_eq!(ix.accounts[15].pubkey.to_string(), key(20));
+    }
+
+    #[test]
+    fn test_raydium_swap_ix_no_market_accounts() {
+        let bytes = build_raydium_swap_ix_no_market(
+            &key(1),
+            &key(2),
+            &key(3),
+            &key(4),
+            &key(5),
+            &key(6),
+            &key(7),
+            1_000,
+            990,
+        )
+        .unwrap();
+        let ix: Instruction = bincode::deserialize(&bytes).unwrap();
+
+        assert_eq!(ix.program_id.to_string(), RAYDIUM_AMM_V4);
+        assert_eq!(ix.data[0], RAYDIUM_SWAP_BASE_IN_V2);
+        assert_eq!(ix.data[1..9], 1_000u64.to_le_bytes());
+        assert_eq!(ix.data[9..17], 990u64.to_le_bytes());
+
+        // token program, amm, authority, coin/pc vaults, user source/destination, owner
+        let keys: Vec<String> = ix.accounts.iter().map(|a| a.pubkey.to_string()).collect();
+        let mut expected = vec![TOKEN_PROGRAM.to_string()];
+        expected.extend((1..=7).map(key));
+        assert_eq!(keys, expected);
+
+        let writable: Vec<bool> = ix.accounts.iter().map(|a| a.is_writable).collect();
+        assert_eq!(writable, [false, true, false, true, true, true, true, false]);
+        assert!(ix.accounts[7].is_signer);
+        assert_eq!(ix.accounts.iter().filter(|a| a.is_signer).count(), 1);
+    }
+
+    const WSOL_MINT: &str = "So11111111111111111111111111111111111111112";
+
+    #[test]
+    fn test_create_ata_ix() {
+        let payer = key(1);
+        let ix: Instruction = bincode::deserialize(&build_create_ata_ix(&payer, &payer, WSOL_MINT, false).unwrap()).unwrap();
+        assert_eq!(ix.program_id.to_string(), ASSOCIATED_TOKEN_PROGRAM);
+        assert_eq!(ix.data, [ATA_CREATE_IDEMPOTENT]);
+
+        let owner = Pubkey::new_from_array([1; 32]);
+        let token_program = Pubkey::from_str(TOKEN_PROGRAM).unwrap();
+        let (ata, _) = Pubkey::find_program_address(
+            &[owner.as_ref(), token_program.as_ref(), Pubkey::from_str(WSOL_MINT).unwrap().as_ref()],
+            &Pubkey::from_str(ASSOCIATED_TOKEN_PROGRAM).unwrap(),
+        );
+        let ata = ata.to_string();
+        let keys: Vec<String> = ix.accounts.iter().map(|a| a.pubkey.to_string()).collect();
+        assert_eq!(
+            keys,
+            [payer.as_str(), ata.as_str(), payer.as_str(), WSOL_MINT, "11111111111111111111111111111111", TOKEN_PROGRAM]
+        );
+        let flags: Vec<(bool, bool)> = ix.accounts.iter().map(|a| (a.is_signer, a.is_writable)).collect();
+        assert_eq!(flags, [(true, true), (false, true), (false, false), (false, false), (false, false), (false, false)]);
+
+        // Token-2022 mints live under a different ATA and token program
+        let ix22: Instruction = bincode::deserialize(&build_create_ata_ix(&payer, &payer, &key(9), true).unwrap()).unwrap();
+        assert_eq!(ix22.accounts[5].pubkey.to_string(), TOKEN_2022_PROGRAM);
+        let ix_spl: Instruction = bincode::deserialize(&build_create_ata_ix(&payer, &payer, &key(9), false).unwrap()).unwrap();
+        assert_ne!(ix22.accounts[1].pubkey, ix_spl.accounts[1].pubkey);
+    }
+
+    #[test]
+    fn test_close_account_ix() {
+        let ix: Instruction = bincode::deserialize(&build_close_account_ix(&key(2), &key(1), &key(1)).unwrap()).unwrap();
+        assert_eq!(ix.program_id.to_string(), TOKEN_PROGRAM);
+        assert_eq!(ix.data, [TOKEN_CLOSE_ACCOUNT]);
+        assert_eq!(ix.accounts, [
+            AccountMeta::new(Pubkey::new_from_array([2; 32]), false),
+            AccountMeta::new(Pubkey::new_from_array([1; 32]), false),
+            AccountMeta::new_readonly(Pubkey::new_from_array([1; 32]), true),
+        ]);
+    }
+
+    #[test]
+    fn test_sync_native_ix() {
+        let ix: Instruction = bincode::deserialize(&build_sync_native_ix(&key(2)).unwrap()).unwrap();
+        assert_eq!(ix.program_id.to_string(), TOKEN_PROGRAM);
+        assert_eq!(ix.data, [TOKEN_SYNC_NATIVE]);
+        assert_eq!(ix.accounts, [AccountMeta::new(Pubkey::new_from_array([2; 32]), false)]);
+    }
+
+    #[test]
+    fn test_instruction_bundle_round_trip() {
+        let payer = key(1);
+        let wsol = key(2);
+        let ixs = vec![
+            build_create_ata_ix(&payer, &payer, WSOL_MINT, false).unwrap(),
+            build_sync_native_ix(&wsol).unwrap(),
+            build_close_account_ix(&wsol, &payer, &payer).unwrap(),
+        ];
+
+        let bundle = serialize_instructions(ixs.clone()).unwrap();
+        assert_eq!(bundle[..8], INSTRUCTION_BUNDLE_MAGIC);
+        assert_eq!(deserialize_instructions(bundle.clone()).unwrap(), ixs);
+        assert_eq!(decode_instructions(&bundle).unwrap().len(), 3);
+
+        // Single instructions pass through, and nested bundles flatten
+        assert_eq!(deserialize_instructions(ixs[1].clone()).unwrap(), vec![ixs[1].clone()]);
+        let nested = serialize_instructions(vec![bundle, ixs[0].clone()]).unwrap();
+        assert_eq!(decode_instructions(&nested).unwrap().len(), 4);
+
+        assert!(serialize_instructions(vec![vec![1, 2, 3]]).is_err());
+    }
+
+    #[test]
+    fn test_dex_program_ids_complete_and_valid() {
+        let ids: std::collections::HashMap<String, String> = get_dex_program_ids().unwrap().into_iter().collect();
+        for name in [
+            "RAYDIUM_AMM_V4",
+            "RAYDIUM_CLMM",
+            "ORCA_WHIRLPOOL",
+            "METEORA_DLMM",
+            "PHOENIX",
+            "OPENBOOK_V2",
+            "JUPITER_V6",
+            "TOKEN_PROGRAM",
+            "TOKEN_2022_PROGRAM",
+            "MEMO_PROGRAM",
+            "ASSOCIATED_TOKEN_PROGRAM",
+        ] {
+            let id = ids.get(name).unwrap_or_else(|| panic!("missing {}", name));
+            assert!(Pubkey::from_str(id).is_ok(), "{} is not a valid pubkey: {}", name, id);
+        }
+    }
+}
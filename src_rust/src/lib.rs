@@ -1,9 +1,12 @@
 #![deny(dead_code)]
 use pyo3::prelude::*;
 use solana_sdk::{
+    address_lookup_table::AddressLookupTableAccount,
+    compute_budget::ComputeBudgetInstruction,
     hash::Hash,
     instruction::Instruction,
     message::{v0, VersionedMessage},
+    pubkey::Pubkey,
     signature::{Keypair, Signer},
     transaction::VersionedTransaction,
 };
@@ -55,6 +58,39 @@ fn calculate_net_profit_batch(
     Ok(results)
 }
 
+/// Batch processing with per-opportunity economics.
+/// Each trade carries its own size, tip, and friction; vectors must align.
+#[pyfunction]
+fn calculate_net_profit_batch_v2(
+    spreads: Vec<f64>,
+    trade_sizes: Vec<f64>,
+    jito_tips: Vec<f64>,
+    route_frictions: Vec<f64>,
+) -> PyResult<Vec<f64>> {
+    let n = spreads.len();
+    if trade_sizes.len() != n || jito_tips.len() != n || route_frictions.len() != n {
+        return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+            "Length mismatch: spreads={}, trade_sizes={}, jito_tips={}, route_frictions={}",
+            n,
+            trade_sizes.len(),
+            jito_tips.len(),
+            route_frictions.len()
+        )));
+    }
+
+    let results = spreads
+        .into_iter()
+        .zip(trade_sizes)
+        .zip(jito_tips)
+        .zip(route_frictions)
+        .map(|(((spread, size), tip), friction)| {
+            web_math::calculate_net_profit(spread, size, tip, friction)
+        })
+        .collect();
+
+    Ok(results)
+}
+
 // ------------------------------------------------------------------------
 // SECTION 2: CHAOS SHIELD (ESTIMATORS & CHECKS)
 // ------------------------------------------------------------------------
@@ -72,6 +108,26 @@ fn estimate_compute_units(
     Ok(web_math::estimate_compute_units(ops, num_accounts, num_signers, safety_margin_percent))
 }
 
+/// Compute Unit Estimator with a caller-supplied op -> CU table.
+/// Lets us recalibrate from on-chain simulation data without recompiling.
+/// Ops missing from `cost_table` fall back to the built-in defaults.
+#[pyfunction]
+fn estimate_compute_units_with_table(
+    ops: Vec<String>,
+    num_accounts: u32,
+    num_signers: u32,
+    safety_margin_percent: f64,
+    cost_table: HashMap<String, f64>,
+) -> PyResult<u32> {
+    Ok(web_math::estimate_compute_units_with_table(
+        ops,
+        num_accounts,
+        num_signers,
+        safety_margin_percent,
+        &cost_table,
+    ))
+}
+
 #[pyfunction]
 fn validate_gate(
     spread_pct: f64,
@@ -96,19 +152,23 @@ fn calculate_basis_yield(mark: f64, oracle: f64) -> PyResult<f64> {
     Ok(web_math::calculate_basis_yield(mark, oracle))
 }
 
-/// Liveness Check: Ensures the RPC data isn't stale.
-/// Returns error if the gap is > 2 slots.
-fn verify_slot_sync(rpc_slot: u64, jito_slot: u64) -> PyResult<()> {
-    let gap = if rpc_slot > jito_slot {
-        rpc_slot - jito_slot
-    } else {
-        jito_slot - rpc_slot
-    };
+/// Default tolerated gap between RPC and Jito slots
+const DEFAULT_MAX_SLOT_GAP: u64 = 2;
+
+/// Liveness Check: true if the RPC and Jito slots are within `max_gap` slots.
+#[pyfunction]
+#[pyo3(signature = (rpc_slot, jito_slot, max_gap=DEFAULT_MAX_SLOT_GAP))]
+fn verify_slot_sync(rpc_slot: u64, jito_slot: u64, max_gap: u64) -> bool {
+    rpc_slot.abs_diff(jito_slot) <= max_gap
+}
 
-    if gap > 2 {
+/// Liveness Check: Ensures the RPC data isn't stale.
+/// Returns error if the gap is > `max_gap` slots.
+fn ensure_slot_sync(rpc_slot: u64, jito_slot: u64, max_gap: u64) -> PyResult<()> {
+    if !verify_slot_sync(rpc_slot, jito_slot, max_gap) {
         return Err(PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!(
             "State Desync: Gap is {} slots. Aborting to prevent Ghost Trade.",
-            gap
+            rpc_slot.abs_diff(jito_slot)
         )));
     }
     Ok(())
@@ -127,21 +187,41 @@ fn verify_slot_sync(rpc_slot: u64, jito_slot: u64) -> PyResult<()> {
 /// * `blockhash_b58` - Recent blockhash
 /// * `rpc_slot` - Current RPC slot for liveness check
 /// * `jito_slot` - Last Jito bundle slot (optional, pass 0 to skip)
+/// * `compute_unit_limit` - Optional CU limit (prepended as a ComputeBudget ix)
+/// * `compute_unit_price_micro_lamports` - Optional priority fee per CU
+/// * `lookup_tables` - Address Lookup Tables as (table_pubkey_b58, [address_b58, ...])
+///   with addresses in on-chain table order
+/// * `max_slot_gap` - Largest tolerated RPC/Jito slot gap (default 2)
 ///
 /// # Returns
 /// Serialized VersionedTransaction (bincode)
 #[pyfunction]
-#[pyo3(signature = (instruction_payload, payer_key_b58, blockhash_b58, rpc_slot, jito_slot=0))]
+#[pyo3(signature = (
+    instruction_payload,
+    payer_key_b58,
+    blockhash_b58,
+    rpc_slot,
+    jito_slot=0,
+    compute_unit_limit=None,
+    compute_unit_price_micro_lamports=None,
+    lookup_tables=Vec::new(),
+    max_slot_gap=DEFAULT_MAX_SLOT_GAP
+))]
+#[allow(clippy::too_many_arguments)]
 fn build_atomic_transaction(
     instruction_payload: Vec<u8>,
     payer_key_b58: String,
     blockhash_b58: String,
     rpc_slot: u64,
     jito_slot: u64,
+    compute_unit_limit: Option<u32>,
+    compute_unit_price_micro_lamports: Option<u64>,
+    lookup_tables: Vec<(String, Vec<String>)>,
+    max_slot_gap: u64,
 ) -> PyResult<Vec<u8>> {
     // 1. Safety Check: Liveness (if Jito slot provided)
     if jito_slot > 0 {
-        verify_slot_sync(rpc_slot, jito_slot)?;
+        ensure_slot_sync(rpc_slot, jito_slot, max_slot_gap)?;
     }
 
     // 2. Parsers (Fast Rust Parsing)
@@ -158,27 +238,92 @@ fn build_atomic_transaction(
         ))
     })?;
 
-    // 4. Message V0 Construction
+    // 4. Compute Budget (must precede the payload instruction)
+    let mut instructions = Vec::with_capacity(3);
+    if let Some(limit) = compute_unit_limit {
+        instructions.push(ComputeBudgetInstruction::set_compute_unit_limit(limit));
+    }
+    if let Some(price) = compute_unit_price_micro_lamports {
+        instructions.push(ComputeBudgetInstruction::set_compute_unit_price(price));
+    }
+    instructions.push(instruction);
+
+    // 5. Address Lookup Tables
+    let parse_pubkey = |key: &str| {
+        Pubkey::from_str(key).map_err(|e| {
+            PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                "Invalid lookup table pubkey {}: {}",
+                key, e
+            ))
+        })
+    };
+    let alt_accounts = lookup_tables
+        .iter()
+        .map(|(table_key, addresses)| {
+            Ok(AddressLookupTableAccount {
+                key: parse_pubkey(table_key)?,
+                addresses: addresses
+                    .iter()
+                    .map(|a| parse_pubkey(a))
+                    .collect::<PyResult<Vec<_>>>()?,
+            })
+        })
+        .collect::<PyResult<Vec<_>>>()?;
+
+    // 6. Message V0 Construction
     let message = v0::Message::try_compile(
         &payer.pubkey(),
-        &[instruction],
-        &[], // Address Lookup Tables (Empty for now)
+        &instructions,
+        &alt_accounts,
         blockhash,
     )
     .map_err(|e| PyErr::new::<pyo3::exceptions::PyValueError, _>(e.to_string()))?;
 
-    // 5. Signing
+    // 7. Signing
     let versioned_msg = VersionedMessage::V0(message);
     let tx = VersionedTransaction::try_new(versioned_msg, &[&payer])
         .map_err(|e| PyErr::new::<pyo3::exceptions::PyValueError, _>(e.to_string()))?;
 
-    // 6. Serialization
+    // 8. Serialization
     let serialized = bincode::serialize(&tx)
         .map_err(|e| PyErr::new::<pyo3::exceptions::PyValueError, _>(e.to_string()))?;
 
     Ok(serialized)
 }
 
+/// Build, sign and submit in one Rust call (no second FFI hop).
+///
+/// Builds the same V0 transaction as `build_atomic_transaction` (no slot
+/// check, compute budget or lookup tables) and sends it with
+/// `sendTransaction` on the shared runtime. Build errors raise; submission
+/// errors come back in the `SubmissionResult`.
+#[pyfunction]
+#[pyo3(signature = (instruction_payload, payer_key_b58, blockhash_b58, rpc_url, skip_preflight=true))]
+fn build_and_submit_atomic(
+    instruction_payload: Vec<u8>,
+    payer_key_b58: String,
+    blockhash_b58: String,
+    rpc_url: String,
+    skip_preflight: bool,
+) -> PyResult<network_submitter::SubmissionResult> {
+    use base64::Engine;
+
+    let tx_bytes = build_atomic_transaction(
+        instruction_payload,
+        payer_key_b58,
+        blockhash_b58,
+        0,
+        0,
+        None,
+        None,
+        Vec::new(),
+        DEFAULT_MAX_SLOT_GAP,
+    )?;
+    let tx_base64 = base64::engine::general_purpose::STANDARD.encode(tx_bytes);
+
+    network_submitter::submit_to_rpc(tx_base64, rpc_url, skip_preflight)
+}
+
 // ------------------------------------------------------------------------
 // SECTION 4: PATHFINDER (GRAPH ENGINE)
 // ------------------------------------------------------------------------
@@ -262,18 +407,67 @@ impl Graph {
         }
     }
 
+    /// Removes the edge from source to target (e.g. after a pool drains).
+    /// Returns true if an edge was removed.
+    fn remove_edge(&mut self, source_mint: String, target_mint: String) -> bool {
+        let (source_id, target_id) = match (
+            self.mint_to_id.get(&source_mint),
+            self.mint_to_id.get(&target_mint),
+        ) {
+            (Some(&s), Some(&t)) => (s, t),
+            _ => return false,
+        };
+
+        let edges = &mut self.adjacency[source_id];
+        let before = edges.len();
+        edges.retain(|e| e.target_id != target_id);
+        edges.len() != before
+    }
+
+    /// Removes every edge backed by the given pool (e.g. after it closes).
+    /// Returns true if any edge was removed.
+    fn remove_pool(&mut self, pool_id: String) -> bool {
+        let mut removed = false;
+        for edges in &mut self.adjacency {
+            let before = edges.len();
+            edges.retain(|e| e.pool_id != pool_id);
+            removed |= edges.len() != before;
+        }
+        removed
+    }
+
     /// SPFA (Shortest Path Faster Algorithm) for Negative Cycle Detection.
     /// Returns a list of Pool IDs forming the arbitrage loop.
-    fn find_arbitrage_loop(&self, start_mint: String) -> PyResult<Vec<String>> {
+    ///
+    /// `max_hops` bounds the path length SPFA will relax along; the returned
+    /// loop never has more than `max_hops` pools.
+    #[pyo3(signature = (start_mint, max_hops=usize::MAX))]
+    fn find_arbitrage_loop(&self, start_mint: String, max_hops: usize) -> PyResult<Vec<String>> {
+        Ok(self.find_arbitrage_loop_scored(start_mint, max_hops)?.0)
+    }
+
+    /// `find_arbitrage_loop` that also returns the loop's total weight
+    /// (sum of -ln(price), negative for a profitable loop), so callers don't
+    /// have to re-multiply rates: profit % = (exp(-weight) - 1) * 100.
+    /// Returns (empty, 0.0) when no loop is found.
+    #[pyo3(signature = (start_mint, max_hops=usize::MAX))]
+    fn find_arbitrage_loop_scored(
+        &self,
+        start_mint: String,
+        max_hops: usize,
+    ) -> PyResult<(Vec<String>, f64)> {
         let start_id = match self.mint_to_id.get(&start_mint) {
             Some(&id) => id,
-            None => return Ok(vec![]), // Token not in graph
+            None => return Ok((vec![], 0.0)), // Token not in graph
         };
 
         let n = self.id_to_mint.len();
+        let bounded = max_hops < n;
         let mut dist = vec![f64::INFINITY; n];
+        let mut depth = vec![0usize; n];
         let mut parent_node = vec![None; n];
         let mut parent_pool = vec![String::new(); n];
+        let mut parent_weight = vec![0.0; n];
         let mut count = vec![0; n];
         let mut in_queue = vec![false; n];
         let mut queue = VecDeque::new();
@@ -285,12 +479,34 @@ impl Graph {
         while let Some(u) = queue.pop_front() {
             in_queue[u] = false;
 
+            // Hop bound: nothing reachable through u fits within max_hops
+            if depth[u] >= max_hops {
+                continue;
+            }
+
             for edge in &self.adjacency[u] {
                 // Relaxation
                 if dist[u] + edge.weight < dist[edge.target_id] {
+                    // Bounded mode: if the target is an ancestor of u, this edge
+                    // closes a negative loop of at most max_hops pools.
+                    if bounded {
+                        if let Some(scored) = self.close_loop(
+                            u,
+                            edge,
+                            max_hops,
+                            &parent_node,
+                            &parent_pool,
+                            &parent_weight,
+                        ) {
+                            return Ok(scored);
+                        }
+                    }
+
                     dist[edge.target_id] = dist[u] + edge.weight;
+                    depth[edge.target_id] = depth[u] + 1;
                     parent_node[edge.target_id] = Some(u);
                     parent_pool[edge.target_id] = edge.pool_id.clone();
+                    parent_weight[edge.target_id] = edge.weight;
 
                     if !in_queue[edge.target_id] {
                         count[edge.target_id] += 1;
@@ -300,11 +516,15 @@ impl Graph {
                         // For arbitrage, we can be more aggressive (e.g. depth > 3).
                         if count[edge.target_id] > n {
                             // Cycle detected! Reconstruct.
-                            return Ok(self.reconstruct_path(
+                            let (path, weight) = self.reconstruct_path(
                                 edge.target_id,
                                 &parent_node,
                                 &parent_pool,
-                            ));
+                                &parent_weight,
+                            );
+                            if !path.is_empty() && path.len() <= max_hops {
+                                return Ok((path, weight));
+                            }
                         }
 
                         queue.push_back(edge.target_id);
@@ -313,62 +533,130 @@ impl Graph {
                 }
             }
         }
-        Ok(vec![])
+        Ok((vec![], 0.0))
     }
 
     /// Scans for arbitrage cycles starting from multiple base tokens.
     /// Returns a list of paths (each path is a list of pool IDs).
     /// Uses Rayon for parallel execution across CPU cores.
+    /// A loop reached from several starts (any rotation of the same pools)
+    /// is returned once.
     fn find_all_cycles(&self, start_mints: Vec<String>) -> PyResult<Vec<Vec<String>>> {
+        Ok(self.find_all_cycles_with_errors(start_mints)?.0)
+    }
+
+    /// `find_all_cycles` plus the number of start mints that couldn't be
+    /// searched: mints not in the graph, or whose search raised. Those starts
+    /// contribute no cycles.
+    fn find_all_cycles_with_errors(
+        &self,
+        start_mints: Vec<String>,
+    ) -> PyResult<(Vec<Vec<String>>, usize)> {
         use rayon::prelude::*;
 
-        // Parallel Iterator
-        let results: Vec<Vec<String>> = start_mints
+        // Parallel Iterator (graph access is read-only, so ThreadSafe)
+        let results: Vec<PyResult<Vec<String>>> = start_mints
             .par_iter()
-            .map(|mint| {
-                // We typically need to handle errors inside map/fold
-                // Graph access is read-only, so ThreadSafe.
-                // However, `find_arbitrage_loop` returns PyResult.
-                // We unwrap or handle here.
-                match self.find_arbitrage_loop(mint.clone()) {
-                    Ok(path) => path,
-                    Err(_) => vec![],
-                }
-            })
-            .filter(|path| !path.is_empty())
+            .map(|mint| self.find_arbitrage_loop(mint.clone(), usize::MAX))
             .collect();
 
-        Ok(results)
+        let mut errors = start_mints
+            .iter()
+            .filter(|mint| !self.mint_to_id.contains_key(*mint))
+            .count();
+        let mut seen = std::collections::HashSet::new();
+        let mut cycles = Vec::new();
+        for result in results {
+            match result {
+                Ok(path) if !path.is_empty() => {
+                    let mut pool_set = path.clone();
+                    pool_set.sort();
+                    if seen.insert(pool_set) {
+                        cycles.push(path);
+                    }
+                }
+                Ok(_) => {}
+                Err(_) => errors += 1,
+            }
+        }
+
+        Ok((cycles, errors))
     }
 }
 
 impl Graph {
+    /// If `edge.target_id` is an ancestor of `u` in the SPFA tree, returns the
+    /// loop target -> ... -> u -> target in forward order, with its total weight.
+    ///
+    /// Only called when `edge` strictly improves the target's distance, which
+    /// along a parent chain implies the closed loop has negative weight.
+    /// Returns None if the loop would exceed `max_hops` pools: once an
+    /// ancestor is re-relaxed, the parent chain can be longer than `depth[u]`.
+    fn close_loop(
+        &self,
+        u: usize,
+        edge: &Edge,
+        max_hops: usize,
+        parent_node: &[Option<usize>],
+        parent_pool: &[String],
+        parent_weight: &[f64],
+    ) -> Option<(Vec<String>, f64)> {
+        let mut path = vec![edge.pool_id.clone()];
+        let mut weight = edge.weight;
+        let mut curr = u;
+
+        while curr != edge.target_id {
+            path.push(parent_pool[curr].clone());
+            weight += parent_weight[curr];
+            curr = parent_node[curr]?;
+            if path.len() > max_hops {
+                return None; // Too long, or stale parent pointers looping elsewhere
+            }
+        }
+
+        path.reverse();
+        Some((path, weight))
+    }
+
+    /// Extracts the negative cycle that `end_id` was relaxed from.
+    /// Returns the pool IDs of exactly the closed loop, in forward order,
+    /// and the loop's total weight.
     fn reconstruct_path(
         &self,
         end_id: usize,
         parent_node: &[Option<usize>],
         parent_pool: &[String],
-    ) -> Vec<String> {
+        parent_weight: &[f64],
+    ) -> (Vec<String>, f64) {
+        // 1. Walk back N times. `end_id` may hang off the cycle on a tail,
+        //    but after N steps we are guaranteed to be on the cycle itself.
+        let mut on_cycle = end_id;
+        for _ in 0..self.id_to_mint.len() {
+            match parent_node[on_cycle] {
+                Some(prev) => on_cycle = prev,
+                None => return (vec![], 0.0), // Chain reached the source: no cycle
+            }
+        }
+
+        // 2. Walk the loop once, collecting pools until we return to the start
         let mut path = Vec::new();
-        let mut curr = end_id;
-        let mut visited = vec![false; self.id_to_mint.len()];
-
-        // Backtrack to find the cycle
-        while let Some(prev) = parent_node[curr] {
-            if visited[curr] {
-                // We closed the loop. Now strictly record the pool IDs.
-                // We need to trace forward from this point or just capture the segment.
-                // Simplified: Just push pool IDs until we loop.
+        let mut weight = 0.0;
+        let mut curr = on_cycle;
+        loop {
+            path.push(parent_pool[curr].clone());
+            weight += parent_weight[curr];
+            curr = match parent_node[curr] {
+                Some(prev) => prev,
+                None => return (vec![], 0.0),
+            };
+            if curr == on_cycle {
                 break;
             }
-            visited[curr] = true;
-            path.push(parent_pool[curr].clone());
-            curr = prev;
         }
 
         // The path is reversed (from end to start)
         path.reverse();
-        path
+        (path, weight)
     }
 }
 
@@ -458,22 +746,30 @@ fn phantom_core(_py: Python, m: &PyModule) -> PyResult<()> {
     m.add_class::<log_parser::SwapEvent>()?;
     m.add_class::<flash_cache::FlashCacheWriter>()?;
     m.add_class::<flash_cache::FlashCacheReader>()?;
+    m.add_class::<flash_cache::CachedPrice>()?;
     m.add_function(wrap_pyfunction!(calculate_net_profit, m)?)?;
     m.add_function(wrap_pyfunction!(calculate_optimal_size, m)?)?;
     m.add_function(wrap_pyfunction!(calculate_net_profit_batch, m)?)?;
+    m.add_function(wrap_pyfunction!(calculate_net_profit_batch_v2, m)?)?;
     m.add_function(wrap_pyfunction!(estimate_compute_units, m)?)?;
+    m.add_function(wrap_pyfunction!(estimate_compute_units_with_table, m)?)?;
     m.add_function(wrap_pyfunction!(validate_gate, m)?)?;
     m.add_function(wrap_pyfunction!(calculate_funding_apr, m)?)?;
     m.add_function(wrap_pyfunction!(calculate_funding_apy, m)?)?;
     m.add_function(wrap_pyfunction!(calculate_basis_yield, m)?)?;
+    m.add_function(wrap_pyfunction!(verify_slot_sync, m)?)?;
     m.add_function(wrap_pyfunction!(build_atomic_transaction, m)?)?;
+    m.add_function(wrap_pyfunction!(build_and_submit_atomic, m)?)?;
     m.add_function(wrap_pyfunction!(log_parser::parse_raydium_log, m)?)?;
     m.add_function(wrap_pyfunction!(log_parser::parse_universal_log, m)?)?;
+    m.add_function(wrap_pyfunction!(log_parser::parse_dlmm_swap, m)?)?;
+    m.add_function(wrap_pyfunction!(log_parser::parse_phoenix_fill, m)?)?;
     
     // Whiff Detection (Asymmetric Intelligence)
     m.add_class::<log_parser::WhiffEvent>()?;
     m.add_function(wrap_pyfunction!(log_parser::parse_whiff_log, m)?)?;
     m.add_function(wrap_pyfunction!(log_parser::parse_whiff_logs_batch, m)?)?;
+    m.add_function(wrap_pyfunction!(log_parser::log_to_whiff, m)?)?;
 
     // AMM Math (The Oracle)
     amm_math::register_amm_functions(m)?;
@@ -526,3 +822,363 @@ fn phantom_core(_py: Python, m: &PyModule) -> PyResult<()> {
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// SOL -> USDC -> BONK -> SOL with a ~1% edge on the loop
+    fn triangle_graph() -> Graph {
+        let mut graph = Graph::new();
+        graph.update_edge("SOL".into(), "USDC".into(), "pool_a".into(), 100.0);
+        graph.update_edge("USDC".into(), "BONK".into(), "pool_b".into(), 50_000.0);
+        graph.update_edge("BONK".into(), "SOL".into(), "pool_c".into(), 0.000_000_202);
+        graph
+    }
+
+    #[test]
+    fn test_reconstruct_exact_cycle() {
+        let mut graph = triangle_graph();
+        // Tail edge into the cycle that must not leak into the result
+        graph.update_edge("SOL".into(), "JUP".into(), "pool_tail".into(), 2.0);
+        graph.update_edge("JUP".into(), "USDC".into(), "pool_tail_2".into(), 10.0);
+
+        let path = graph.find_arbitrage_loop("SOL".into(), usize::MAX).unwrap();
+        assert_eq!(path.len(), 3);
+
+        // Forward order: some rotation of a -> b -> c
+        let expected = ["pool_a", "pool_b", "pool_c"];
+        let offset = expected.iter().position(|p| *p == path[0]).unwrap();
+        for (i, pool) in path.iter().enumerate() {
+            assert_eq!(pool, expected[(offset + i) % 3]);
+        }
+    }
+
+    #[test]
+    fn test_max_hops_excludes_long_cycle() {
+        // Six-hop loop with a ~1% edge, plus spokes so n exceeds max_hops
+        let mut graph = Graph::new();
+        let mints = ["SOL", "USDC", "BONK", "JUP", "WIF", "RAY"];
+        for (i, mint) in mints.iter().enumerate() {
+            let next = mints[(i + 1) % mints.len()];
+            let price = if i == 0 { 1.01 } else { 1.0 };
+            graph.update_edge(mint.to_string(), next.into(), format!("pool_{}", i), price);
+        }
+        graph.update_edge("SOL".into(), "ORCA".into(), "spoke".into(), 0.5);
+
+        let unbounded = graph.find_arbitrage_loop("SOL".into(), usize::MAX).unwrap();
+        assert_eq!(unbounded.len(), 6);
+
+        assert!(graph.find_arbitrage_loop("SOL".into(), 4).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_max_hops_keeps_short_cycle() {
+        let mut graph = triangle_graph();
+        for i in 0..5 {
+            graph.update_edge("SOL".into(), format!("SPOKE{}", i), format!("spoke_{}", i), 1.0);
+        }
+
+        let path = graph.find_arbitrage_loop("SOL".into(), 4).unwrap();
+        assert_eq!(path.len(), 3);
+    }
+
+    #[test]
+    fn test_find_all_cycles_returns_shared_loop_once() {
+        let mut graph = triangle_graph();
+        // A second, disjoint loop through JUP
+        graph.update_edge("JUP".into(), "WIF".into(), "pool_d".into(), 2.0);
+        graph.update_edge("WIF".into(), "JUP".into(), "pool_e".into(), 0.51);
+
+        // SOL and USDC both sit on the triangle; UNKNOWN isn't in the graph
+        let starts = vec!["SOL".into(), "USDC".into(), "JUP".into(), "UNKNOWN".into()];
+        let (cycles, errors) = graph.find_all_cycles_with_errors(starts.clone()).unwrap();
+        assert_eq!(errors, 1);
+        assert_eq!(cycles.len(), 2);
+
+        let mut triangle = cycles[0].clone();
+        triangle.sort();
+        assert_eq!(triangle, ["pool_a", "pool_b", "pool_c"]);
+        let mut pair = cycles[1].clone();
+        pair.sort();
+        assert_eq!(pair, ["pool_d", "pool_e"]);
+
+        assert_eq!(graph.find_all_cycles(starts).unwrap(), cycles);
+    }
+
+    #[test]
+    fn test_find_all_cycles_counts_unknown_start() {
+        let graph = triangle_graph();
+        let starts = vec!["SOL".into(), "NOT_A_MINT".into()];
+        let (cycles, errors) = graph.find_all_cycles_with_errors(starts).unwrap();
+        assert_eq!(errors, 1);
+        assert_eq!(cycles.len(), 1);
+    }
+
+    #[test]
+    fn test_scored_loop_reports_profit() {
+        // SOL -> USDC -> BONK -> SOL multiplies out to exactly 1.02
+        let mut graph = Graph::new();
+        graph.update_edge("SOL".into(), "USDC".into(), "pool_a".into(), 100.0);
+        graph.update_edge("USDC".into(), "BONK".into(), "pool_b".into(), 50_000.0);
+        graph.update_edge("BONK".into(), "SOL".into(), "pool_c".into(), 0.000_000_204);
+        // Spokes so max_hops = 4 takes the bounded (close_loop) path
+        for i in 0..5 {
+            graph.update_edge("SOL".into(), format!("SPOKE{}", i), format!("spoke_{}", i), 1.0);
+        }
+
+        for max_hops in [usize::MAX, 4] {
+            let (path, weight) = graph.find_arbitrage_loop_scored("SOL".into(), max_hops).unwrap();
+            assert_eq!(path.len(), 3);
+            assert!(weight < 0.0);
+            let profit_pct = ((-weight).exp() - 1.0) * 100.0;
+            assert!((profit_pct - 2.0).abs() < 1e-6, "profit {}%", profit_pct);
+            assert_eq!(graph.find_arbitrage_loop("SOL".into(), max_hops).unwrap(), path);
+        }
+
+        assert_eq!(graph.find_arbitrage_loop_scored("UNKNOWN".into(), usize::MAX).unwrap(), (vec![], 0.0));
+    }
+
+    #[test]
+    fn test_max_hops_holds_after_ancestor_rerelaxed() {
+        // BONK is first reached via SOL -> JUP -> BONK, then re-relaxed via
+        // the longer SOL -> USDC -> JUP -> BONK chain before BONK -> SOL
+        // closes the loop. Following parent pointers from there yields a
+        // 4-pool loop, which must not be returned with max_hops = 3.
+        let mut graph = Graph::new();
+        let price = |weight: f64| (-weight).exp();
+        graph.update_edge("BONK".into(), "SOL".into(), "p_bonk_sol".into(), price(0.0));
+        graph.update_edge("SOL".into(), "JUP".into(), "p_sol_jup".into(), price(-0.1));
+        graph.update_edge("SOL".into(), "USDC".into(), "p_sol_usdc".into(), price(-0.3));
+        graph.update_edge("BONK".into(), "USDC".into(), "p_bonk_usdc".into(), price(0.5));
+        graph.update_edge("USDC".into(), "JUP".into(), "p_usdc_jup".into(), price(-0.1));
+        graph.update_edge("JUP".into(), "BONK".into(), "p_jup_bonk".into(), price(-0.1));
+        // Unreachable spoke so the search runs in bounded mode (n > max_hops)
+        graph.update_edge("WIF".into(), "SOL".into(), "p_wif_sol".into(), 1.0);
+
+        assert_eq!(graph.find_arbitrage_loop("SOL".into(), usize::MAX).unwrap().len(), 4);
+        for max_hops in 2..=4 {
+            let (path, _) = graph.find_arbitrage_loop_scored("SOL".into(), max_hops).unwrap();
+            assert!(path.len() <= max_hops, "{} pools for max_hops {}", path.len(), max_hops);
+        }
+    }
+
+    #[test]
+    fn test_net_profit_batch_v2() {
+        let net = calculate_net_profit_batch_v2(
+            vec![1.0, 0.5],
+            vec![1000.0, 200.0],
+            vec![0.5, 0.1],
+            vec![1.0, 0.2],
+        )
+        .unwrap();
+        assert_eq!(net, vec![10.0 - 0.5 - 1.0, 1.0 - 0.1 - 0.2]);
+
+        assert!(calculate_net_profit_batch_v2(vec![1.0], vec![], vec![0.1], vec![0.1]).is_err());
+    }
+
+    #[test]
+    fn test_compute_units_custom_table() {
+        let ops = vec!["raydium_swap_v4".to_string(), "memo".to_string()];
+        let default = estimate_compute_units(ops.clone(), 10, 1, 0.0).unwrap();
+        assert_eq!(default, 80_000 + 100 + 8_500 + 1_500);
+
+        // Override one op; the other keeps its built-in cost
+        let mut table = HashMap::new();
+        table.insert("raydium_swap_v4".to_string(), 60_000.0);
+        let custom = estimate_compute_units_with_table(ops, 10, 1, 0.0, table).unwrap();
+        assert_eq!(custom, 60_000 + 100 + 8_500 + 1_500);
+    }
+
+    #[test]
+    fn test_atomic_transaction_compute_budget_first() {
+        use solana_sdk::compute_budget;
+
+        let payer = Keypair::new();
+        let payload = Instruction::new_with_bytes(Pubkey::new_unique(), &[1, 2, 3], vec![]);
+
+        let raw = build_atomic_transaction(
+            bincode::serialize(&payload).unwrap(),
+            payer.to_base58_string(),
+            Hash::new_unique().to_string(),
+            100,
+            0,
+            Some(400_000),
+            Some(10_000),
+            Vec::new(),
+            DEFAULT_MAX_SLOT_GAP,
+        )
+        .unwrap();
+
+        let tx: VersionedTransaction = bincode::deserialize(&raw).unwrap();
+        let keys = tx.message.static_account_keys();
+        let ixs = tx.message.instructions();
+        assert_eq!(ixs.len(), 3);
+
+        let program = |i: usize| keys[ixs[i].program_id_index as usize];
+        assert_eq!(program(0), compute_budget::id());
+        assert_eq!(program(1), compute_budget::id());
+        assert_eq!(program(2), payload.program_id);
+
+        assert_eq!(ixs[0].data, ComputeBudgetInstruction::set_compute_unit_limit(400_000).data);
+        assert_eq!(ixs[1].data, ComputeBudgetInstruction::set_compute_unit_price(10_000).data);
+        assert!(tx.verify_with_results().iter().all(|ok| *ok));
+    }
+
+    #[test]
+    fn test_atomic_transaction_with_lookup_table() {
+        use solana_sdk::instruction::AccountMeta;
+
+        let payer = Keypair::new();
+        let table_key = Pubkey::new_unique();
+        let pool = Pubkey::new_unique();
+        let vault = Pubkey::new_unique();
+        let payload = Instruction::new_with_bytes(
+            Pubkey::new_unique(),
+            &[9],
+            vec![AccountMeta::new(pool, false), AccountMeta::new_readonly(vault, false)],
+        );
+
+        let raw = build_atomic_transaction(
+            bincode::serialize(&payload).unwrap(),
+            payer.to_base58_string(),
+            Hash::new_unique().to_string(),
+            100,
+            0,
+            None,
+            None,
+            vec![(table_key.to_string(), vec![pool.to_string(), vault.to_string()])],
+            DEFAULT_MAX_SLOT_GAP,
+        )
+        .unwrap();
+
+        let tx: VersionedTransaction = bincode::deserialize(&raw).unwrap();
+        let lookups = match &tx.message {
+            VersionedMessage::V0(message) => &message.address_table_lookups,
+            VersionedMessage::Legacy(_) => panic!("expected a v0 message"),
+        };
+        assert_eq!(lookups.len(), 1);
+        assert_eq!(lookups[0].account_key, table_key);
+        assert_eq!(lookups[0].writable_indexes, vec![0]);
+        assert_eq!(lookups[0].readonly_indexes, vec![1]);
+
+        // Still signed by the payer
+        assert_eq!(tx.message.static_account_keys()[0], payer.pubkey());
+        assert!(tx.verify_with_results().iter().all(|ok| *ok));
+    }
+
+    #[test]
+    fn test_remove_edge_breaks_cycle() {
+        let mut graph = triangle_graph();
+        assert!(!graph.find_arbitrage_loop("SOL".into(), usize::MAX).unwrap().is_empty());
+
+        assert!(graph.remove_edge("USDC".into(), "BONK".into()));
+        assert!(!graph.remove_edge("USDC".into(), "BONK".into()));
+        assert!(!graph.remove_edge("USDC".into(), "UNKNOWN".into()));
+        assert!(graph.find_arbitrage_loop("SOL".into(), usize::MAX).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_remove_pool_breaks_cycle() {
+        let mut graph = triangle_graph();
+
+        assert!(graph.remove_pool("pool_c".into()));
+        assert!(!graph.remove_pool("pool_c".into()));
+        assert!(graph.find_arbitrage_loop("SOL".into(), usize::MAX).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_build_and_submit_atomic_posts_signed_tx() {
+        let rpc = network_submitter::tests::mock_rpc(vec![(
+            0,
+            r#"{"jsonrpc":"2.0","id":1,"result":"atomicsig"}"#,
+        )]);
+        let payer = Keypair::new();
+        let memo = Instruction::new_with_bytes(
+            Pubkey::from_str("MemoSq4gqABAXKb96qnH8TysNcWxMyWCqXgDLGmfcHr").unwrap(),
+            b"phantom",
+            vec![solana_sdk::instruction::AccountMeta::new_readonly(payer.pubkey(), true)],
+        );
+
+        let result = build_and_submit_atomic(
+            bincode::serialize(&memo).unwrap(),
+            payer.to_base58_string(),
+            Hash::default().to_string(),
+            rpc.clone(),
+            true,
+        )
+        .unwrap();
+        assert!(result.success);
+        assert_eq!(result.signature.as_deref(), Some("atomicsig"));
+        assert_eq!(result.endpoint, rpc);
+
+        // Submission failures are reported, not raised
+        let rpc_err = network_submitter::tests::mock_rpc(vec![(
+            0,
+            r#"{"jsonrpc":"2.0","id":1,"error":{"code":-32002,"message":"Blockhash not found"}}"#,
+        )]);
+        let result = build_and_submit_atomic(
+            bincode::serialize(&memo).unwrap(),
+            payer.to_base58_string(),
+            Hash::default().to_string(),
+            rpc_err,
+            true,
+        )
+        .unwrap();
+        assert!(!result.success);
+        assert!(result.error.unwrap().contains("Blockhash not found"));
+
+        // A bad payload never reaches the network
+        assert!(build_and_submit_atomic(vec![1, 2, 3], payer.to_base58_string(), Hash::default().to_string(), rpc, true).is_err());
+    }
+
+    #[test]
+    fn test_verify_slot_sync_in_sync() {
+        assert!(verify_slot_sync(1_000, 1_000, DEFAULT_MAX_SLOT_GAP));
+        assert!(verify_slot_sync(1_001, 1_000, DEFAULT_MAX_SLOT_GAP));
+        assert!(ensure_slot_sync(1_000, 1_001, DEFAULT_MAX_SLOT_GAP).is_ok());
+    }
+
+    #[test]
+    fn test_verify_slot_sync_at_threshold() {
+        // Gap equal to max_gap is still in sync, either side ahead
+        assert!(verify_slot_sync(1_002, 1_000, 2));
+        assert!(verify_slot_sync(1_000, 1_002, 2));
+        assert!(verify_slot_sync(1_005, 1_000, 5));
+        assert!(ensure_slot_sync(1_000, 1_002, 2).is_ok());
+    }
+
+    #[test]
+    fn test_verify_slot_sync_over_threshold() {
+        pyo3::prepare_freethreaded_python();
+        assert!(!verify_slot_sync(1_003, 1_000, 2));
+        assert!(!verify_slot_sync(1_000, 1_003, 2));
+        assert!(!verify_slot_sync(1, 0, 0));
+        let err = ensure_slot_sync(1_000, 1_003, 2).unwrap_err();
+        assert!(err.to_string().contains("Gap is 3 slots"));
+
+        // The builder honours a configured gap
+        let payer = Keypair::new();
+        let memo = bincode::serialize(&Instruction::new_with_bytes(
+            Pubkey::from_str("MemoSq4gqABAXKb96qnH8TysNcWxMyWCqXgDLGmfcHr").unwrap(),
+            b"phantom",
+            vec![solana_sdk::instruction::AccountMeta::new_readonly(payer.pubkey(), true)],
+        ))
+        .unwrap();
+        let build = |max_gap| {
+            build_atomic_transaction(
+                memo.clone(),
+                payer.to_base58_string(),
+                Hash::default().to_string(),
+                1_000,
+                1_004,
+                None,
+                None,
+                Vec::new(),
+                max_gap,
+            )
+        };
+        assert!(build(DEFAULT_MAX_SLOT_GAP).is_err());
+        assert!(build(4).is_ok());
+    }
+}
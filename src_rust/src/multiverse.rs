@@ -18,9 +18,85 @@
 // - 5-Hops: O(E * avg_degree³) - High (with pruning)
 // ------------------------------------------------------------------------
 
+use crate::amm_math::compute_amm_out;
 use crate::graph::HopGraph;
 use pyo3::prelude::*;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+/// Default bound on memoized return-path entries per hop level
+const DEFAULT_MEMO_CAPACITY: usize = 100_000;
+
+/// (start_mint, current_mint, remaining_hops)
+type MemoKey = (String, String, usize);
+
+/// Bounded LRU memo: MemoKey -> lower bound on return weight.
+/// Evicting is always safe, a missing entry is just recomputed.
+struct ReturnWeightMemo {
+    /// key -> (value, recency tick)
+    entries: HashMap<MemoKey, (f64, u64)>,
+    /// recency tick -> key, oldest first
+    recency: BTreeMap<u64, MemoKey>,
+    tick: u64,
+    capacity: usize,
+    /// Lookups answered from the memo
+    hits: usize,
+}
+
+impl ReturnWeightMemo {
+    fn new(capacity: usize) -> Self {
+        Self {
+            entries: HashMap::new(),
+            recency: BTreeMap::new(),
+            tick: 0,
+            capacity: capacity.max(1),
+            hits: 0,
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    fn clear(&mut self) {
+        self.entries.clear();
+        self.recency.clear();
+        self.hits = 0;
+    }
+
+    /// Look up a key, counting the hit and marking it most recently used.
+    fn get(&mut self, key: &MemoKey) -> Option<f64> {
+        let (value, tick) = self.entries.get_mut(key)?;
+        self.tick += 1;
+        let key = self.recency.remove(tick)?;
+        *tick = self.tick;
+        self.recency.insert(self.tick, key);
+        self.hits += 1;
+        Some(*value)
+    }
+
+    /// Insert a key, evicting the least recently used entry when full.
+    fn insert(&mut self, key: MemoKey, value: f64) {
+        self.tick += 1;
+        if let Some((_, old_tick)) = self.entries.insert(key.clone(), (value, self.tick)) {
+            self.recency.remove(&old_tick);
+        } else if self.entries.len() > self.capacity {
+            if let Some((_, oldest)) = self.recency.pop_first() {
+                self.entries.remove(&oldest);
+            }
+        }
+        self.recency.insert(self.tick, key);
+    }
+
+    /// Move another memo's entries in, oldest first, so its recency carries over.
+    fn absorb(&mut self, other: ReturnWeightMemo) {
+        let mut entries = other.entries;
+        for (_, key) in other.recency {
+            if let Some((value, _)) = entries.remove(&key) {
+                self.insert(key, value);
+            }
+        }
+    }
+}
 
 /// Result of a multiverse scan - grouped by hop count
 #[pyclass]
@@ -50,6 +126,7 @@ pub struct ScanStats {
     pub paths_explored: usize,
     #[pyo3(get)]
     pub paths_pruned: usize,
+    /// Return-path lookups answered from the memo
     #[pyo3(get)]
     pub memoization_hits: usize,
 }
@@ -101,6 +178,57 @@ impl MultiverseCycle {
         self.profit_pct > (fee_impact + gas_impact_pct)
     }
 
+    /// Find the input size that maximizes net output through this cycle.
+    ///
+    /// `reserves` holds (reserve_in, reserve_out) for each hop in order, in
+    /// the smallest unit of each hop's input/output token. Each hop charges
+    /// the cycle's average fee (`total_fee_bps / hop_count`). The composed
+    /// constant-product output minus input is unimodal, so a ternary search
+    /// over [0, first reserve_in] finds the peak.
+    ///
+    /// Returns (best_input, best_net_profit_pct); (0, 0.0) if no size profits.
+    pub fn optimal_size_and_profit(&self, reserves: Vec<(u64, u64)>) -> PyResult<(u64, f64)> {
+        if reserves.is_empty() || reserves.len() != self.hop_count {
+            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                "Expected {} (reserve_in, reserve_out) pairs, got {}",
+                self.hop_count,
+                reserves.len()
+            )));
+        }
+
+        let fee_bps = self.total_fee_bps as u64 / self.hop_count as u64;
+        let net_out = |amount_in: u64| -> i128 {
+            let mut amount = amount_in;
+            for &(reserve_in, reserve_out) in &reserves {
+                amount = compute_amm_out(amount, reserve_in, reserve_out, fee_bps).unwrap_or(0);
+            }
+            amount as i128 - amount_in as i128
+        };
+
+        let (mut lo, mut hi) = (0u64, reserves[0].0);
+        while hi - lo > 2 {
+            let m1 = lo + (hi - lo) / 3;
+            let m2 = hi - (hi - lo) / 3;
+            if net_out(m1) < net_out(m2) {
+                lo = m1 + 1;
+            } else {
+                hi = m2;
+            }
+        }
+
+        // Integer rounding leaves a tiny window; check it exhaustively
+        let (best_input, best_net) = (lo..=hi)
+            .map(|x| (x, net_out(x)))
+            .max_by_key(|&(_, net)| net)
+            .unwrap_or((0, 0));
+
+        if best_net <= 0 || best_input == 0 {
+            return Ok((0, 0.0));
+        }
+
+        Ok((best_input, best_net as f64 / best_input as f64 * 100.0))
+    }
+
     pub fn __repr__(&self) -> String {
         let path_short: Vec<String> = self
             .path
@@ -127,8 +255,8 @@ pub struct MultiverseScanner {
     /// Maximum hops to consider (default: 5)
     max_hops: usize,
 
-    /// Minimum profit threshold per hop level (indexed by hop count)
-    /// e.g., thresholds[3] = 0.1 means 3-hop needs 0.1% profit
+    /// Minimum profit threshold per hop level (indexed by hop count), as a decimal
+    /// e.g., thresholds[3] = 0.001 means 3-hop needs 0.1% profit
     min_profit_thresholds: HashMap<usize, f64>,
 
     /// Minimum liquidity threshold in USD
@@ -137,9 +265,21 @@ pub struct MultiverseScanner {
     /// Maximum cycles to return per hop level
     max_cycles_per_level: usize,
 
-    /// Memoization cache for sub-path profitability
-    /// Key: (start_mint, end_mint, hops) -> best_weight
-    memo_cache: HashMap<(String, String, usize), f64>,
+    /// Mints never routed through (honeypots, transfer-tax traps)
+    blocklist: HashSet<String>,
+
+    /// Memoization cache for return-path lower bounds
+    /// Key: (start_mint, current_mint, remaining_hops) -> lowest weight of any
+    /// walk from current_mint back to start_mint in exactly remaining_hops.
+    /// The walk ignores the no-revisit rule, so it is a relaxation of the DFS
+    /// and never larger than the weight of a real completion. That makes the
+    /// value independent of the path taken to current_mint and safe to prune on.
+    /// Each hop level's memo, and this merged one, hold at most
+    /// `memo_capacity` entries (least recently used evicted first).
+    memo_cache: ReturnWeightMemo,
+
+    /// Bound on memo entries
+    memo_capacity: usize,
 }
 
 #[pymethods]
@@ -149,20 +289,22 @@ impl MultiverseScanner {
         min_hops = 2,
         max_hops = 5,
         min_liquidity_usd = 5000,
-        max_cycles_per_level = 50
+        max_cycles_per_level = 50,
+        memo_capacity = DEFAULT_MEMO_CAPACITY
     ))]
     pub fn new(
         min_hops: usize,
         max_hops: usize,
         min_liquidity_usd: u64,
         max_cycles_per_level: usize,
+        memo_capacity: usize,
     ) -> Self {
         // Default profit thresholds (higher hops = lower threshold since more fee accumulation)
         let mut thresholds = HashMap::new();
-        thresholds.insert(2, 0.20); // 2-hop: need 0.20% (high competition)
-        thresholds.insert(3, 0.15); // 3-hop: need 0.15%
-        thresholds.insert(4, 0.10); // 4-hop: need 0.10% (the alpha zone)
-        thresholds.insert(5, 0.08); // 5-hop: need 0.08% (deep path exploration)
+        thresholds.insert(2, 0.0020); // 2-hop: need 0.20% (high competition)
+        thresholds.insert(3, 0.0015); // 3-hop: need 0.15%
+        thresholds.insert(4, 0.0010); // 4-hop: need 0.10% (the alpha zone)
+        thresholds.insert(5, 0.0008); // 5-hop: need 0.08% (deep path exploration)
 
         Self {
             min_hops: min_hops.clamp(2, 5),
@@ -170,7 +312,9 @@ impl MultiverseScanner {
             min_profit_thresholds: thresholds,
             min_liquidity_usd,
             max_cycles_per_level,
-            memo_cache: HashMap::new(),
+            blocklist: HashSet::new(),
+            memo_cache: ReturnWeightMemo::new(memo_capacity),
+            memo_capacity,
         }
     }
 
@@ -182,8 +326,49 @@ impl MultiverseScanner {
         }
     }
 
-    /// Scan the graph for all profitable cycles across all hop levels
+    /// Replace the blocklist: cycles never pass through these mints.
+    pub fn set_blocklist(&mut self, mints: Vec<String>) {
+        self.blocklist = mints.into_iter().collect();
+    }
+
+    /// Allow routing through every mint again
+    pub fn clear_blocklist(&mut self) {
+        self.blocklist.clear();
+    }
+
+    /// Scan the graph for all profitable cycles across all hop levels.
+    /// Hop levels run concurrently; the graph is only read during a scan.
     pub fn scan_multiverse(&mut self, graph: &HopGraph, start_mint: &str) -> MultiverseResult {
+        self.scan_levels(graph, start_mint, true)
+    }
+
+    /// Get scanner configuration
+    pub fn get_config(&self) -> HashMap<String, f64> {
+        let mut config = HashMap::new();
+        config.insert("min_hops".to_string(), self.min_hops as f64);
+        config.insert("max_hops".to_string(), self.max_hops as f64);
+        config.insert(
+            "min_liquidity_usd".to_string(),
+            self.min_liquidity_usd as f64,
+        );
+        config.insert("memo_capacity".to_string(), self.memo_capacity as f64);
+        config
+    }
+
+    /// Entries held in the memoization cache from the last scan
+    pub fn cache_size(&self) -> usize {
+        self.memo_cache.len()
+    }
+
+    /// Clear the memoization cache
+    pub fn clear_cache(&mut self) {
+        self.memo_cache.clear();
+    }
+}
+
+impl MultiverseScanner {
+    /// Full scan; `parallel` = false runs the levels one after another.
+    fn scan_levels(&mut self, graph: &HopGraph, start_mint: &str, parallel: bool) -> MultiverseResult {
         use std::time::Instant;
         let start_time = Instant::now();
 
@@ -202,16 +387,28 @@ impl MultiverseScanner {
             };
         }
 
-        // Tiered DFS for each hop level
-        for hop_level in self.min_hops..=self.max_hops {
-            let threshold = self
-                .min_profit_thresholds
-                .get(&hop_level)
-                .copied()
-                .unwrap_or(0.10);
+        // Tiered DFS, one task per hop level with its own memo and stats
+        let levels: Vec<usize> = (self.min_hops..=self.max_hops).collect();
+        let scanner = &*self;
+        let level_results: Vec<(usize, Vec<MultiverseCycle>, ScanStats, ReturnWeightMemo)> =
+            if parallel {
+                use rayon::prelude::*;
+                levels
+                    .par_iter()
+                    .map(|&hop_level| scanner.scan_level(graph, start_mint, hop_level))
+                    .collect()
+            } else {
+                levels
+                    .iter()
+                    .map(|&hop_level| scanner.scan_level(graph, start_mint, hop_level))
+                    .collect()
+            };
 
-            let cycles =
-                self.find_cycles_at_level(graph, start_mint, hop_level, threshold, &mut stats);
+        for (hop_level, cycles, level_stats, memo) in level_results {
+            stats.paths_explored += level_stats.paths_explored;
+            stats.paths_pruned += level_stats.paths_pruned;
+            stats.memoization_hits += level_stats.memoization_hits;
+            self.memo_cache.absorb(memo);
 
             if !cycles.is_empty() {
                 all_cycles.insert(hop_level, cycles);
@@ -227,7 +424,6 @@ impl MultiverseScanner {
 
         stats.total_cycles_found = all_cycles.values().map(|v| v.len()).sum();
         stats.scan_time_ms = start_time.elapsed().as_secs_f64() * 1000.0;
-        stats.memoization_hits = self.memo_cache.len();
 
         MultiverseResult {
             cycles_by_hops: all_cycles,
@@ -236,40 +432,44 @@ impl MultiverseScanner {
         }
     }
 
-    /// Get scanner configuration
-    pub fn get_config(&self) -> HashMap<String, f64> {
-        let mut config = HashMap::new();
-        config.insert("min_hops".to_string(), self.min_hops as f64);
-        config.insert("max_hops".to_string(), self.max_hops as f64);
-        config.insert(
-            "min_liquidity_usd".to_string(),
-            self.min_liquidity_usd as f64,
-        );
-        config
-    }
+    /// Search one hop level with a fresh memo and stats.
+    fn scan_level(
+        &self,
+        graph: &HopGraph,
+        start_mint: &str,
+        hop_level: usize,
+    ) -> (usize, Vec<MultiverseCycle>, ScanStats, ReturnWeightMemo) {
+        let threshold = self
+            .min_profit_thresholds
+            .get(&hop_level)
+            .copied()
+            .unwrap_or(0.0010);
 
-    /// Clear the memoization cache
-    pub fn clear_cache(&mut self) {
-        self.memo_cache.clear();
+        let mut stats = ScanStats::default();
+        let mut memo = ReturnWeightMemo::new(self.memo_capacity);
+        let cycles =
+            self.find_cycles_at_level(graph, start_mint, hop_level, threshold, &mut stats, &mut memo);
+        stats.memoization_hits = memo.hits;
+
+        (hop_level, cycles, stats, memo)
     }
-}
 
-impl MultiverseScanner {
     /// Find cycles at a specific hop level
     fn find_cycles_at_level(
-        &mut self,
+        &self,
         graph: &HopGraph,
         start_mint: &str,
         target_hops: usize,
         min_profit: f64,
         stats: &mut ScanStats,
+        memo: &mut ReturnWeightMemo,
     ) -> Vec<MultiverseCycle> {
         let mut cycles = Vec::new();
 
-        let initial_edges = graph.get_outbound(start_mint);
-
-        for edge in initial_edges {
-            if edge.liquidity_usd < self.min_liquidity_usd {
+        for edge in graph.outbound_ref(start_mint) {
+            if edge.liquidity_usd < self.min_liquidity_usd
+                || self.blocklist.contains(&edge.target_mint)
+            {
                 stats.paths_pruned += 1;
                 continue;
             }
@@ -289,6 +489,7 @@ impl MultiverseScanner {
                 min_profit,
                 &mut cycles,
                 stats,
+                memo,
             );
         }
 
@@ -299,10 +500,44 @@ impl MultiverseScanner {
         cycles
     }
 
+    /// Lowest weight of any walk from `current_mint` to `start_mint` in exactly
+    /// `remaining` hops over edges passing the liquidity and blocklist filters
+    /// (INFINITY if none).
+    /// Memoized per scan; see `memo_cache` for why this is a valid lower bound.
+    fn best_return_weight(
+        &self,
+        graph: &HopGraph,
+        start_mint: &str,
+        current_mint: &str,
+        remaining: usize,
+        memo: &mut ReturnWeightMemo,
+    ) -> f64 {
+        if remaining == 0 {
+            return if current_mint == start_mint { 0.0 } else { f64::INFINITY };
+        }
+
+        let memo_key = (start_mint.to_string(), current_mint.to_string(), remaining);
+        if let Some(cached) = memo.get(&memo_key) {
+            return cached;
+        }
+
+        let mut best = f64::INFINITY;
+        for edge in graph.outbound_ref(current_mint) {
+            if edge.liquidity_usd < self.min_liquidity_usd || self.blocklist.contains(&edge.target_mint) {
+                continue;
+            }
+            let rest = self.best_return_weight(graph, start_mint, &edge.target_mint, remaining - 1, memo);
+            best = best.min(edge.weight + rest);
+        }
+
+        memo.insert(memo_key, best);
+        best
+    }
+
     /// DFS that finds cycles at EXACTLY target_hops depth
     #[allow(clippy::too_many_arguments)]
     fn dfs_exact_hops(
-        &mut self,
+        &self,
         graph: &HopGraph,
         start_mint: &str,
         current_mint: &str,
@@ -317,29 +552,33 @@ impl MultiverseScanner {
         min_profit: f64,
         results: &mut Vec<MultiverseCycle>,
         stats: &mut ScanStats,
+        memo: &mut ReturnWeightMemo,
     ) {
         stats.paths_explored += 1;
 
-        // Check memoization for sub-path pruning
-        let memo_key = (start_mint.to_string(), current_mint.to_string(), depth);
-        if let Some(&cached_weight) = self.memo_cache.get(&memo_key) {
-            // If we've seen a better path to this point, prune
-            if total_weight > cached_weight {
-                stats.paths_pruned += 1;
-                return;
-            }
+        // Prune only when even the best possible return leg can't reach
+        // min_profit: profit >= min_profit  <=>  weight <= -ln(1 + min_profit)
+        let max_cycle_weight = -(1.0 + min_profit).ln();
+        let best_return =
+            self.best_return_weight(graph, start_mint, current_mint, target_hops - depth, memo);
+        if total_weight + best_return > max_cycle_weight + 1e-12 {
+            stats.paths_pruned += 1;
+            return;
         }
-        self.memo_cache.insert(memo_key, total_weight);
 
-        let edges = graph.get_outbound(current_mint);
-
-        for edge in edges {
+        for edge in graph.outbound_ref(current_mint) {
             // Liquidity pruning
             if edge.liquidity_usd < self.min_liquidity_usd {
                 stats.paths_pruned += 1;
                 continue;
             }
 
+            // Blocked tokens (honeypots)
+            if self.blocklist.contains(&edge.target_mint) {
+                stats.paths_pruned += 1;
+                continue;
+            }
+
             // Cycle detection for intermediate nodes (not start)
             if path[1..].contains(&edge.target_mint) && edge.target_mint != start_mint {
                 continue;
@@ -383,15 +622,6 @@ impl MultiverseScanner {
 
             // Continue DFS if we need more hops
             if depth < target_hops - 1 {
-                // Early pruning: if weight is already too positive, skip
-                // (We can't possibly reach a profitable cycle)
-                let remaining_hops = target_hops - depth - 1;
-                let optimistic_remaining = -0.003 * remaining_hops as f64; // Best case: 0.3% per hop
-                if new_weight + optimistic_remaining > 0.0 {
-                    stats.paths_pruned += 1;
-                    continue;
-                }
-
                 let mut new_path = path.clone();
                 new_path.push(edge.target_mint.clone());
 
@@ -416,6 +646,7 @@ impl MultiverseScanner {
                     min_profit,
                     results,
                     stats,
+                    memo,
                 );
             }
         }
@@ -516,7 +747,7 @@ mod tests {
     #[test]
     fn test_multiverse_scan() {
         let graph = create_multi_hop_graph();
-        let mut scanner = MultiverseScanner::new(2, 4, 100_000, 10);
+        let mut scanner = MultiverseScanner::new(2, 4, 100_000, 10, DEFAULT_MEMO_CAPACITY);
 
         let result = scanner.scan_multiverse(&graph, "SOL");
 
@@ -538,14 +769,188 @@ mod tests {
         println!("Best cycle: {}", best.__repr__());
     }
 
+    #[test]
+    fn test_four_hop_cycle_survives_full_scan() {
+        let graph = create_multi_hop_graph();
+        let mut scanner = MultiverseScanner::new(2, 5, 100_000, 50, DEFAULT_MEMO_CAPACITY);
+
+        let result = scanner.scan_multiverse(&graph, "SOL");
+        let four_hop = result.cycles_by_hops.get(&4).expect("4-hop level should be populated");
+        assert!(four_hop
+            .iter()
+            .any(|c| c.pool_addresses == vec!["p_sol_usdc", "p_usdc_bonk", "p_bonk_wif", "p_wif_sol"]));
+    }
+
+    #[test]
+    fn test_better_prefix_does_not_hide_cycle() {
+        // SOL -> D -> C reaches C cheaper than SOL -> A -> C, but can't close
+        // (C only leads back to D). The only 4-hop cycle is SOL -> A -> C -> D -> SOL.
+        let mut graph = HopGraph::new();
+        let edge = |src: &str, dst: &str, pool: &str, rate: f64| {
+            PoolEdge::new(src.to_string(), dst.to_string(), pool.to_string(), rate, 25, 1_000_000, 1000, "TEST")
+        };
+        graph.update_edge(edge("SOL", "D", "p_sol_d", 10.0));
+        graph.update_edge(edge("D", "C", "p_d_c", 10.0));
+        graph.update_edge(edge("SOL", "A", "p_sol_a", 2.0));
+        graph.update_edge(edge("A", "C", "p_a_c", 2.0));
+        graph.update_edge(edge("C", "D", "p_c_d", 2.6));
+        graph.update_edge(edge("D", "SOL", "p_d_sol", 0.0999));
+
+        let mut scanner = MultiverseScanner::new(2, 5, 100_000, 50, DEFAULT_MEMO_CAPACITY);
+        let result = scanner.scan_multiverse(&graph, "SOL");
+
+        let four_hop = result.cycles_by_hops.get(&4).expect("4-hop cycle was pruned");
+        assert_eq!(four_hop.len(), 1);
+        assert_eq!(four_hop[0].path, vec!["SOL", "A", "C", "D", "SOL"]);
+        assert!((four_hop[0].profit_pct - 3.896).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_parallel_scan_matches_serial() {
+        let graph = create_multi_hop_graph();
+        let mut scanner = MultiverseScanner::new(2, 5, 100_000, 50, DEFAULT_MEMO_CAPACITY);
+
+        let serial = scanner.scan_levels(&graph, "SOL", false);
+        let parallel = scanner.scan_multiverse(&graph, "SOL");
+
+        let mut levels: Vec<_> = parallel.cycles_by_hops.keys().copied().collect();
+        levels.sort();
+        let mut serial_levels: Vec<_> = serial.cycles_by_hops.keys().copied().collect();
+        serial_levels.sort();
+        assert_eq!(levels, serial_levels);
+
+        for level in levels {
+            let a: Vec<_> = serial.cycles_by_hops[&level].iter().map(|c| c.pool_addresses.clone()).collect();
+            let b: Vec<_> = parallel.cycles_by_hops[&level].iter().map(|c| c.pool_addresses.clone()).collect();
+            assert_eq!(a, b, "level {} differs", level);
+        }
+
+        assert_eq!(serial.scan_stats.total_cycles_found, parallel.scan_stats.total_cycles_found);
+        assert_eq!(serial.scan_stats.paths_explored, parallel.scan_stats.paths_explored);
+        assert_eq!(serial.scan_stats.paths_pruned, parallel.scan_stats.paths_pruned);
+        assert_eq!(
+            serial.best_cycle.map(|c| c.pool_addresses),
+            parallel.best_cycle.map(|c| c.pool_addresses)
+        );
+        assert!(parallel.scan_stats.scan_time_ms > 0.0);
+    }
+
+    #[test]
+    fn test_optimal_size_maximizes_output() {
+        let cycle = MultiverseCycle {
+            path: vec!["SOL".into(), "USDC".into(), "BONK".into(), "SOL".into()],
+            pool_addresses: vec!["p1".into(), "p2".into(), "p3".into()],
+            hop_count: 3,
+            profit_pct: 3.0,
+            min_liquidity_usd: 1_000_000,
+            total_fee_bps: 75,
+            dexes: vec!["RAYDIUM".into(), "ORCA".into(), "METEORA".into()],
+            estimated_gas_lamports: 0,
+        };
+        // Each hop is mispriced by 1% in our favour
+        let reserves = vec![(1_000_000_000u64, 1_010_000_000u64); 3];
+
+        let (best_input, best_profit_pct) = cycle.optimal_size_and_profit(reserves.clone()).unwrap();
+        assert!(best_input > 0);
+        assert!(best_profit_pct > 0.0);
+
+        let net = |x: u64| -> i128 {
+            let mut amount = x;
+            for &(r_in, r_out) in &reserves {
+                amount = compute_amm_out(amount, r_in, r_out, 25).unwrap();
+            }
+            amount as i128 - x as i128
+        };
+        let best_net = net(best_input);
+        for delta in [1u64, 10, 1_000, 100_000, 1_000_000] {
+            assert!(best_net >= net(best_input - delta), "smaller size by {} beats optimum", delta);
+            assert!(best_net >= net(best_input + delta), "larger size by {} beats optimum", delta);
+        }
+
+        // Fairly priced pools: nothing to gain
+        let fair = vec![(1_000_000_000u64, 1_000_000_000u64); 3];
+        assert_eq!(cycle.optimal_size_and_profit(fair).unwrap(), (0, 0.0));
+        assert!(cycle.optimal_size_and_profit(vec![(1, 1)]).is_err());
+    }
+
+    #[test]
+    fn test_blocklist_excludes_cycles_through_token() {
+        let graph = create_multi_hop_graph();
+        let mut scanner = MultiverseScanner::new(2, 5, 100_000, 50, DEFAULT_MEMO_CAPACITY);
+
+        // BONK is on every 3- and 4-hop cycle in the fixture
+        scanner.set_blocklist(vec!["BONK".to_string()]);
+        let blocked = scanner.scan_multiverse(&graph, "SOL");
+        assert!(blocked
+            .cycles_by_hops
+            .values()
+            .flatten()
+            .all(|c| !c.path.contains(&"BONK".to_string())));
+        assert!(!blocked.cycles_by_hops.contains_key(&3));
+        assert!(!blocked.cycles_by_hops.contains_key(&4));
+        assert!(blocked.cycles_by_hops.contains_key(&2));
+
+        scanner.clear_blocklist();
+        let unblocked = scanner.scan_multiverse(&graph, "SOL");
+        assert!(unblocked.cycles_by_hops.contains_key(&4));
+        assert!(blocked.scan_stats.paths_pruned > unblocked.scan_stats.paths_pruned);
+    }
+
     #[test]
     fn test_multiverse_scan_stats() {
         let graph = create_multi_hop_graph();
-        let mut scanner = MultiverseScanner::new(2, 5, 100_000, 50);
+        let mut scanner = MultiverseScanner::new(2, 5, 100_000, 50, DEFAULT_MEMO_CAPACITY);
 
         let result = scanner.scan_multiverse(&graph, "SOL");
 
         assert!(result.scan_stats.paths_explored > 0, "Should explore paths");
         assert!(result.scan_stats.scan_time_ms >= 0.0, "Should track time");
     }
+
+    #[test]
+    fn test_memo_evicts_least_recently_used_and_counts_hits() {
+        let key = |mint: &str| ("SOL".to_string(), mint.to_string(), 2);
+        let mut memo = ReturnWeightMemo::new(2);
+
+        memo.insert(key("A"), 0.1);
+        memo.insert(key("B"), 0.2);
+        assert_eq!(memo.get(&key("A")), Some(0.1));
+        assert_eq!(memo.get(&key("C")), None);
+        assert_eq!(memo.hits, 1);
+
+        // B is least recently used now
+        memo.insert(key("C"), 0.3);
+        assert_eq!(memo.len(), 2);
+        assert_eq!(memo.get(&key("B")), None);
+        assert_eq!(memo.get(&key("A")), Some(0.1));
+        assert_eq!(memo.get(&key("C")), Some(0.3));
+        assert_eq!(memo.hits, 3);
+
+        memo.clear();
+        assert_eq!((memo.len(), memo.hits), (0, 0));
+    }
+
+    #[test]
+    fn test_bounded_memo_keeps_results_and_reports_hits() {
+        let graph = create_multi_hop_graph();
+        let mut unbounded = MultiverseScanner::new(2, 5, 100_000, 50, DEFAULT_MEMO_CAPACITY);
+        let mut bounded = MultiverseScanner::new(2, 5, 100_000, 50, 2);
+
+        let full = unbounded.scan_multiverse(&graph, "SOL");
+        let small = bounded.scan_multiverse(&graph, "SOL");
+        assert!(bounded.cache_size() <= 2);
+        assert!(unbounded.cache_size() > 2);
+
+        // Eviction only costs recomputation, never cycles
+        assert_eq!(full.scan_stats.total_cycles_found, small.scan_stats.total_cycles_found);
+        assert_eq!(
+            full.best_cycle.map(|c| c.pool_addresses),
+            small.best_cycle.map(|c| c.pool_addresses)
+        );
+
+        // Hits are counted lookups, not the final cache size
+        assert!(full.scan_stats.memoization_hits > 0);
+        assert_ne!(full.scan_stats.memoization_hits, unbounded.cache_size());
+        assert!(small.scan_stats.memoization_hits < full.scan_stats.memoization_hits);
+    }
 }
@@ -6,6 +6,7 @@
 
 use pyo3::prelude::*;
 use serde::{Deserialize, Serialize};
+use std::sync::OnceLock;
 use std::time::Instant;
 // use base64::{Engine as _, engine::general_purpose};
 
@@ -19,6 +20,12 @@ const JITO_MAINNET_AMSTERDAM: &str = "https://amsterdam.mainnet.block-engine.jit
 const JITO_MAINNET_FRANKFURT: &str = "https://frankfurt.mainnet.block-engine.jito.wtf";
 const JITO_MAINNET_TOKYO: &str = "https://tokyo.mainnet.block-engine.jito.wtf";
 
+/// Jito publishes landed-tip percentiles from one global service, not per block engine
+const JITO_TIP_FLOOR_URL: &str = "https://bundles.jito.wtf/api/v1/bundles/tip_floor";
+
+/// Jito rejects bundles with more transactions than this
+const JITO_MAX_BUNDLE_TXS: usize = 5;
+
 /// Default Helius RPC endpoint (requires API key)
 const HELIUS_MAINNET: &str = "https://mainnet.helius-rpc.com";
 
@@ -84,18 +91,144 @@ impl SubmissionResult {
     }
 }
 
+/// simulateTransaction outcome returned to Python
+#[pyclass]
+#[derive(Clone)]
+pub struct SimulationResult {
+    /// Transaction error as JSON (e.g. `{"InstructionError":[0,{"Custom":1}]}`), None on success
+    #[pyo3(get)]
+    pub err: Option<String>,
+    #[pyo3(get)]
+    pub units_consumed: Option<u64>,
+    #[pyo3(get)]
+    pub logs: Vec<String>,
+}
+
+#[pymethods]
+impl SimulationResult {
+    fn __repr__(&self) -> String {
+        format!(
+            "SimulationResult(err={}, units_consumed={}, logs={})",
+            self.err.as_deref().unwrap_or("None"),
+            self.units_consumed.map_or("None".to_string(), |u| u.to_string()),
+            self.logs.len()
+        )
+    }
+}
+
+/// (url, success, latency_ms, error) for one endpoint in a race
+pub type EndpointOutcome = (String, bool, f64, Option<String>);
+
+/// Detailed race result: the winner plus every endpoint's outcome
+#[pyclass]
+#[derive(Clone)]
+pub struct RaceResult {
+    #[pyo3(get)]
+    pub success: bool,
+    #[pyo3(get)]
+    pub signature: Option<String>,
+    #[pyo3(get)]
+    pub error: Option<String>,
+    /// Endpoint that returned the signature first
+    #[pyo3(get)]
+    pub winner: Option<String>,
+    /// Winner's own request latency
+    #[pyo3(get)]
+    pub latency_ms: f64,
+    /// (url, success, latency_ms, error) per endpoint, in input order
+    #[pyo3(get)]
+    pub outcomes: Vec<EndpointOutcome>,
+}
+
+#[pymethods]
+impl RaceResult {
+    fn __repr__(&self) -> String {
+        let succeeded = self.outcomes.iter().filter(|o| o.1).count();
+        format!(
+            "RaceResult(success={}, winner={}, latency={:.1}ms, {}/{} endpoints ok)",
+            if self.success { "True" } else { "False" },
+            self.winner.as_deref().unwrap_or("None"),
+            self.latency_ms,
+            succeeded,
+            self.outcomes.len()
+        )
+    }
+}
+
+/// Round-trip statistics from `measure_latency_samples` (all in ms)
+#[pyclass]
+#[derive(Clone)]
+pub struct LatencyStats {
+    /// Number of successful samples (excluding the discarded warm-up)
+    #[pyo3(get)]
+    pub samples: usize,
+    #[pyo3(get)]
+    pub min_ms: f64,
+    #[pyo3(get)]
+    pub median_ms: f64,
+    #[pyo3(get)]
+    pub p95_ms: f64,
+    #[pyo3(get)]
+    pub mean_ms: f64,
+}
+
+#[pymethods]
+impl LatencyStats {
+    fn __repr__(&self) -> String {
+        format!(
+            "LatencyStats(n={}, min={:.1}ms, median={:.1}ms, p95={:.1}ms, mean={:.1}ms)",
+            self.samples, self.min_ms, self.median_ms, self.p95_ms, self.mean_ms
+        )
+    }
+}
+
+impl LatencyStats {
+    /// Summarize RTTs; an empty slice yields -1.0 for every stat
+    /// (same convention as `measure_latency`).
+    fn from_samples(samples_ms: &[f64]) -> Self {
+        if samples_ms.is_empty() {
+            return Self {
+                samples: 0,
+                min_ms: -1.0,
+                median_ms: -1.0,
+                p95_ms: -1.0,
+                mean_ms: -1.0,
+            };
+        }
+
+        let mut sorted = samples_ms.to_vec();
+        sorted.sort_by(|a, b| a.total_cmp(b));
+        let n = sorted.len();
+
+        // Nearest-rank percentile
+        let percentile = |p: f64| sorted[((p * n as f64).ceil() as usize).clamp(1, n) - 1];
+
+        Self {
+            samples: n,
+            min_ms: sorted[0],
+            median_ms: percentile(0.5),
+            p95_ms: percentile(0.95),
+            mean_ms: sorted.iter().sum::<f64>() / n as f64,
+        }
+    }
+}
+
 // ============================================================================
 // RUNTIME MANAGEMENT
 // ============================================================================
 
-/// Get or create the Tokio runtime.
+/// Get the shared Tokio runtime (created on first use).
 /// PyO3 functions can't be async directly, so we use a blocking runtime.
-pub(crate) fn get_runtime() -> tokio::runtime::Runtime {
-    tokio::runtime::Builder::new_multi_thread()
-        .enable_all()
-        .worker_threads(2)
-        .build()
-        .expect("Failed to create Tokio runtime")
+/// Shared so each submission doesn't pay for spawning and joining worker threads.
+pub(crate) fn get_runtime() -> &'static tokio::runtime::Runtime {
+    static RUNTIME: OnceLock<tokio::runtime::Runtime> = OnceLock::new();
+    RUNTIME.get_or_init(|| {
+        tokio::runtime::Builder::new_multi_thread()
+            .enable_all()
+            .worker_threads(2)
+            .build()
+            .expect("Failed to create Tokio runtime")
+    })
 }
 
 // ============================================================================
@@ -120,13 +253,7 @@ pub fn submit_to_jito(
     region: &str,
     tip_lamports: u64,
 ) -> PyResult<SubmissionResult> {
-    let endpoint = match region.to_lowercase().as_str() {
-        "ny" | "nyc" | "new_york" => JITO_MAINNET_NY,
-        "amsterdam" | "ams" => JITO_MAINNET_AMSTERDAM,
-        "frankfurt" | "fra" => JITO_MAINNET_FRANKFURT,
-        "tokyo" | "tyo" => JITO_MAINNET_TOKYO,
-        _ => JITO_MAINNET_NY,
-    };
+    let endpoint = jito_endpoint(region);
     
     let rt = get_runtime();
     let start = Instant::now();
@@ -155,6 +282,22 @@ pub fn submit_to_jito(
     }
 }
 
+/// Resolve a region name or alias (case-insensitive) to its Block Engine URL.
+fn resolve_jito_region(region: &str) -> Option<&'static str> {
+    match region.to_lowercase().as_str() {
+        "ny" | "nyc" | "new_york" => Some(JITO_MAINNET_NY),
+        "amsterdam" | "ams" => Some(JITO_MAINNET_AMSTERDAM),
+        "frankfurt" | "fra" => Some(JITO_MAINNET_FRANKFURT),
+        "tokyo" | "tyo" => Some(JITO_MAINNET_TOKYO),
+        _ => None,
+    }
+}
+
+/// Map a region name to its Block Engine URL (defaults to NY).
+fn jito_endpoint(region: &str) -> &'static str {
+    resolve_jito_region(region).unwrap_or(JITO_MAINNET_NY)
+}
+
 pub(crate) async fn submit_jito_async(
     endpoint: &str,
     tx_base64: &str,
@@ -166,7 +309,7 @@ pub(crate) async fn submit_jito_async(
         .map_err(|e| format!("Client build error: {}", e))?;
     
     // Jito uses sendTransaction for single transactions
-    // For bundles, use /api/v1/bundles
+    // For bundles, see submit_jito_bundle_async (/api/v1/bundles)
     let url = format!("{}/api/v1/transactions", endpoint);
     
     let request = RpcRequest {
@@ -202,6 +345,122 @@ pub(crate) async fn submit_jito_async(
         .ok_or_else(|| "No signature in response".to_string())
 }
 
+/// Submit a Jito bundle (up to 5 transactions, executed atomically in order).
+///
+/// Posts `sendBundle` to `/api/v1/bundles`. The tip must already be paid
+/// by one of the transactions (usually the last).
+///
+/// # Arguments
+/// * `txs_base64` - Base64 encoded serialized transactions, in execution order
+/// * `region` - Jito region: "ny", "amsterdam", "frankfurt", "tokyo"
+///
+/// # Returns
+/// SubmissionResult with the bundle ID in `signature`, or error
+#[pyfunction]
+#[pyo3(signature = (txs_base64, region="ny"))]
+pub fn submit_jito_bundle(
+    txs_base64: Vec<String>,
+    region: &str,
+) -> PyResult<SubmissionResult> {
+    let endpoint = jito_endpoint(region);
+
+    if let Err(e) = validate_bundle(&txs_base64) {
+        return Ok(SubmissionResult {
+            success: false,
+            signature: None,
+            error: Some(e),
+            latency_ms: 0.0,
+            endpoint: endpoint.to_string(),
+        });
+    }
+
+    let rt = get_runtime();
+    let start = Instant::now();
+
+    let result = rt.block_on(async {
+        submit_jito_bundle_async(endpoint, &txs_base64).await
+    });
+
+    let latency_ms = start.elapsed().as_secs_f64() * 1000.0;
+
+    match result {
+        Ok(bundle_id) => Ok(SubmissionResult {
+            success: true,
+            signature: Some(bundle_id),
+            error: None,
+            latency_ms,
+            endpoint: endpoint.to_string(),
+        }),
+        Err(e) => Ok(SubmissionResult {
+            success: false,
+            signature: None,
+            error: Some(e),
+            latency_ms,
+            endpoint: endpoint.to_string(),
+        }),
+    }
+}
+
+fn validate_bundle(txs_base64: &[String]) -> Result<(), String> {
+    if txs_base64.is_empty() {
+        return Err("Bundle is empty".to_string());
+    }
+    if txs_base64.len() > JITO_MAX_BUNDLE_TXS {
+        return Err(format!(
+            "Bundle has {} transactions (max {})",
+            txs_base64.len(),
+            JITO_MAX_BUNDLE_TXS
+        ));
+    }
+    Ok(())
+}
+
+pub(crate) async fn submit_jito_bundle_async(
+    endpoint: &str,
+    txs_base64: &[String],
+) -> Result<String, String> {
+    validate_bundle(txs_base64)?;
+
+    let client = reqwest::Client::builder()
+        .timeout(std::time::Duration::from_secs(5))
+        .build()
+        .map_err(|e| format!("Client build error: {}", e))?;
+
+    let url = format!("{}/api/v1/bundles", endpoint);
+
+    let request = RpcRequest {
+        jsonrpc: "2.0",
+        id: 1,
+        method: "sendBundle",
+        params: serde_json::json!([txs_base64, {"encoding": "base64"}]),
+    };
+
+    let response = client
+        .post(&url)
+        .json(&request)
+        .send()
+        .await
+        .map_err(|e| format!("Request failed: {}", e))?;
+
+    let status = response.status();
+    if !status.is_success() {
+        return Err(format!("HTTP {}: {}", status.as_u16(), status.as_str()));
+    }
+
+    let rpc_response: RpcResponse = response
+        .json()
+        .await
+        .map_err(|e| format!("JSON parse error: {}", e))?;
+
+    if let Some(error) = rpc_response.error {
+        return Err(format!("RPC Error {}: {}", error.code, error.message));
+    }
+
+    rpc_response.result
+        .and_then(|v| v.as_str().map(|s| s.to_string()))
+        .ok_or_else(|| "No bundle ID in response".to_string())
+}
+
 // ============================================================================
 // HELIUS SUBMISSION
 // ============================================================================
@@ -497,6 +756,489 @@ async fn submit_race_async(
     }
 }
 
+/// Submit to multiple endpoints simultaneously and report every outcome.
+///
+/// Returns as soon as the stragglers finish or `straggler_timeout_ms` elapses
+/// after the first success, whichever comes first. Endpoints still pending
+/// at that point are reported as timed out.
+///
+/// # Arguments
+/// * `tx_base64` - Base64 encoded serialized transaction
+/// * `endpoints` - List of RPC endpoint URLs
+/// * `straggler_timeout_ms` - How long to keep collecting after the winner
+///
+/// # Returns
+/// RaceResult with the winner and per-endpoint (url, success, latency_ms, error)
+#[pyfunction]
+#[pyo3(signature = (tx_base64, endpoints, straggler_timeout_ms=250))]
+pub fn submit_race_detailed(
+    tx_base64: String,
+    endpoints: Vec<String>,
+    straggler_timeout_ms: u64,
+) -> PyResult<RaceResult> {
+    if endpoints.is_empty() {
+        return Ok(RaceResult {
+            success: false,
+            signature: None,
+            error: Some("No endpoints provided".to_string()),
+            winner: None,
+            latency_ms: 0.0,
+            outcomes: Vec::new(),
+        });
+    }
+
+    let rt = get_runtime();
+    Ok(rt.block_on(async {
+        submit_race_detailed_async(&tx_base64, &endpoints, straggler_timeout_ms).await
+    }))
+}
+
+async fn submit_race_detailed_async(
+    tx_base64: &str,
+    endpoints: &[String],
+    straggler_timeout_ms: u64,
+) -> RaceResult {
+    let start = Instant::now();
+
+    let mut futures: Vec<_> = endpoints
+        .iter()
+        .enumerate()
+        .map(|(idx, ep)| {
+            let ep_clone = ep.clone();
+            let tx_clone = tx_base64.to_string();
+            tokio::spawn(async move {
+                let req_start = Instant::now();
+                let result = submit_rpc_async(&ep_clone, &tx_clone, true).await;
+                (idx, result, req_start.elapsed().as_secs_f64() * 1000.0)
+            })
+        })
+        .collect();
+    let abort_handles: Vec<_> = futures.iter().map(|f| f.abort_handle()).collect();
+
+    let mut outcomes: Vec<Option<EndpointOutcome>> = vec![None; endpoints.len()];
+    let mut winner: Option<(usize, String, f64)> = None;
+    let mut deadline: Option<tokio::time::Instant> = None;
+
+    while !futures.is_empty() {
+        let next = futures::future::select_all(futures);
+        let (result, _index, remaining) = match deadline {
+            Some(deadline) => match tokio::time::timeout_at(deadline, next).await {
+                Ok(done) => done,
+                Err(_) => break, // Stragglers ran out of time
+            },
+            None => next.await,
+        };
+        futures = remaining;
+
+        // Task panics leave their slot empty and are reported below
+        if let Ok((idx, result, latency_ms)) = result {
+            let endpoint = endpoints[idx].clone();
+            outcomes[idx] = Some(match result {
+                Ok(sig) => {
+                    if winner.is_none() {
+                        winner = Some((idx, sig, latency_ms));
+                        deadline = Some(
+                            tokio::time::Instant::now()
+                                + std::time::Duration::from_millis(straggler_timeout_ms),
+                        );
+                    }
+                    (endpoint, true, latency_ms, None)
+                }
+                Err(e) => (endpoint, false, latency_ms, Some(e)),
+            });
+        }
+    }
+
+    // Stop anything still in flight (no-op for finished tasks)
+    for handle in &abort_handles {
+        handle.abort();
+    }
+
+    let elapsed_ms = start.elapsed().as_secs_f64() * 1000.0;
+    let outcomes = outcomes
+        .into_iter()
+        .enumerate()
+        .map(|(idx, outcome)| {
+            outcome.unwrap_or_else(|| {
+                (
+                    endpoints[idx].clone(),
+                    false,
+                    elapsed_ms,
+                    Some("No result (timed out or task failed)".to_string()),
+                )
+            })
+        })
+        .collect();
+
+    match winner {
+        Some((idx, sig, latency_ms)) => RaceResult {
+            success: true,
+            signature: Some(sig),
+            error: None,
+            winner: Some(endpoints[idx].clone()),
+            latency_ms,
+            outcomes,
+        },
+        None => RaceResult {
+            success: false,
+            signature: None,
+            error: Some("All endpoints failed".to_string()),
+            winner: None,
+            latency_ms: elapsed_ms,
+            outcomes,
+        },
+    }
+}
+
+// ============================================================================
+// CONFIRMATION
+// ============================================================================
+
+/// Where a signature stands according to getSignatureStatuses
+#[derive(Debug, PartialEq)]
+enum SignatureState {
+    /// Unknown to the node (not landed yet, or dropped)
+    NotFound,
+    /// Landed but below the requested commitment
+    Pending(u64),
+    /// Reached the requested commitment without error
+    Confirmed(u64),
+    /// Landed with a transaction error
+    Failed(u64),
+}
+
+fn commitment_rank(commitment: &str) -> u8 {
+    match commitment {
+        "processed" => 0,
+        "confirmed" => 1,
+        _ => 2, // finalized
+    }
+}
+
+/// Interpret one entry of `result.value` from getSignatureStatuses.
+fn parse_signature_status(status: &serde_json::Value, commitment: &str) -> SignatureState {
+    let slot = match status.get("slot").and_then(|v| v.as_u64()) {
+        Some(slot) => slot,
+        None => return SignatureState::NotFound, // null entry
+    };
+
+    if status.get("err").is_some_and(|e| !e.is_null()) {
+        return SignatureState::Failed(slot);
+    }
+
+    let reached = status
+        .get("confirmationStatus")
+        .and_then(|v| v.as_str())
+        .map(commitment_rank)
+        .unwrap_or(0);
+
+    if reached >= commitment_rank(commitment) {
+        SignatureState::Confirmed(slot)
+    } else {
+        SignatureState::Pending(slot)
+    }
+}
+
+/// Poll getSignatureStatuses until the signature reaches `commitment` or
+/// `timeout_ms` elapses. The GIL is released while polling.
+///
+/// # Arguments
+/// * `rpc_url` - RPC endpoint URL
+/// * `signature` - Transaction signature (base58)
+/// * `timeout_ms` - Give up after this long
+/// * `commitment` - "processed", "confirmed" or "finalized"
+///
+/// # Returns
+/// (confirmed, landed_slot):
+/// * `(true, Some(slot))` - reached `commitment` without error
+/// * `(false, Some(slot))` - landed, still below `commitment` at timeout
+/// * `(false, None)` - never seen (not landed or dropped)
+///
+/// Raises RuntimeError if the transaction landed but failed on-chain.
+#[pyfunction]
+#[pyo3(signature = (rpc_url, signature, timeout_ms=30000, commitment="confirmed"))]
+pub fn await_confirmation(
+    py: Python<'_>,
+    rpc_url: String,
+    signature: String,
+    timeout_ms: u64,
+    commitment: &str,
+) -> PyResult<(bool, Option<u64>)> {
+    let rt = get_runtime();
+
+    let state = py.allow_threads(|| {
+        rt.block_on(async {
+            await_confirmation_async(&rpc_url, &signature, timeout_ms, commitment).await
+        })
+    });
+
+    match state {
+        SignatureState::Confirmed(slot) => Ok((true, Some(slot))),
+        SignatureState::Pending(slot) => Ok((false, Some(slot))),
+        SignatureState::Failed(slot) => Err(PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(
+            format!("Transaction {} failed on-chain at slot {}", signature, slot)
+        )),
+        SignatureState::NotFound => Ok((false, None)),
+    }
+}
+
+async fn await_confirmation_async(
+    rpc_url: &str,
+    signature: &str,
+    timeout_ms: u64,
+    commitment: &str,
+) -> SignatureState {
+    let deadline = Instant::now() + std::time::Duration::from_millis(timeout_ms);
+
+    let client = match reqwest::Client::builder()
+        .timeout(std::time::Duration::from_secs(2))
+        .build()
+    {
+        Ok(client) => client,
+        Err(_) => return SignatureState::NotFound,
+    };
+
+    let request = RpcRequest {
+        jsonrpc: "2.0",
+        id: 1,
+        method: "getSignatureStatuses",
+        params: serde_json::json!([[signature], {"searchTransactionHistory": false}]),
+    };
+
+    let mut state = SignatureState::NotFound;
+    let mut backoff_ms = 100u64;
+
+    loop {
+        // Transient RPC failures just count as another miss
+        if let Ok(response) = client.post(rpc_url).json(&request).send().await {
+            if let Ok(rpc_response) = response.json::<RpcResponse>().await {
+                if let Some(status) = rpc_response
+                    .result
+                    .as_ref()
+                    .and_then(|r| r.get("value"))
+                    .and_then(|v| v.get(0))
+                {
+                    state = parse_signature_status(status, commitment);
+                }
+            }
+        }
+
+        if matches!(state, SignatureState::Confirmed(_) | SignatureState::Failed(_)) {
+            return state;
+        }
+
+        let now = Instant::now();
+        if now >= deadline {
+            return state;
+        }
+
+        let sleep = std::time::Duration::from_millis(backoff_ms).min(deadline - now);
+        tokio::time::sleep(sleep).await;
+        backoff_ms = (backoff_ms * 3 / 2).min(1000);
+    }
+}
+
+// ============================================================================
+// PRIORITY FEES
+// ============================================================================
+
+/// Send one JSON-RPC request and return its `result`.
+async fn rpc_call_async(
+    rpc_url: &str,
+    method: &str,
+    params: serde_json::Value,
+) -> Result<serde_json::Value, String> {
+    let client = reqwest::Client::builder()
+        .timeout(std::time::Duration::from_secs(5))
+        .build()
+        .map_err(|e| format!("Client build error: {}", e))?;
+
+    let request = RpcRequest {
+        jsonrpc: "2.0",
+        id: 1,
+        method,
+        params,
+    };
+
+    let response = client
+        .post(rpc_url)
+        .json(&request)
+        .send()
+        .await
+        .map_err(|e| format!("Request failed: {}", e))?;
+
+    let status = response.status();
+    if !status.is_success() {
+        return Err(format!("HTTP {}: {}", status.as_u16(), status.as_str()));
+    }
+
+    let rpc_response: RpcResponse = response
+        .json()
+        .await
+        .map_err(|e| format!("JSON parse error: {}", e))?;
+
+    if let Some(error) = rpc_response.error {
+        return Err(format!("RPC Error {}: {}", error.code, error.message));
+    }
+
+    rpc_response.result.ok_or_else(|| "No result in response".to_string())
+}
+
+/// Estimate a priority fee from `getRecentPrioritizationFees`.
+///
+/// # Arguments
+/// * `rpc_url` - RPC endpoint URL
+/// * `account_keys` - Writable accounts the transaction will lock
+/// * `percentile` - 0-100, nearest-rank over the recent slots' fees
+///
+/// # Returns
+/// Fee in micro-lamports per CU (0 if the RPC reports no recent slots)
+#[pyfunction]
+#[pyo3(signature = (rpc_url, account_keys, percentile=75.0))]
+pub fn estimate_priority_fee(rpc_url: String, account_keys: Vec<String>, percentile: f64) -> PyResult<u64> {
+    if !(0.0..=100.0).contains(&percentile) {
+        return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+            "percentile must be within 0-100, got {}",
+            percentile
+        )));
+    }
+
+    let result = get_runtime()
+        .block_on(rpc_call_async(
+            &rpc_url,
+            "getRecentPrioritizationFees",
+            serde_json::json!([account_keys]),
+        ))
+        .map_err(PyErr::new::<pyo3::exceptions::PyRuntimeError, _>)?;
+
+    let mut fees: Vec<u64> = result
+        .as_array()
+        .map(|entries| {
+            entries
+                .iter()
+                .filter_map(|e| e.get("prioritizationFee").and_then(|f| f.as_u64()))
+                .collect()
+        })
+        .unwrap_or_default();
+
+    if fees.is_empty() {
+        return Ok(0);
+    }
+    fees.sort_unstable();
+
+    // Nearest-rank percentile
+    let n = fees.len();
+    let rank = ((percentile / 100.0 * n as f64).ceil() as usize).clamp(1, n);
+    Ok(fees[rank - 1])
+}
+
+// ============================================================================
+// SIMULATION
+// ============================================================================
+
+/// Simulate a transaction and read back compute units and logs.
+///
+/// Runs with `sigVerify: false` and `replaceRecentBlockhash: true`, so
+/// unsigned transactions or stale blockhashes still simulate. Raises if the
+/// RPC call itself fails; a failing transaction comes back in `err`.
+#[pyfunction]
+pub fn simulate_transaction(rpc_url: String, tx_base64: String) -> PyResult<SimulationResult> {
+    let result = get_runtime()
+        .block_on(rpc_call_async(
+            &rpc_url,
+            "simulateTransaction",
+            serde_json::json!([
+                tx_base64,
+                {
+                    "encoding": "base64",
+                    "sigVerify": false,
+                    "replaceRecentBlockhash": true
+                }
+            ]),
+        ))
+        .map_err(PyErr::new::<pyo3::exceptions::PyRuntimeError, _>)?;
+
+    Ok(parse_simulation_value(result.get("value").unwrap_or(&serde_json::Value::Null)))
+}
+
+fn parse_simulation_value(value: &serde_json::Value) -> SimulationResult {
+    SimulationResult {
+        err: value.get("err").filter(|e| !e.is_null()).map(|e| e.to_string()),
+        units_consumed: value.get("unitsConsumed").and_then(|u| u.as_u64()),
+        logs: value
+            .get("logs")
+            .and_then(|l| l.as_array())
+            .map(|logs| logs.iter().filter_map(|l| l.as_str().map(str::to_string)).collect())
+            .unwrap_or_default(),
+    }
+}
+
+// ============================================================================
+// ACCOUNT FETCH
+// ============================================================================
+
+/// Maximum pubkeys per `getMultipleAccounts` request (RPC limit)
+const MAX_MULTIPLE_ACCOUNTS: usize = 100;
+
+/// Fetch raw data for many accounts via `getMultipleAccounts`.
+///
+/// Splits into requests of at most 100 keys, sent one after another.
+/// Results are in `pubkeys` order; accounts that don't exist are None.
+#[pyfunction]
+pub fn fetch_multiple_accounts(rpc_url: String, pubkeys: Vec<String>) -> PyResult<Vec<Option<Vec<u8>>>> {
+    get_runtime()
+        .block_on(fetch_multiple_accounts_async(&rpc_url, &pubkeys))
+        .map_err(PyErr::new::<pyo3::exceptions::PyRuntimeError, _>)
+}
+
+async fn fetch_multiple_accounts_async(rpc_url: &str, pubkeys: &[String]) -> Result<Vec<Option<Vec<u8>>>, String> {
+    let mut accounts = Vec::with_capacity(pubkeys.len());
+    for chunk in pubkeys.chunks(MAX_MULTIPLE_ACCOUNTS) {
+        let result = rpc_call_async(
+            rpc_url,
+            "getMultipleAccounts",
+            serde_json::json!([chunk, { "encoding": "base64" }]),
+        )
+        .await?;
+        accounts.extend(parse_multiple_accounts(&result, chunk.len())?);
+    }
+    Ok(accounts)
+}
+
+fn parse_multiple_accounts(result: &serde_json::Value, expected: usize) -> Result<Vec<Option<Vec<u8>>>, String> {
+    use base64::Engine as _;
+
+    let values = result
+        .get("value")
+        .and_then(|v| v.as_array())
+        .ok_or_else(|| "getMultipleAccounts result has no value array".to_string())?;
+    if values.len() != expected {
+        return Err(format!(
+            "getMultipleAccounts returned {} accounts for {} keys",
+            values.len(),
+            expected
+        ));
+    }
+
+    values
+        .iter()
+        .map(|account| {
+            if account.is_null() {
+                return Ok(None);
+            }
+            let data = account
+                .get("data")
+                .and_then(|d| d.get(0))
+                .and_then(|d| d.as_str())
+                .ok_or_else(|| "Account entry has no base64 data".to_string())?;
+            base64::engine::general_purpose::STANDARD
+                .decode(data)
+                .map(Some)
+                .map_err(|e| format!("Invalid base64 account data: {}", e))
+        })
+        .collect()
+}
+
 // ============================================================================
 // UTILITY FUNCTIONS
 // ============================================================================
@@ -512,6 +1254,69 @@ pub fn get_jito_endpoints() -> PyResult<Vec<(String, String)>> {
     ])
 }
 
+/// One entry of the tip-floor response; percentiles are in SOL
+#[derive(Deserialize)]
+struct TipFloorEntry {
+    landed_tips_25th_percentile: f64,
+    landed_tips_50th_percentile: f64,
+    landed_tips_75th_percentile: f64,
+}
+
+/// Fetch the current Jito tip floor as (p25, p50, p75) landed tips in lamports.
+///
+/// `region` is any region name or alias the bundle submitters accept; every
+/// region reads the same global tip-floor service. A full `http(s)://` URL
+/// is queried as-is.
+#[pyfunction]
+#[pyo3(signature = (region="ny"))]
+pub fn fetch_jito_tip_floor(region: &str) -> PyResult<(u64, u64, u64)> {
+    let url = if region.starts_with("http://") || region.starts_with("https://") {
+        region.to_string()
+    } else if resolve_jito_region(region).is_some() {
+        JITO_TIP_FLOOR_URL.to_string()
+    } else {
+        return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+            "Unknown Jito region: {}",
+            region
+        )));
+    };
+
+    get_runtime()
+        .block_on(fetch_tip_floor_async(&url))
+        .map_err(PyErr::new::<pyo3::exceptions::PyRuntimeError, _>)
+}
+
+async fn fetch_tip_floor_async(url: &str) -> Result<(u64, u64, u64), String> {
+    let client = reqwest::Client::builder()
+        .timeout(std::time::Duration::from_secs(5))
+        .build()
+        .map_err(|e| format!("Client build error: {}", e))?;
+
+    let response = client
+        .get(url)
+        .send()
+        .await
+        .map_err(|e| format!("Request failed: {}", e))?;
+
+    let status = response.status();
+    if !status.is_success() {
+        return Err(format!("HTTP {}: {}", status.as_u16(), status.as_str()));
+    }
+
+    let entries: Vec<TipFloorEntry> = response
+        .json()
+        .await
+        .map_err(|e| format!("JSON parse error: {}", e))?;
+    let latest = entries.first().ok_or_else(|| "Empty tip floor response".to_string())?;
+
+    let lamports = |sol: f64| (sol * 1e9).round() as u64;
+    Ok((
+        lamports(latest.landed_tips_25th_percentile),
+        lamports(latest.landed_tips_50th_percentile),
+        lamports(latest.landed_tips_75th_percentile),
+    ))
+}
+
 /// Measure network latency to an endpoint (ping).
 #[pyfunction]
 pub fn measure_latency(endpoint: String) -> PyResult<f64> {
@@ -543,6 +1348,59 @@ pub fn measure_latency(endpoint: String) -> PyResult<f64> {
     Ok(latency.unwrap_or(-1.0))
 }
 
+/// Measure latency to an endpoint over several sequential `getHealth` calls.
+///
+/// The first request (cold connection: DNS, TCP, TLS) is discarded.
+/// Failed requests are skipped, so a dead endpoint returns quickly with
+/// `samples == 0` and -1.0 stats.
+///
+/// # Arguments
+/// * `endpoint` - RPC endpoint URL
+/// * `samples` - Number of measured requests after the warm-up
+/// * `timeout_ms` - Per-request timeout
+#[pyfunction]
+#[pyo3(signature = (endpoint, samples=5, timeout_ms=1000))]
+pub fn measure_latency_samples(endpoint: String, samples: usize, timeout_ms: u64) -> PyResult<LatencyStats> {
+    let rt = get_runtime();
+
+    let rtts = rt.block_on(async {
+        let client = match reqwest::Client::builder()
+            .timeout(std::time::Duration::from_millis(timeout_ms))
+            .build()
+        {
+            Ok(client) => client,
+            Err(_) => return Vec::new(),
+        };
+
+        let request = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "getHealth"
+        });
+
+        let mut rtts = Vec::with_capacity(samples);
+        for i in 0..=samples {
+            let start = Instant::now();
+            let ok = client.post(&endpoint).json(&request).send().await.is_ok();
+            let rtt_ms = start.elapsed().as_secs_f64() * 1000.0;
+
+            if !ok {
+                // A timed-out warm-up means the endpoint is dead; don't wait N more times
+                if i == 0 {
+                    break;
+                }
+                continue;
+            }
+            if i > 0 {
+                rtts.push(rtt_ms);
+            }
+        }
+        rtts
+    });
+
+    Ok(LatencyStats::from_samples(&rtts))
+}
+
 // ============================================================================
 // MODULE EXPORTS
 // ============================================================================
@@ -550,10 +1408,15 @@ pub fn measure_latency(endpoint: String) -> PyResult<f64> {
 pub fn register_network_functions(m: &PyModule) -> PyResult<()> {
     // Classes
     m.add_class::<SubmissionResult>()?;
+    m.add_class::<RaceResult>()?;
+    m.add_class::<LatencyStats>()?;
+    m.add_class::<SimulationResult>()?;
     
     // Jito
     m.add_function(wrap_pyfunction!(submit_to_jito, m)?)?;
+    m.add_function(wrap_pyfunction!(submit_jito_bundle, m)?)?;
     m.add_function(wrap_pyfunction!(get_jito_endpoints, m)?)?;
+    m.add_function(wrap_pyfunction!(fetch_jito_tip_floor, m)?)?;
     
     // Helius
     m.add_function(wrap_pyfunction!(submit_to_helius, m)?)?;
@@ -561,9 +1424,359 @@ pub fn register_network_functions(m: &PyModule) -> PyResult<()> {
     // Generic RPC
     m.add_function(wrap_pyfunction!(submit_to_rpc, m)?)?;
     m.add_function(wrap_pyfunction!(submit_race, m)?)?;
+    m.add_function(wrap_pyfunction!(submit_race_detailed, m)?)?;
     
+    // Confirmation
+    m.add_function(wrap_pyfunction!(await_confirmation, m)?)?;
+
+    // Priority fees & simulation
+    m.add_function(wrap_pyfunction!(estimate_priority_fee, m)?)?;
+    m.add_function(wrap_pyfunction!(simulate_transaction, m)?)?;
+    m.add_function(wrap_pyfunction!(fetch_multiple_accounts, m)?)?;
+
     // Utilities
     m.add_function(wrap_pyfunction!(measure_latency, m)?)?;
+    m.add_function(wrap_pyfunction!(measure_latency_samples, m)?)?;
     
     Ok(())
 }
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+
+    /// Minimal HTTP/1.1 server: answers one connection per canned
+    /// (delay_ms, json body) entry, then exits. Returns its base URL.
+    pub(crate) fn mock_rpc(responses: Vec<(u64, &'static str)>) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+
+        std::thread::spawn(move || {
+            for (delay_ms, body) in responses {
+                let (mut stream, _) = match listener.accept() {
+                    Ok(conn) => conn,
+                    Err(_) => return,
+                };
+
+                // Read headers, then as much body as Content-Length says
+                let mut buf = Vec::new();
+                let mut chunk = [0u8; 4096];
+                loop {
+                    let n = stream.read(&mut chunk).unwrap_or(0);
+                    if n == 0 {
+                        break;
+                    }
+                    buf.extend_from_slice(&chunk[..n]);
+                    let text = String::from_utf8_lossy(&buf).to_lowercase();
+                    if let Some(header_end) = text.find("\r\n\r\n") {
+                        let content_length = text
+                            .lines()
+                            .find_map(|l| l.strip_prefix("content-length:"))
+                            .and_then(|v| v.trim().parse::<usize>().ok())
+                            .unwrap_or(0);
+                        if buf.len() >= header_end + 4 + content_length {
+                            break;
+                        }
+                    }
+                }
+
+                std::thread::sleep(std::time::Duration::from_millis(delay_ms));
+                let response = format!(
+                    "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    body.len(),
+                    body
+                );
+                let _ = stream.write_all(response.as_bytes());
+            }
+        });
+
+        url
+    }
+
+    #[test]
+    fn test_race_detailed_reports_every_endpoint() {
+        let fast = mock_rpc(vec![(0, r#"{"jsonrpc":"2.0","id":1,"result":"fastsig"}"#)]);
+        let failing = mock_rpc(vec![(
+            50,
+            r#"{"jsonrpc":"2.0","id":1,"error":{"code":-32002,"message":"Blockhash not found"}}"#,
+        )]);
+
+        let result =
+            submit_race_detailed("AAAA".to_string(), vec![fast.clone(), failing.clone()], 2000).unwrap();
+
+        assert!(result.success);
+        assert_eq!(result.signature.as_deref(), Some("fastsig"));
+        assert_eq!(result.winner.as_deref(), Some(fast.as_str()));
+
+        assert_eq!(result.outcomes.len(), 2);
+        let (url, ok, _latency, error) = &result.outcomes[0];
+        assert_eq!(url, &fast);
+        assert!(ok);
+        assert!(error.is_none());
+
+        let (url, ok, _latency, error) = &result.outcomes[1];
+        assert_eq!(url, &failing);
+        assert!(!ok);
+        assert!(error.as_deref().unwrap().contains("Blockhash not found"));
+    }
+
+    #[test]
+    fn test_jito_bundle_rejects_more_than_five_txs() {
+        let txs = vec!["AAAA".to_string(); 6];
+        let result = submit_jito_bundle(txs, "ny").unwrap();
+
+        assert!(!result.success);
+        assert!(result.signature.is_none());
+        assert!(result.error.as_deref().unwrap().contains("max 5"));
+        assert_eq!(result.endpoint, JITO_MAINNET_NY);
+
+        let empty = submit_jito_bundle(Vec::new(), "tokyo").unwrap();
+        assert!(!empty.success);
+        assert_eq!(empty.endpoint, JITO_MAINNET_TOKYO);
+    }
+
+    #[test]
+    fn test_jito_bundle_returns_bundle_id() {
+        let engine = mock_rpc(vec![(0, r#"{"jsonrpc":"2.0","id":1,"result":"2id3YC2jjsL2i"}"#)]);
+        let txs = vec!["AAAA".to_string(), "BBBB".to_string()];
+
+        let rt = get_runtime();
+        let bundle_id = rt.block_on(submit_jito_bundle_async(&engine, &txs)).unwrap();
+        assert_eq!(bundle_id, "2id3YC2jjsL2i");
+    }
+
+    #[test]
+    fn test_latency_samples_are_ordered() {
+        let health = r#"{"jsonrpc":"2.0","id":1,"result":"ok"}"#;
+        // Warm-up + 5 measured requests with varying server delay
+        let endpoint = mock_rpc(vec![(0, health), (5, health), (20, health), (1, health), (10, health), (2, health)]);
+
+        let stats = measure_latency_samples(endpoint, 5, 1000).unwrap();
+
+        assert_eq!(stats.samples, 5);
+        assert!(stats.min_ms > 0.0);
+        assert!(stats.min_ms <= stats.median_ms);
+        assert!(stats.median_ms <= stats.p95_ms);
+        assert!(stats.mean_ms >= stats.min_ms && stats.mean_ms <= stats.p95_ms);
+        assert!(stats.p95_ms >= 20.0);
+    }
+
+    #[test]
+    fn test_latency_samples_dead_endpoint() {
+        // Bind then drop so nothing is listening on the port
+        let addr = TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap();
+
+        let start = Instant::now();
+        let stats = measure_latency_samples(format!("http://{}", addr), 5, 200).unwrap();
+
+        assert_eq!(stats.samples, 0);
+        assert_eq!(stats.median_ms, -1.0);
+        assert!(start.elapsed() < std::time::Duration::from_secs(1));
+    }
+
+    #[test]
+    fn test_await_confirmation_second_poll() {
+        let rpc = mock_rpc(vec![
+            (0, r#"{"jsonrpc":"2.0","id":1,"result":{"context":{"slot":100},"value":[null]}}"#),
+            (
+                0,
+                r#"{"jsonrpc":"2.0","id":1,"result":{"context":{"slot":101},"value":[{"slot":101,"confirmations":0,"err":null,"status":{"Ok":null},"confirmationStatus":"confirmed"}]}}"#,
+            ),
+        ]);
+
+        pyo3::prepare_freethreaded_python();
+        let result = Python::with_gil(|py| {
+            await_confirmation(py, rpc, "5VERv8NMvzbJMEkV8xnrLkEaWRtSz9CosKDYjCJjBRnb".to_string(), 5000, "confirmed")
+        })
+        .unwrap();
+        assert_eq!(result, (true, Some(101)));
+    }
+
+    #[test]
+    fn test_await_confirmation_failed_raises() {
+        let failed = r#"{"jsonrpc":"2.0","id":1,"result":{"context":{"slot":102},"value":[{"slot":102,"confirmations":0,"err":{"InstructionError":[0,{"Custom":1}]},"status":{"Err":{"InstructionError":[0,{"Custom":1}]}},"confirmationStatus":"confirmed"}]}}"#;
+        let rpc = mock_rpc(vec![(0, failed)]);
+
+        pyo3::prepare_freethreaded_python();
+        let err = Python::with_gil(|py| await_confirmation(py, rpc, "sig".to_string(), 5000, "confirmed"))
+            .unwrap_err();
+        assert!(err.to_string().contains("failed on-chain at slot 102"));
+    }
+
+    #[test]
+    fn test_await_confirmation_not_found_times_out() {
+        let not_found = r#"{"jsonrpc":"2.0","id":1,"result":{"context":{"slot":100},"value":[null]}}"#;
+        let rpc = mock_rpc(vec![(0, not_found); 10]);
+
+        pyo3::prepare_freethreaded_python();
+        let result = Python::with_gil(|py| await_confirmation(py, rpc, "sig".to_string(), 300, "confirmed"))
+            .unwrap();
+        assert_eq!(result, (false, None));
+    }
+
+    #[test]
+    fn test_parse_signature_status() {
+        let processed = serde_json::json!({"slot": 7, "err": null, "confirmationStatus": "processed"});
+        let failed = serde_json::json!({"slot": 8, "err": {"InstructionError": [0, "Custom"]}, "confirmationStatus": "confirmed"});
+
+        assert_eq!(parse_signature_status(&serde_json::Value::Null, "confirmed"), SignatureState::NotFound);
+        assert_eq!(parse_signature_status(&processed, "confirmed"), SignatureState::Pending(7));
+        assert_eq!(parse_signature_status(&processed, "processed"), SignatureState::Confirmed(7));
+        assert_eq!(parse_signature_status(&failed, "confirmed"), SignatureState::Failed(8));
+    }
+
+    /// Threads in this process, from /proc (Linux only)
+    fn thread_count() -> Option<usize> {
+        let status = std::fs::read_to_string("/proc/self/status").ok()?;
+        status
+            .lines()
+            .find_map(|l| l.strip_prefix("Threads:"))
+            .and_then(|v| v.trim().parse().ok())
+    }
+
+    #[test]
+    fn test_runtime_is_shared() {
+        assert!(std::ptr::eq(get_runtime(), get_runtime()));
+
+        // Nothing listening: every submission fails fast
+        let addr = TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap();
+        let rpc_url = format!("http://{}", addr);
+
+        let _ = submit_to_rpc("AAAA".to_string(), rpc_url.clone(), true).unwrap();
+        let before = thread_count();
+        for _ in 0..100 {
+            let result = submit_to_rpc("AAAA".to_string(), rpc_url.clone(), true).unwrap();
+            assert!(!result.success);
+        }
+        let after = thread_count();
+
+        // Other tests run concurrently, so allow a little slack
+        if let (Some(before), Some(after)) = (before, after) {
+            assert!(after <= before + 8, "threads grew from {} to {}", before, after);
+        }
+    }
+
+    #[test]
+    fn test_race_detailed_times_out_stragglers() {
+        let fast = mock_rpc(vec![(0, r#"{"jsonrpc":"2.0","id":1,"result":"fastsig"}"#)]);
+        let slow = mock_rpc(vec![(3000, r#"{"jsonrpc":"2.0","id":1,"result":"slowsig"}"#)]);
+
+        let start = Instant::now();
+        let result = submit_race_detailed("AAAA".to_string(), vec![fast, slow], 100).unwrap();
+
+        assert!(start.elapsed() < std::time::Duration::from_millis(2000));
+        assert_eq!(result.signature.as_deref(), Some("fastsig"));
+        assert!(!result.outcomes[1].1);
+        assert!(result.outcomes[1].3.as_deref().unwrap().contains("timed out"));
+    }
+
+    #[test]
+    fn test_fetch_jito_tip_floor_parses_percentiles() {
+        let url = mock_rpc(vec![(
+            0,
+            r#"[{"time":"2024-11-05T12:00:00Z","landed_tips_25th_percentile":6.0e-06,"landed_tips_50th_percentile":1.0e-05,"landed_tips_75th_percentile":3.6361e-05,"landed_tips_95th_percentile":0.0014,"landed_tips_99th_percentile":0.01,"ema_landed_tips_50th_percentile":1.2e-05}]"#,
+        )]);
+        assert_eq!(fetch_jito_tip_floor(&url).unwrap(), (6_000, 10_000, 36_361));
+
+        let empty = mock_rpc(vec![(0, "[]")]);
+        assert!(fetch_jito_tip_floor(&empty).is_err());
+    }
+
+    #[test]
+    fn test_fetch_jito_tip_floor_rejects_unknown_region() {
+        pyo3::prepare_freethreaded_python();
+        let err = fetch_jito_tip_floor("mars").unwrap_err();
+        assert!(err.to_string().contains("Unknown Jito region"));
+    }
+
+    #[test]
+    fn test_jito_region_aliases_resolve_consistently() {
+        // Every listed name and alias is known to both the submitters and the tip floor
+        for (name, url) in get_jito_endpoints().unwrap() {
+            assert_eq!(resolve_jito_region(&name), Some(url.as_str()));
+        }
+        for alias in ["NY", "nyc", "new_york", "ams", "Frankfurt", "fra", "tyo"] {
+            let url = resolve_jito_region(alias).unwrap();
+            assert_eq!(jito_endpoint(alias), url);
+        }
+        assert_eq!(resolve_jito_region("mars"), None);
+        assert_eq!(jito_endpoint("mars"), JITO_MAINNET_NY);
+    }
+
+    #[test]
+    fn test_estimate_priority_fee_selects_percentile() {
+        // Ten slots, fees unsorted: sorted they are 0, 100, 200, ..., 900
+        let body = r#"{"jsonrpc":"2.0","id":1,"result":[
+            {"slot":10,"prioritizationFee":500},{"slot":11,"prioritizationFee":0},
+            {"slot":12,"prioritizationFee":900},{"slot":13,"prioritizationFee":100},
+            {"slot":14,"prioritizationFee":300},{"slot":15,"prioritizationFee":800},
+            {"slot":16,"prioritizationFee":200},{"slot":17,"prioritizationFee":700},
+            {"slot":18,"prioritizationFee":400},{"slot":19,"prioritizationFee":600}]}"#;
+        let rpc = mock_rpc(vec![(0, body), (0, body), (0, body), (0, body)]);
+        let accounts = vec!["58oQChx4yWmvKdwLLZzBi4ChoCc2fqCUWBkwMihLYQo2".to_string()];
+
+        assert_eq!(estimate_priority_fee(rpc.clone(), accounts.clone(), 50.0).unwrap(), 400);
+        assert_eq!(estimate_priority_fee(rpc.clone(), accounts.clone(), 75.0).unwrap(), 700);
+        assert_eq!(estimate_priority_fee(rpc.clone(), accounts.clone(), 100.0).unwrap(), 900);
+        assert_eq!(estimate_priority_fee(rpc, accounts.clone(), 0.0).unwrap(), 0);
+
+        let empty = mock_rpc(vec![(0, r#"{"jsonrpc":"2.0","id":1,"result":[]}"#)]);
+        assert_eq!(estimate_priority_fee(empty, accounts, 75.0).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_simulate_transaction_reads_units_and_logs() {
+        let rpc = mock_rpc(vec![
+            (
+                0,
+                r#"{"jsonrpc":"2.0","id":1,"result":{"context":{"slot":250000000},"value":{"err":null,"accounts":null,"logs":["Program 675kPX9MHTjS2zt1qfr1NYHuzeLXfQM9H24wFSUt1Mp8 invoke [1]","Program log: ray_log: AwDh9QUAAAAA","Program 675kPX9MHTjS2zt1qfr1NYHuzeLXfQM9H24wFSUt1Mp8 consumed 31874 of 200000 compute units","Program 675kPX9MHTjS2zt1qfr1NYHuzeLXfQM9H24wFSUt1Mp8 success"],"returnData":null,"unitsConsumed":31874}}}"#,
+            ),
+            (
+                0,
+                r#"{"jsonrpc":"2.0","id":1,"result":{"context":{"slot":250000001},"value":{"err":{"InstructionError":[0,{"Custom":30}]},"logs":["Program log: Error: exceeds desired slippage limit"],"unitsConsumed":12000}}}"#,
+            ),
+        ]);
+
+        let ok = simulate_transaction(rpc.clone(), "AAAA".to_string()).unwrap();
+        assert_eq!(ok.err, None);
+        assert_eq!(ok.units_consumed, Some(31_874));
+        assert_eq!(ok.logs.len(), 4);
+        assert_eq!(ok.logs[1], "Program log: ray_log: AwDh9QUAAAAA");
+
+        let failed = simulate_transaction(rpc, "AAAA".to_string()).unwrap();
+        assert_eq!(failed.err.as_deref(), Some(r#"{"InstructionError":[0,{"Custom":30}]}"#));
+        assert_eq!(failed.units_consumed, Some(12_000));
+        assert_eq!(failed.logs, ["Program log: Error: exceeds desired slippage limit"]);
+    }
+
+    #[test]
+    fn test_fetch_multiple_accounts_chunks_and_keeps_missing() {
+        fn accounts_body(values: &[&str]) -> &'static str {
+            format!(
+                r#"{{"jsonrpc":"2.0","id":1,"result":{{"context":{{"slot":250000000}},"value":[{}]}}}}"#,
+                values.join(",")
+            )
+            .leak()
+        }
+        let account = r#"{"data":["AQIDBAU=","base64"],"executable":false,"lamports":2039280,"owner":"TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA","rentEpoch":18446744073709551615,"space":5}"#;
+
+        // 101 keys: a full chunk with one missing account, then a single key
+        let mut first = vec![account; 100];
+        first[1] = "null";
+        let rpc = mock_rpc(vec![(0, accounts_body(&first)), (0, accounts_body(&["null"]))]);
+        let pubkeys: Vec<String> = (0..101).map(|i| format!("Pool{}", i)).collect();
+
+        let accounts = fetch_multiple_accounts(rpc, pubkeys).unwrap();
+        assert_eq!(accounts.len(), 101);
+        assert_eq!(accounts[0].as_deref(), Some(&[1u8, 2, 3, 4, 5][..]));
+        assert_eq!(accounts[1], None);
+        assert!(accounts[2..100].iter().all(|a| a.is_some()));
+        assert_eq!(accounts[100], None);
+
+        // A short response can't be lined up with the keys
+        let short = mock_rpc(vec![(0, accounts_body(&["null"]))]);
+        assert!(fetch_multiple_accounts(short, vec!["A".to_string(), "B".to_string()]).is_err());
+    }
+}
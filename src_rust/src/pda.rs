@@ -7,11 +7,25 @@ use std::str::FromStr;
 const RAYDIUM_V4_PROGRAM_ID: &str = "675kPX9MHTjS2zt1qfr1NYHuzeLXfQM9H24wFSUt1Mp8";
 const ORCA_WHIRLPOOL_PROGRAM_ID: &str = "whirLbMiicVdio4qvUfM5KAg6Ct8VwpYzGff3uctyCc";
 
+/// Seed for the Raydium V4 AMM authority (shared by every V4 pool)
+const RAYDIUM_AMM_AUTHORITY_SEED: &[u8] = b"amm authority";
+
+/// A derived address together with the inputs that produced it, so a hash
+/// collision can be told apart from a hit
+struct CachedPda {
+    program_id: String,
+    seeds: Vec<Vec<u8>>,
+    pda: String,
+}
+
 #[pyclass]
 pub struct PdaCache {
-    cache: HashMap<String, String>,
+    // Keyed by a hash of (program_id, seeds); a bucket only holds more than
+    // one entry on a hash collision
+    cache: HashMap<u64, Vec<CachedPda>>,
+    hasher: ahash::RandomState,
     raydium_pid: Pubkey,
-    orca_pid: Pubkey,
+    raydium_authority: Option<String>,
 }
 
 #[pymethods]
@@ -20,57 +34,32 @@ impl PdaCache {
     fn new() -> Self {
         PdaCache {
             cache: HashMap::new(),
+            hasher: ahash::RandomState::new(),
             raydium_pid: Pubkey::from_str(RAYDIUM_V4_PROGRAM_ID).unwrap(),
-            orca_pid: Pubkey::from_str(ORCA_WHIRLPOOL_PROGRAM_ID).unwrap(),
+            raydium_authority: None,
         }
     }
 
-    /// Derives the Raydium V4 AMM ID (Pool Address)
-    /// Seeds: [program_id, market_id, "amm_associated_seed"]
-    /// Note: Raydium derivation acts slightly differently depending on version, 
-    /// but standard V4 uses specific seeds.
-    /// 
-    /// However, usually Raydium pools are found via the factory or hardcoded.
-    /// The most common calculation needed is actually for Associated Token Accounts (ATAs).
-    /// But sticking to the user request for "PDA Derivation Cache" for "pool lookups".
+    /// Derives the Raydium V4 AMM Authority PDA
+    /// Seeds: ["amm authority"]
     ///
-    /// Let's implement the standard Raydium V4 Authority PDA which is common.
-    fn get_raydium_authority(&self) -> String {
-        let (pda, _) = Pubkey::find_program_address(
-            &[], // Raydium V4 authority has no seeds? Or specific seeds?
-                 // Actually, Raydium often checks authorities.
-                 // Let's implement generic finding first.
-            &self.raydium_pid
-        );
-        pda.to_string()
+    /// Every V4 pool shares this authority (the AMM program signs vault
+    /// transfers with it), so it is derived once and cached.
+    fn get_raydium_authority(&mut self) -> String {
+        let raydium_pid = self.raydium_pid;
+        self.raydium_authority
+            .get_or_insert_with(|| {
+                let (pda, _) =
+                    Pubkey::find_program_address(&[RAYDIUM_AMM_AUTHORITY_SEED], &raydium_pid);
+                pda.to_string()
+            })
+            .clone()
     }
 
     /// Generic find_program_address wrapper
     /// Returns (pda_address, bump_seed)
     fn find_address(&mut self, program_id_str: String, seeds: Vec<Vec<u8>>) -> PyResult<String> {
-        // Construct a cache key
-        // Key format: "PID:SEED1:SEED2..."
-        // This is a bit expensive to construct strings, but faster than FFI overhead in Python loops
-        let mut key = program_id_str.clone();
-        for seed in &seeds {
-            key.push(':');
-            key.push_str(&hex::encode(seed));
-        }
-
-        if let Some(cached) = self.cache.get(&key) {
-            return Ok(cached.clone());
-        }
-
-        let pid = Pubkey::from_str(&program_id_str)
-            .map_err(|e| PyErr::new::<pyo3::exceptions::PyValueError, _>(e.to_string()))?;
-
-        let seed_slices: Vec<&[u8]> = seeds.iter().map(|v| v.as_slice()).collect();
-        let (pda, _) = Pubkey::find_program_address(&seed_slices, &pid);
-        
-        let pda_str = pda.to_string();
-        self.cache.insert(key, pda_str.clone());
-        
-        Ok(pda_str)
+        self.find_cached(&program_id_str, seeds)
     }
 
     /// Derives the Orca Whirlpool Address
@@ -82,31 +71,56 @@ impl PdaCache {
         token_mint_b: String, 
         tick_spacing: u16
     ) -> PyResult<String> {
-        let config_pubkey = Pubkey::from_str(&whirlpools_config)
-            .map_err(|e| PyErr::new::<pyo3::exceptions::PyValueError, _>(e.to_string()))?;
-        
-        let mut mint_a_pubkey = Pubkey::from_str(&token_mint_a)
-            .map_err(|e| PyErr::new::<pyo3::exceptions::PyValueError, _>(e.to_string()))?;
-        let mut mint_b_pubkey = Pubkey::from_str(&token_mint_b)
-             .map_err(|e| PyErr::new::<pyo3::exceptions::PyValueError, _>(e.to_string()))?;
+        self.get_whirlpool(whirlpools_config, token_mint_a, token_mint_b, tick_spacing)
+    }
+
+    /// Derives (and caches) the Orca Whirlpool pool address
+    /// Seeds: ["whirlpool", whirlpools_config, mint_a, mint_b, tick_spacing (u16 LE)]
+    /// Mints are sorted first, as the program requires mint_a < mint_b.
+    fn get_whirlpool(
+        &mut self,
+        whirlpools_config: String,
+        mint_a: String,
+        mint_b: String,
+        tick_spacing: u16,
+    ) -> PyResult<String> {
+        let config_pubkey = parse_pubkey(&whirlpools_config)?;
+        let mut mint_a_pubkey = parse_pubkey(&mint_a)?;
+        let mut mint_b_pubkey = parse_pubkey(&mint_b)?;
 
         // Orca requires token mints to be sorted
         if mint_a_pubkey > mint_b_pubkey {
             std::mem::swap(&mut mint_a_pubkey, &mut mint_b_pubkey);
         }
 
-        let tick_spacing_bytes = tick_spacing.to_le_bytes();
-        
         let seeds = vec![
-            b"whirlpool",
-            config_pubkey.as_ref(),
-            mint_a_pubkey.as_ref(),
-            mint_b_pubkey.as_ref(),
-            &tick_spacing_bytes
+            b"whirlpool".to_vec(),
+            config_pubkey.to_bytes().to_vec(),
+            mint_a_pubkey.to_bytes().to_vec(),
+            mint_b_pubkey.to_bytes().to_vec(),
+            tick_spacing.to_le_bytes().to_vec(),
         ];
+        self.find_cached(ORCA_WHIRLPOOL_PROGRAM_ID, seeds)
+    }
 
-        let (pda, _) = Pubkey::find_program_address(&seeds, &self.orca_pid);
-        Ok(pda.to_string())
+    /// Derives (and caches) a Whirlpool's oracle account
+    /// Seeds: ["oracle", whirlpool]
+    fn get_whirlpool_oracle(&mut self, whirlpool: String) -> PyResult<String> {
+        let whirlpool_pubkey = parse_pubkey(&whirlpool)?;
+        let seeds = vec![b"oracle".to_vec(), whirlpool_pubkey.to_bytes().to_vec()];
+        self.find_cached(ORCA_WHIRLPOOL_PROGRAM_ID, seeds)
+    }
+
+    /// Derives (and caches) a Whirlpool tick array account
+    /// Seeds: ["tick_array", whirlpool, start_tick as a decimal string]
+    fn get_whirlpool_tick_array(&mut self, whirlpool: String, start_tick: i32) -> PyResult<String> {
+        let whirlpool_pubkey = parse_pubkey(&whirlpool)?;
+        let seeds = vec![
+            b"tick_array".to_vec(),
+            whirlpool_pubkey.to_bytes().to_vec(),
+            start_tick.to_string().into_bytes(),
+        ];
+        self.find_cached(ORCA_WHIRLPOOL_PROGRAM_ID, seeds)
     }
 
     /// Derives the Associated Token Account (ATA) address
@@ -133,8 +147,143 @@ impl PdaCache {
     }
 }
 
+impl PdaCache {
+    /// find_program_address through the cache
+    fn find_cached(&mut self, program_id: &str, seeds: Vec<Vec<u8>>) -> PyResult<String> {
+        // Hash the raw inputs instead of building a string key, so a hit
+        // allocates nothing beyond the returned address
+        let key = self.hasher.hash_one((program_id, &seeds));
+        if let Some(bucket) = self.cache.get(&key) {
+            if let Some(hit) = bucket
+                .iter()
+                .find(|entry| entry.program_id == program_id && entry.seeds == seeds)
+            {
+                return Ok(hit.pda.clone());
+            }
+        }
+
+        let pid = parse_pubkey(program_id)?;
+
+        let seed_slices: Vec<&[u8]> = seeds.iter().map(|v| v.as_slice()).collect();
+        let (pda, _) = Pubkey::find_program_address(&seed_slices, &pid);
+        
+        let pda_str = pda.to_string();
+        self.cache.entry(key).or_default().push(CachedPda {
+            program_id: program_id.to_string(),
+            seeds,
+            pda: pda_str.clone(),
+        });
+        
+        Ok(pda_str)
+    }
+}
+
+fn parse_pubkey(value: &str) -> PyResult<Pubkey> {
+    Pubkey::from_str(value)
+        .map_err(|e| PyErr::new::<pyo3::exceptions::PyValueError, _>(e.to_string()))
+}
+
 /// Registers the module classes with PyO3
 pub fn register_pda_classes(m: &PyModule) -> PyResult<()> {
     m.add_class::<PdaCache>()?;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_raydium_authority_matches_mainnet() {
+        let mut cache = PdaCache::new();
+        assert_eq!(
+            cache.get_raydium_authority(),
+            "5Q544fKrFoe6tsEbD7S8EmxGTJYAKtTVhAW5Q5pge4j1"
+        );
+        // Served from the cache on the second call
+        assert!(cache.raydium_authority.is_some());
+        assert_eq!(
+            cache.get_raydium_authority(),
+            "5Q544fKrFoe6tsEbD7S8EmxGTJYAKtTVhAW5Q5pge4j1"
+        );
+    }
+
+    #[test]
+    fn test_find_address_cache_matches_uncached_derivation() {
+        let mut cache = PdaCache::new();
+        let program = RAYDIUM_V4_PROGRAM_ID.to_string();
+        let pid = Pubkey::from_str(RAYDIUM_V4_PROGRAM_ID).unwrap();
+        let seeds_for = |i: u32| vec![b"pool".to_vec(), i.to_le_bytes().to_vec()];
+
+        let derived: Vec<String> = (0..10_000u32)
+            .map(|i| cache.find_address(program.clone(), seeds_for(i)).unwrap())
+            .collect();
+
+        for (i, address) in derived.iter().enumerate() {
+            let seeds = seeds_for(i as u32);
+            let slices: Vec<&[u8]> = seeds.iter().map(|s| s.as_slice()).collect();
+            let (reference, _) = Pubkey::find_program_address(&slices, &pid);
+            assert_eq!(address, &reference.to_string());
+            // Second lookup is a cache hit and must agree
+            assert_eq!(&cache.find_address(program.clone(), seeds).unwrap(), address);
+        }
+        assert_eq!(cache.cache.values().map(Vec::len).sum::<usize>(), 10_000);
+
+        // Same seeds under a different program are a different entry
+        let other = cache
+            .find_address(ORCA_WHIRLPOOL_PROGRAM_ID.to_string(), seeds_for(0))
+            .unwrap();
+        assert_ne!(other, derived[0]);
+    }
+
+    const WHIRLPOOLS_CONFIG: &str = "2LecshUwdy9xi7meFgHtFJQNSKk4KdTrcpvaB56dP2NQ";
+    const WSOL: &str = "So11111111111111111111111111111111111111112";
+    const USDC: &str = "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v";
+    /// Mainnet SOL/USDC Whirlpool (tick spacing 64)
+    const SOL_USDC_WHIRLPOOL: &str = "HJPjoWUrhoZzkNfRpHuieeFk9WcZWjwy6PBjZ81ngndJ";
+
+    #[test]
+    fn test_whirlpool_matches_mainnet_sol_usdc() {
+        let mut cache = PdaCache::new();
+        let pool = cache
+            .get_whirlpool(WHIRLPOOLS_CONFIG.into(), WSOL.into(), USDC.into(), 64)
+            .unwrap();
+        assert_eq!(pool, SOL_USDC_WHIRLPOOL);
+
+        // Mint order does not matter, and the legacy name agrees
+        let swapped = cache
+            .get_orca_whirlpool_address(WHIRLPOOLS_CONFIG.into(), USDC.into(), WSOL.into(), 64)
+            .unwrap();
+        assert_eq!(swapped, SOL_USDC_WHIRLPOOL);
+        assert_eq!(cache.cache.len(), 1);
+    }
+
+    #[test]
+    fn test_whirlpool_oracle_and_tick_array_seeds() {
+        let mut cache = PdaCache::new();
+        let orca = Pubkey::from_str(ORCA_WHIRLPOOL_PROGRAM_ID).unwrap();
+        let pool = Pubkey::from_str(SOL_USDC_WHIRLPOOL).unwrap();
+
+        let (oracle, _) = Pubkey::find_program_address(&[b"oracle", pool.as_ref()], &orca);
+        assert_eq!(
+            cache.get_whirlpool_oracle(SOL_USDC_WHIRLPOOL.into()).unwrap(),
+            oracle.to_string()
+        );
+
+        for start_tick in [-22_528, 0, 5_632] {
+            let (tick_array, _) = Pubkey::find_program_address(
+                &[b"tick_array", pool.as_ref(), start_tick.to_string().as_bytes()],
+                &orca,
+            );
+            assert_eq!(
+                cache
+                    .get_whirlpool_tick_array(SOL_USDC_WHIRLPOOL.into(), start_tick)
+                    .unwrap(),
+                tick_array.to_string()
+            );
+        }
+        assert_eq!(cache.cache.len(), 4);
+
+        assert!(cache.get_whirlpool_oracle("not-a-pubkey".into()).is_err());
+    }
+}
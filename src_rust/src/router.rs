@@ -7,8 +7,16 @@ use solana_sdk::system_instruction;
 use solana_sdk::transaction::Transaction;
 use std::str::FromStr; // Fix base64 trait scope
 
+use crate::instruction_builder::decode_instructions;
 use crate::network_submitter::{get_runtime, submit_jito_async, submit_rpc_async};
 
+/// Default endpoints (mainnet, NY block engine)
+const DEFAULT_RPC_URL: &str = "https://api.mainnet-beta.solana.com";
+const DEFAULT_JITO_ENDPOINT: &str = "https://ny.mainnet.block-engine.jito.wtf";
+
+/// Default session exposure cap: $10k in milli-USD
+const DEFAULT_MAX_SESSION_EXPOSURE_MILLI_USD: u64 = 10_000_000;
+
 #[pyclass]
 #[derive(Clone, Debug)]
 pub enum ExecutionPath {
@@ -20,14 +28,26 @@ pub enum ExecutionPath {
 pub struct UnifiedTradeRouter {
     keypair: Keypair,
     jito_tip_account: Pubkey,
+    /// RPC used by the SmartStandard path
+    rpc_url: String,
+    /// Block engine used by the AtomicJito path
+    jito_endpoint: String,
     // Removed #[pyo3(get)] as AtomicU64 doesn't implement IntoPy/Clone directly for get
     pub total_session_exposure: std::sync::atomic::AtomicU64, // In Milli-USD for atomic ops
+    /// `route` aborts once exposure passes this (milli-USD)
+    max_session_exposure_milli_usd: u64,
 }
 
 #[pymethods]
 impl UnifiedTradeRouter {
     #[new]
-    pub fn new(private_key_base58: String) -> PyResult<Self> {
+    #[pyo3(signature = (private_key_base58, rpc_url=None, jito_endpoint=None, max_session_exposure_milli_usd=DEFAULT_MAX_SESSION_EXPOSURE_MILLI_USD))]
+    pub fn new(
+        private_key_base58: String,
+        rpc_url: Option<String>,
+        jito_endpoint: Option<String>,
+        max_session_exposure_milli_usd: u64,
+    ) -> PyResult<Self> {
         // Init keypair once for zero-latency signing
         // Keypair::from_base58_string in this version returns Self directly (panics on invalid)
         let keypair = Keypair::from_base58_string(&private_key_base58);
@@ -36,10 +56,43 @@ impl UnifiedTradeRouter {
             keypair,
             jito_tip_account: Pubkey::from_str("96g9sAg9CeGguRiYp9YmNTSUky1F9p7hYy1B52B7WAbA")
                 .unwrap(),
+            rpc_url: rpc_url.unwrap_or_else(|| DEFAULT_RPC_URL.to_string()),
+            jito_endpoint: jito_endpoint.unwrap_or_else(|| DEFAULT_JITO_ENDPOINT.to_string()),
             total_session_exposure: std::sync::atomic::AtomicU64::new(0),
+            max_session_exposure_milli_usd,
         })
     }
 
+    /// Tune the session exposure cap (milli-USD) without a rebuild
+    pub fn set_max_exposure(&mut self, milli_usd: u64) {
+        self.max_session_exposure_milli_usd = milli_usd;
+    }
+
+    #[getter]
+    pub fn get_max_session_exposure(&self) -> u64 {
+        self.max_session_exposure_milli_usd
+    }
+
+    /// Point the SmartStandard path at a different RPC (e.g. devnet)
+    pub fn set_rpc_url(&mut self, rpc_url: String) {
+        self.rpc_url = rpc_url;
+    }
+
+    /// Point the AtomicJito path at a different block engine region
+    pub fn set_jito_endpoint(&mut self, jito_endpoint: String) {
+        self.jito_endpoint = jito_endpoint;
+    }
+
+    #[getter]
+    pub fn get_rpc_url(&self) -> String {
+        self.rpc_url.clone()
+    }
+
+    #[getter]
+    pub fn get_jito_endpoint(&self) -> String {
+        self.jito_endpoint.clone()
+    }
+
     /// Manual getter for atomic exposure
     #[getter]
     pub fn get_total_session_exposure(&self) -> u64 {
@@ -47,61 +100,78 @@ impl UnifiedTradeRouter {
             .load(std::sync::atomic::Ordering::Relaxed)
     }
 
+    /// Add a trade's notional (milli-USD) to the session exposure
+    pub fn record_exposure(&self, milli_usd: u64) {
+        self.total_session_exposure
+            .fetch_add(milli_usd, std::sync::atomic::Ordering::Relaxed);
+    }
+
+    /// Clear session exposure (e.g. at the start of a new session)
+    pub fn reset_exposure(&self) {
+        self.total_session_exposure
+            .store(0, std::sync::atomic::Ordering::Relaxed);
+    }
+
     /// The High-Frequency Entry Point
+    /// `notional_milli_usd` is added to the session exposure once submitted.
+    /// On SmartStandard, `priority_fee_lamports` is the compute unit price
+    /// (micro-lamports per CU); on AtomicJito it is the tip.
+    #[pyo3(signature = (path, instruction_data, cu_limit, priority_fee_lamports, recent_blockhash, notional_milli_usd=0))]
     pub fn route(
         &self,
         path: ExecutionPath,
         instruction_data: Vec<u8>, // Serialized Instruction
-        _cu_limit: u32,
+        cu_limit: u32,
         priority_fee_lamports: u64,
         recent_blockhash: String,
+        notional_milli_usd: u64,
     ) -> PyResult<String> {
         let blockhash = solana_sdk::hash::Hash::from_str(&recent_blockhash).map_err(|e| {
             PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Invalid blockhash: {}", e))
         })?;
 
         // V34 Safety Check
-        let exposure = self
-            .total_session_exposure
-            .load(std::sync::atomic::Ordering::Relaxed);
-        if exposure > 10_000_000 {
-            // $10k hard limit in Rust
-            return Err(PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(
-                "EMERGENCY_STOP: Session exposure limit reached in Rust",
-            ));
-        }
+        self.check_exposure()?;
 
-        match path {
+        let sig = match path {
             ExecutionPath::AtomicJito => self.execute_jito_bundle(
                 instruction_data,
-                _cu_limit,
+                cu_limit,
                 priority_fee_lamports,
                 blockhash,
             ),
             ExecutionPath::SmartStandard => self.execute_standard_tx(
                 instruction_data,
-                _cu_limit,
+                cu_limit,
                 priority_fee_lamports,
                 blockhash,
             ),
-        }
+        }?;
+
+        self.record_exposure(notional_milli_usd);
+        Ok(sig)
     }
 
     /// Optimized path for pre-built transactions (e.g. from Jupiter)
+    /// `notional_milli_usd` is added to the session exposure once submitted.
+    #[pyo3(signature = (path, tx_data, tip_lamports, notional_milli_usd=0))]
     pub fn route_transaction(
         &self,
         path: ExecutionPath,
         tx_data: Vec<u8>, // Serialized VersionedTransaction
         tip_lamports: u64,
+        notional_milli_usd: u64,
     ) -> PyResult<String> {
+        self.check_exposure()?;
+
         let tx_base64 = base64::engine::general_purpose::STANDARD.encode(&tx_data);
         let rt = get_runtime();
 
-        match path {
+        let sig = match path {
             ExecutionPath::AtomicJito => {
                 match rt.block_on(async {
                     submit_jito_async(
-                        "https://ny.mainnet.block-engine.jito.wtf",
+                        &self.jito_endpoint,
                         &tx_base64,
                         tip_lamports,
                     )
@@ -113,17 +183,33 @@ impl UnifiedTradeRouter {
             }
             ExecutionPath::SmartStandard => {
                 match rt.block_on(async {
-                    submit_rpc_async("https://api.mainnet-beta.solana.com", &tx_base64, true).await
+                    submit_rpc_async(&self.rpc_url, &tx_base64, true).await
                 }) {
                     Ok(sig) => Ok(sig),
                     Err(e) => Err(PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(e)),
                 }
             }
-        }
+        }?;
+
+        self.record_exposure(notional_milli_usd);
+        Ok(sig)
     }
 }
 
 impl UnifiedTradeRouter {
+    /// Refuse to trade once the session exposure passes the configured cap
+    fn check_exposure(&self) -> PyResult<()> {
+        let exposure = self
+            .total_session_exposure
+            .load(std::sync::atomic::Ordering::Relaxed);
+        if exposure > self.max_session_exposure_milli_usd {
+            return Err(PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(
+                "EMERGENCY_STOP: Session exposure limit reached in Rust",
+            ));
+        }
+        Ok(())
+    }
+
     fn execute_jito_bundle(
         &self,
         ix_data: Vec<u8>,
@@ -161,7 +247,7 @@ impl UnifiedTradeRouter {
 
         match rt.block_on(async {
             submit_jito_async(
-                "https://ny.mainnet.block-engine.jito.wtf",
+                &self.jito_endpoint,
                 &tx_base64,
                 tip_lamports,
             )
@@ -172,28 +258,44 @@ impl UnifiedTradeRouter {
         }
     }
 
-    fn execute_standard_tx(
+    /// Sign the payload behind a compute budget: `cu_limit` units at
+    /// `priority_fee` micro-lamports per CU.
+    fn build_standard_tx(
         &self,
-        ix_data: Vec<u8>,
-        _cu_limit: u32,
-        _priority_fee: u64,
+        ix_data: &[u8],
+        cu_limit: u32,
+        priority_fee: u64,
         blockhash: solana_sdk::hash::Hash,
-    ) -> PyResult<String> {
+    ) -> PyResult<Transaction> {
         // 1. Deserialize
-        let ix: Instruction = bincode::deserialize(&ix_data).map_err(|e| {
+        let ix: Instruction = bincode::deserialize(ix_data).map_err(|e| {
             PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
                 "Failed to deserialize instruction: {}",
                 e
             ))
         })?;
 
-        // 2. Build & Sign
-        let tx = Transaction::new_signed_with_payer(
-            &[ix],
+        // 2. Build & Sign (compute budget must precede the payload)
+        Ok(Transaction::new_signed_with_payer(
+            &[
+                ComputeBudgetInstruction::set_compute_unit_limit(cu_limit),
+                ComputeBudgetInstruction::set_compute_unit_price(priority_fee),
+                ix,
+            ],
             Some(&self.keypair.pubkey()),
             &[&self.keypair],
             blockhash,
-        );
+        ))
+    }
+
+    fn execute_standard_tx(
+        &self,
+        ix_data: Vec<u8>,
+        cu_limit: u32,
+        priority_fee: u64,
+        blockhash: solana_sdk::hash::Hash,
+    ) -> PyResult<String> {
+        let tx = self.build_standard_tx(&ix_data, cu_limit, priority_fee, blockhash)?;
 
         // 3. Submit via RPC
         let rt = get_runtime();
@@ -201,7 +303,7 @@ impl UnifiedTradeRouter {
             base64::engine::general_purpose::STANDARD.encode(bincode::serialize(&tx).unwrap());
 
         match rt.block_on(async {
-            submit_rpc_async("https://api.mainnet-beta.solana.com", &tx_base64, true).await
+            submit_rpc_async(&self.rpc_url, &tx_base64, true).await
         }) {
             Ok(sig) => Ok(sig),
             Err(e) => Err(PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(e)),
@@ -216,6 +318,9 @@ impl UnifiedTradeRouter {
 
 use solana_sdk::compute_budget::ComputeBudgetInstruction;
 
+/// Per-transaction compute unit cap enforced by the runtime
+const MAX_TX_COMPUTE_UNITS: u32 = 1_400_000;
+
 /// Multi-hop atomic execution builder
 /// Transforms a MultiverseCycle into a single Jito bundle transaction
 #[pyclass]
@@ -266,7 +371,8 @@ pub struct SwapLeg {
     #[pyo3(get)]
     pub output_mint: String,
 
-    /// Serialized swap instruction (DEX-specific)
+    /// Serialized swap instruction (DEX-specific), or several instructions
+    /// packed with `serialize_instructions` (e.g. wrap SOL + swap + unwrap)
     #[pyo3(get)]
     pub instruction_data: Vec<u8>,
 }
@@ -306,6 +412,15 @@ pub struct MultiHopBuilder {
     /// Minimum tip in lamports
     min_tip_lamports: u64,
 
+    /// Bundles estimated above this many CU are rejected (see `set_max_compute_units`)
+    max_compute_units: u32,
+
+    /// On-chain assert program used for revert protection (see `set_assert_program`)
+    assert_program: Option<Pubkey>,
+
+    /// Round-robin cursor into `jito_tip_accounts`
+    tip_cursor: std::sync::atomic::AtomicUsize,
+
     /// Session statistics
     bundles_built: std::sync::atomic::AtomicU64,
     bundles_submitted: std::sync::atomic::AtomicU64,
@@ -339,11 +454,37 @@ impl MultiHopBuilder {
             cu_per_leg: cu_per_leg.unwrap_or(60_000),
             cu_base_overhead: 50_000,
             min_tip_lamports: min_tip_lamports.unwrap_or(10_000),
+            max_compute_units: MAX_TX_COMPUTE_UNITS,
+            assert_program: None,
+            tip_cursor: std::sync::atomic::AtomicUsize::new(0),
             bundles_built: std::sync::atomic::AtomicU64::new(0),
             bundles_submitted: std::sync::atomic::AtomicU64::new(0),
         })
     }
 
+    /// Set the assert program used when `build_bundle` gets `min_out_lamports`.
+    ///
+    /// The program is invoked with data `[0x00, min_lamports: u64 LE]` and the
+    /// wallet as its only (read-only) account, and must fail the transaction
+    /// if the wallet's balance is below `min_lamports`.
+    pub fn set_assert_program(&mut self, program_id: String) -> PyResult<()> {
+        let program = Pubkey::from_str(&program_id).map_err(|e| {
+            PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                "Invalid assert program id: {}",
+                e
+            ))
+        })?;
+        self.assert_program = Some(program);
+        Ok(())
+    }
+
+    /// Cap on the estimated CU of a bundle (default 1.4M, the runtime limit).
+    /// `build_bundle` rejects routes above it instead of building a
+    /// transaction that can only fail.
+    pub fn set_max_compute_units(&mut self, max_compute_units: u32) {
+        self.max_compute_units = max_compute_units;
+    }
+
     /// Calculate required compute units for a multi-hop transaction
     pub fn estimate_compute_units(&self, leg_count: usize) -> u32 {
         // Base overhead + per-leg costs
@@ -376,14 +517,18 @@ impl MultiHopBuilder {
     /// 1. Compute Budget instructions (limit + heap size)
     /// 2. All swap leg instructions in sequence
     /// 3. Jito tip instruction
+    /// 4. Balance assertion (only with `min_out_lamports`), so the whole
+    ///    transaction reverts if the wallet ends below that balance
     ///
     /// Returns a MultiHopBundle ready for submission
+    #[pyo3(signature = (swap_legs, tip_lamports, recent_blockhash, expected_profit_pct, min_out_lamports=None))]
     pub fn build_bundle(
         &self,
         swap_legs: Vec<SwapLeg>,
         tip_lamports: u64,
         recent_blockhash: String,
         expected_profit_pct: f64,
+        min_out_lamports: Option<u64>,
     ) -> PyResult<MultiHopBundle> {
         use std::time::{SystemTime, UNIX_EPOCH};
 
@@ -395,15 +540,28 @@ impl MultiHopBuilder {
             )));
         }
 
+        // A mis-ordered leg list fails on-chain and burns the tip
+        Self::validate_leg_chain(&swap_legs)?;
+
+        // 1. Calculate compute budget; an over-budget route can never land
+        let compute_units = self.estimate_compute_units(leg_count);
+        if compute_units > self.max_compute_units {
+            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                "Estimated {} CU for {} legs exceeds the {} CU limit",
+                compute_units, leg_count, self.max_compute_units
+            )));
+        }
+
+        let assert_ix = min_out_lamports
+            .map(|min_out| self.build_assert_instruction(min_out))
+            .transpose()?;
+
         let blockhash = solana_sdk::hash::Hash::from_str(&recent_blockhash).map_err(|e| {
             PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Invalid blockhash: {}", e))
         })?;
 
-        // 1. Calculate compute budget
-        let compute_units = self.estimate_compute_units(leg_count);
-
         // 2. Build instruction list
-        let mut instructions: Vec<Instruction> = Vec::with_capacity(leg_count + 3);
+        let mut instructions: Vec<Instruction> = Vec::with_capacity(leg_count + 4);
 
         // Add compute budget instruction
         instructions.push(ComputeBudgetInstruction::set_compute_unit_limit(
@@ -415,15 +573,15 @@ impl MultiHopBuilder {
             instructions.push(ComputeBudgetInstruction::request_heap_frame(256 * 1024));
         }
 
-        // 3. Deserialize and add swap instructions
+        // 3. Deserialize and add swap instructions, flattening multi-instruction legs
         for leg in &swap_legs {
-            let ix: Instruction = bincode::deserialize(&leg.instruction_data).map_err(|e| {
+            let leg_ixs = decode_instructions(&leg.instruction_data).map_err(|e| {
                 PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
                     "Failed to deserialize leg instruction for {}: {}",
                     leg.dex, e
                 ))
             })?;
-            instructions.push(ix);
+            instructions.extend(leg_ixs);
         }
 
         // 4. Add Jito tip instruction
@@ -432,7 +590,12 @@ impl MultiHopBuilder {
             system_instruction::transfer(&self.keypair.pubkey(), &tip_account, tip_lamports);
         instructions.push(tip_ix);
 
-        // 5. Build and sign transaction
+        // 5. Revert protection (checked after the tip is paid)
+        if let Some(ix) = assert_ix {
+            instructions.push(ix);
+        }
+
+        // 6. Build and sign transaction
         let tx = Transaction::new_signed_with_payer(
             &instructions,
             Some(&self.keypair.pubkey()),
@@ -440,7 +603,7 @@ impl MultiHopBuilder {
             blockhash,
         );
 
-        // 6. Serialize to base64
+        // 7. Serialize to base64
         let tx_bytes = bincode::serialize(&tx).map_err(|e| {
             PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!(
                 "Failed to serialize transaction: {}",
@@ -478,7 +641,7 @@ impl MultiHopBuilder {
 
         match rt.block_on(async {
             submit_jito_async(
-                "https://ny.mainnet.block-engine.jito.wtf",
+                DEFAULT_JITO_ENDPOINT,
                 &bundle.tx_base64,
                 bundle.tip_lamports,
             )
@@ -494,18 +657,21 @@ impl MultiHopBuilder {
     }
 
     /// Build and submit in one call for maximum speed
+    #[pyo3(signature = (swap_legs, tip_lamports, recent_blockhash, expected_profit_pct, min_out_lamports=None))]
     pub fn build_and_submit(
         &self,
         swap_legs: Vec<SwapLeg>,
         tip_lamports: u64,
         recent_blockhash: String,
         expected_profit_pct: f64,
+        min_out_lamports: Option<u64>,
     ) -> PyResult<String> {
         let bundle = self.build_bundle(
             swap_legs,
             tip_lamports,
             recent_blockhash,
             expected_profit_pct,
+            min_out_lamports,
         )?;
 
         self.submit_bundle(&bundle)
@@ -528,16 +694,70 @@ impl MultiHopBuilder {
 }
 
 impl MultiHopBuilder {
+    /// Each leg must consume what the previous one produced, and the last
+    /// leg must return to the starting mint.
+    fn validate_leg_chain(swap_legs: &[SwapLeg]) -> PyResult<()> {
+        for (i, pair) in swap_legs.windows(2).enumerate() {
+            if pair[0].output_mint != pair[1].input_mint {
+                return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                    "Broken hop {} -> {}: leg {} ({}) outputs {} but leg {} ({}) inputs {}",
+                    i,
+                    i + 1,
+                    i,
+                    pair[0].dex,
+                    pair[0].output_mint,
+                    i + 1,
+                    pair[1].dex,
+                    pair[1].input_mint
+                )));
+            }
+        }
+
+        if let (Some(first), Some(last)) = (swap_legs.first(), swap_legs.last()) {
+            if last.output_mint != first.input_mint {
+                return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                    "Cycle does not close: final leg {} ({}) outputs {} but leg 0 ({}) inputs {}",
+                    swap_legs.len() - 1,
+                    last.dex,
+                    last.output_mint,
+                    first.dex,
+                    first.input_mint
+                )));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Assert instruction: `[0x00, min_lamports: u64 LE]`, accounts `[wallet]`
+    fn build_assert_instruction(&self, min_out_lamports: u64) -> PyResult<Instruction> {
+        let program = self.assert_program.ok_or_else(|| {
+            PyErr::new::<pyo3::exceptions::PyValueError, _>(
+                "min_out_lamports requires an assert program (call set_assert_program first)",
+            )
+        })?;
+
+        let mut data = Vec::with_capacity(9);
+        data.push(0u8);
+        data.extend_from_slice(&min_out_lamports.to_le_bytes());
+
+        Ok(Instruction::new_with_bytes(
+            program,
+            &data,
+            vec![solana_sdk::instruction::AccountMeta::new_readonly(
+                self.keypair.pubkey(),
+                false,
+            )],
+        ))
+    }
+
     /// Rotate through Jito tip accounts for load balancing
     fn get_tip_account(&self) -> Pubkey {
-        use std::time::{SystemTime, UNIX_EPOCH};
-        let now = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap()
-            .as_millis() as usize;
-
-        // Simple rotation based on time
-        self.jito_tip_accounts[now % self.jito_tip_accounts.len()]
+        // Round-robin per bundle, so bundles built in the same millisecond don't collide
+        let idx = self
+            .tip_cursor
+            .fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+        self.jito_tip_accounts[idx % self.jito_tip_accounts.len()]
     }
 }
 
@@ -550,3 +770,286 @@ pub fn register_router_classes(m: &PyModule) -> PyResult<()> {
     m.add_class::<MultiHopBuilder>()?;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::network_submitter::tests::mock_rpc;
+    use solana_sdk::instruction::AccountMeta;
+
+    fn memo_ix_data(payer: &Pubkey) -> Vec<u8> {
+        let ix = Instruction::new_with_bytes(
+            Pubkey::from_str("MemoSq4gqABAXKb96qnH8TysNcWxMyWCqXgDLGmfcHr").unwrap(),
+            b"phantom",
+            vec![AccountMeta::new_readonly(*payer, true)],
+        );
+        bincode::serialize(&ix).unwrap()
+    }
+
+    #[test]
+    fn test_route_posts_to_configured_rpc() {
+        let rpc = mock_rpc(vec![(0, r#"{"jsonrpc":"2.0","id":1,"result":"mocksig"}"#)]);
+        let keypair = Keypair::new();
+
+        let router = UnifiedTradeRouter::new(keypair.to_base58_string(), Some(rpc.clone()), None, DEFAULT_MAX_SESSION_EXPOSURE_MILLI_USD).unwrap();
+        assert_eq!(router.get_rpc_url(), rpc);
+        assert_eq!(router.get_jito_endpoint(), DEFAULT_JITO_ENDPOINT);
+
+        let sig = router
+            .route(
+                ExecutionPath::SmartStandard,
+                memo_ix_data(&keypair.pubkey()),
+                200_000,
+                0,
+                solana_sdk::hash::Hash::default().to_string(),
+                0,
+            )
+            .unwrap();
+        assert_eq!(sig, "mocksig");
+    }
+
+    #[test]
+    fn test_standard_tx_applies_compute_budget() {
+        let keypair = Keypair::new();
+        let router = UnifiedTradeRouter::new(keypair.to_base58_string(), None, None, DEFAULT_MAX_SESSION_EXPOSURE_MILLI_USD).unwrap();
+
+        let tx = router
+            .build_standard_tx(&memo_ix_data(&keypair.pubkey()), 250_000, 12_345, solana_sdk::hash::Hash::default())
+            .unwrap();
+        let ixs = &tx.message.instructions;
+        assert_eq!(ixs.len(), 3);
+
+        let program = |i: usize| tx.message.account_keys[ixs[i].program_id_index as usize];
+        assert_eq!(program(0), solana_sdk::compute_budget::id());
+        assert_eq!(program(1), solana_sdk::compute_budget::id());
+        assert_eq!(ixs[0].data, ComputeBudgetInstruction::set_compute_unit_limit(250_000).data);
+        assert_eq!(ixs[1].data, ComputeBudgetInstruction::set_compute_unit_price(12_345).data);
+        assert_eq!(program(2).to_string(), "MemoSq4gqABAXKb96qnH8TysNcWxMyWCqXgDLGmfcHr");
+        assert!(tx.verify().is_ok());
+    }
+
+    #[test]
+    fn test_route_tracks_exposure_and_stops() {
+        pyo3::prepare_freethreaded_python();
+        let rpc = mock_rpc(vec![(0, r#"{"jsonrpc":"2.0","id":1,"result":"mocksig"}"#)]);
+        let keypair = Keypair::new();
+        let router = UnifiedTradeRouter::new(keypair.to_base58_string(), Some(rpc), None, DEFAULT_MAX_SESSION_EXPOSURE_MILLI_USD).unwrap();
+        let blockhash = solana_sdk::hash::Hash::default().to_string();
+
+        // Successful submission adds the trade's notional
+        router
+            .route(ExecutionPath::SmartStandard, memo_ix_data(&keypair.pubkey()), 200_000, 0, blockhash.clone(), 2_500_000)
+            .unwrap();
+        assert_eq!(router.get_total_session_exposure(), 2_500_000);
+
+        router.record_exposure(8_000_000);
+        let err = router
+            .route(ExecutionPath::SmartStandard, memo_ix_data(&keypair.pubkey()), 200_000, 0, blockhash.clone(), 0)
+            .unwrap_err();
+        assert!(err.to_string().contains("EMERGENCY_STOP"));
+
+        let err = router.route_transaction(ExecutionPath::SmartStandard, vec![0u8; 8], 0, 0).unwrap_err();
+        assert!(err.to_string().contains("EMERGENCY_STOP"));
+
+        router.reset_exposure();
+        assert_eq!(router.get_total_session_exposure(), 0);
+    }
+
+    #[test]
+    fn test_low_exposure_cap_stops_earlier() {
+        pyo3::prepare_freethreaded_python();
+        let keypair = Keypair::new();
+        let mut router = UnifiedTradeRouter::new(keypair.to_base58_string(), None, None, 1_000).unwrap();
+        let blockhash = solana_sdk::hash::Hash::default().to_string();
+
+        // $2 recorded is far below the default cap but past a $1 cap
+        router.record_exposure(2_000);
+        let err = router
+            .route(ExecutionPath::SmartStandard, memo_ix_data(&keypair.pubkey()), 200_000, 0, blockhash, 0)
+            .unwrap_err();
+        assert!(err.to_string().contains("EMERGENCY_STOP"));
+
+        router.set_max_exposure(5_000);
+        assert_eq!(router.get_max_session_exposure(), 5_000);
+        assert!(router.check_exposure().is_ok());
+    }
+
+    const MINT_A: &str = "So11111111111111111111111111111111111111112";
+    const MINT_B: &str = "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v";
+    const MINT_C: &str = "DezXAZ8z7PnrnRJjz3wXBoRgixCa6xjnB7YaB1pPB263";
+
+    fn leg(input_mint: &str, output_mint: &str, payer: &Pubkey) -> SwapLeg {
+        SwapLeg::new(
+            "pool".to_string(),
+            "Raydium".to_string(),
+            input_mint.to_string(),
+            output_mint.to_string(),
+            memo_ix_data(payer),
+        )
+    }
+
+    /// MINT_A -> MINT_B -> MINT_C -> MINT_A
+    fn triangle_legs(payer: &Pubkey) -> Vec<SwapLeg> {
+        vec![
+            leg(MINT_A, MINT_B, payer),
+            leg(MINT_B, MINT_C, payer),
+            leg(MINT_C, MINT_A, payer),
+        ]
+    }
+
+    fn decode_bundle(bundle: &MultiHopBundle) -> Transaction {
+        let bytes = base64::engine::general_purpose::STANDARD
+            .decode(&bundle.tx_base64)
+            .unwrap();
+        bincode::deserialize(&bytes).unwrap()
+    }
+
+    #[test]
+    fn test_tip_accounts_rotate_round_robin() {
+        let keypair = Keypair::new();
+        let builder = MultiHopBuilder::new(keypair.to_base58_string(), None, None).unwrap();
+        let blockhash = solana_sdk::hash::Hash::default().to_string();
+
+        let mut uses: std::collections::HashMap<Pubkey, usize> = std::collections::HashMap::new();
+        for _ in 0..16 {
+            let bundle = builder
+                .build_bundle(triangle_legs(&keypair.pubkey()), 10_000, blockhash.clone(), 0.01, None)
+                .unwrap();
+            let tx = decode_bundle(&bundle);
+
+            // Tip transfer is the last instruction: [from, to]
+            let tip_ix = tx.message.instructions.last().unwrap();
+            let tip_account = tx.message.account_keys[tip_ix.accounts[1] as usize];
+            *uses.entry(tip_account).or_default() += 1;
+        }
+
+        assert_eq!(uses.len(), 8);
+        assert!(builder.jito_tip_accounts.iter().all(|a| uses[a] == 2));
+    }
+
+    #[test]
+    fn test_build_bundle_accepts_closed_cycle() {
+        let keypair = Keypair::new();
+        let builder = MultiHopBuilder::new(keypair.to_base58_string(), None, None).unwrap();
+
+        let bundle = builder
+            .build_bundle(triangle_legs(&keypair.pubkey()), 10_000, solana_sdk::hash::Hash::default().to_string(), 0.01, None)
+            .unwrap();
+        assert_eq!(bundle.leg_count, 3);
+    }
+
+    #[test]
+    fn test_build_bundle_flattens_multi_instruction_leg() {
+        let keypair = Keypair::new();
+        let payer = keypair.pubkey();
+        let builder = MultiHopBuilder::new(keypair.to_base58_string(), None, None).unwrap();
+
+        // Leg 0 wraps SOL, swaps, and closes the temporary account
+        let wsol = Pubkey::new_unique().to_string();
+        let wrapped = crate::instruction_builder::serialize_instructions(vec![
+            crate::instruction_builder::build_sync_native_ix(&wsol).unwrap(),
+            memo_ix_data(&payer),
+            crate::instruction_builder::build_close_account_ix(&wsol, &payer.to_string(), &payer.to_string()).unwrap(),
+        ])
+        .unwrap();
+        let mut legs = triangle_legs(&payer);
+        legs[0].instruction_data = wrapped;
+
+        let bundle = builder
+            .build_bundle(legs, 10_000, solana_sdk::hash::Hash::default().to_string(), 0.01, None)
+            .unwrap();
+        assert_eq!(bundle.leg_count, 3);
+
+        // compute limit + (3 + 1 + 1) leg instructions + tip
+        let tx = decode_bundle(&bundle);
+        assert_eq!(tx.message.instructions.len(), 1 + 5 + 1);
+        let program_of = |i: usize| tx.message.account_keys[tx.message.instructions[i].program_id_index as usize].to_string();
+        let token_program = "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA";
+        let memo_program = "MemoSq4gqABAXKb96qnH8TysNcWxMyWCqXgDLGmfcHr";
+        assert_eq!(
+            (1..6).map(program_of).collect::<Vec<_>>(),
+            [token_program, memo_program, token_program, memo_program, memo_program]
+        );
+    }
+
+    #[test]
+    fn test_build_bundle_rejects_broken_chain() {
+        pyo3::prepare_freethreaded_python();
+        let keypair = Keypair::new();
+        let builder = MultiHopBuilder::new(keypair.to_base58_string(), None, None).unwrap();
+        let blockhash = solana_sdk::hash::Hash::default().to_string();
+
+        // Legs 1 and 2 swapped: A->B is followed by C->A
+        let mut legs = triangle_legs(&keypair.pubkey());
+        legs.swap(1, 2);
+        let err = builder.build_bundle(legs, 10_000, blockhash.clone(), 0.01, None).unwrap_err();
+        assert!(err.to_string().contains("Broken hop 0 -> 1"));
+
+        // Chain is linked but ends on the wrong mint
+        let open = vec![leg(MINT_A, MINT_B, &keypair.pubkey()), leg(MINT_B, MINT_C, &keypair.pubkey())];
+        let err = builder.build_bundle(open, 10_000, blockhash, 0.01, None).unwrap_err();
+        assert!(err.to_string().contains("Cycle does not close"));
+    }
+
+    #[test]
+    fn test_build_bundle_appends_assert_instruction() {
+        pyo3::prepare_freethreaded_python();
+        let keypair = Keypair::new();
+        let mut builder = MultiHopBuilder::new(keypair.to_base58_string(), None, None).unwrap();
+        let blockhash = solana_sdk::hash::Hash::default().to_string();
+
+        // No assert program configured yet
+        let err = builder
+            .build_bundle(triangle_legs(&keypair.pubkey()), 10_000, blockhash.clone(), 0.01, Some(1_000_000))
+            .unwrap_err();
+        assert!(err.to_string().contains("set_assert_program"));
+
+        let assert_program = Pubkey::new_unique();
+        builder.set_assert_program(assert_program.to_string()).unwrap();
+
+        let bundle = builder
+            .build_bundle(triangle_legs(&keypair.pubkey()), 10_000, blockhash.clone(), 0.01, Some(1_000_000))
+            .unwrap();
+        let tx = decode_bundle(&bundle);
+        let last = tx.message.instructions.last().unwrap();
+        assert_eq!(tx.message.account_keys[last.program_id_index as usize], assert_program);
+        assert_eq!(last.data[0], 0);
+        assert_eq!(u64::from_le_bytes(last.data[1..9].try_into().unwrap()), 1_000_000);
+
+        // Without min_out the bundle has no assert instruction
+        let bundle = builder
+            .build_bundle(triangle_legs(&keypair.pubkey()), 10_000, blockhash, 0.01, None)
+            .unwrap();
+        let tx = decode_bundle(&bundle);
+        assert!(!tx.message.account_keys.contains(&assert_program));
+    }
+
+    #[test]
+    fn test_build_bundle_rejects_routes_over_cu_limit() {
+        pyo3::prepare_freethreaded_python();
+        let keypair = Keypair::new();
+        let payer = keypair.pubkey();
+        let blockhash = solana_sdk::hash::Hash::default().to_string();
+
+        // Five heavy legs: 50k + 5 * 300k = 1.55M CU
+        let mut builder = MultiHopBuilder::new(keypair.to_base58_string(), Some(300_000), None).unwrap();
+        let five_legs = || {
+            let mut legs = triangle_legs(&payer);
+            legs.push(leg(MINT_A, MINT_B, &payer));
+            legs.push(leg(MINT_B, MINT_A, &payer));
+            legs
+        };
+        let err = builder.build_bundle(five_legs(), 10_000, blockhash.clone(), 0.01, None).unwrap_err();
+        assert!(err.to_string().contains("exceeds the 1400000 CU limit"));
+
+        // Three of the same legs fit
+        let bundle = builder
+            .build_bundle(triangle_legs(&payer), 10_000, blockhash.clone(), 0.01, None)
+            .unwrap();
+        assert_eq!(bundle.compute_units, 950_000);
+
+        // A tighter cap applies to shorter routes too
+        builder.set_max_compute_units(900_000);
+        assert!(builder.build_bundle(triangle_legs(&payer), 10_000, blockhash, 0.01, None).is_err());
+    }
+}
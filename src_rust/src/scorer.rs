@@ -8,8 +8,13 @@
 //! Net Profit Equation:
 //! Net = (Size × Spread%) - (Gas + Jito + Slippage + DEX Fee)
 
+use crate::amm_math::compute_price_impact;
 use crate::metadata::SharedTokenMetadata;
 use pyo3::prelude::*;
+use std::collections::HashMap;
+
+/// Minimum pool liquidity required to consider a trade (USD).
+const MIN_LIQUIDITY_USD: f64 = 500.0;
 
 // ============================================================================
 // CONFIGURATION
@@ -43,6 +48,16 @@ pub struct ScorerConfig {
     /// Default trade size for calculations (USD)
     #[pyo3(get, set)]
     pub default_trade_size_usd: f64,
+
+    /// Per-DEX fee overrides in basis points (e.g. {"orca_stable": 5}).
+    /// DEXes not listed here fall back to `dex_fee_bps`.
+    #[pyo3(get, set)]
+    pub dex_fee_overrides: HashMap<String, u16>,
+
+    /// Slot age at which metadata is considered fully stale.
+    /// Confidence decays linearly to zero at this age and the trade is rejected.
+    #[pyo3(get, set)]
+    pub max_stale_slots: u64,
 }
 
 #[pymethods]
@@ -54,8 +69,11 @@ impl ScorerConfig {
         gas_fee_usd = 0.02,
         jito_tip_usd = 0.001,
         dex_fee_bps = 30,
-        default_trade_size_usd = 15.0
+        default_trade_size_usd = 15.0,
+        dex_fee_overrides = None,
+        max_stale_slots = 20
     ))]
+    #[allow(clippy::too_many_arguments)]
     fn new(
         min_profit_usd: f64,
         max_slippage_bps: u16,
@@ -63,6 +81,8 @@ impl ScorerConfig {
         jito_tip_usd: f64,
         dex_fee_bps: u16,
         default_trade_size_usd: f64,
+        dex_fee_overrides: Option<HashMap<String, u16>>,
+        max_stale_slots: u64,
     ) -> Self {
         ScorerConfig {
             min_profit_usd,
@@ -71,9 +91,23 @@ impl ScorerConfig {
             jito_tip_usd,
             dex_fee_bps,
             default_trade_size_usd,
+            dex_fee_overrides: dex_fee_overrides.unwrap_or_default(),
+            max_stale_slots,
         }
     }
 
+    /// Set the fee for a specific DEX (basis points).
+    fn set_dex_fee(&mut self, dex: String, fee_bps: u16) {
+        self.dex_fee_overrides.insert(dex, fee_bps);
+    }
+
+    /// Resolve the fee for a DEX, falling back to `dex_fee_bps`.
+    #[pyo3(signature = (dex = None))]
+    fn fee_bps_for(&self, dex: Option<&str>) -> u16 {
+        dex.and_then(|d| self.dex_fee_overrides.get(d).copied())
+            .unwrap_or(self.dex_fee_bps)
+    }
+
     fn __repr__(&self) -> String {
         format!(
             "ScorerConfig(min_profit={:.4}, max_slip={}bps, gas={:.4}, jito={:.4}, dex={}bps)",
@@ -130,6 +164,185 @@ impl ValidatedSignal {
     }
 }
 
+// ============================================================================
+// TRADE EXPLANATION (DRY-RUN)
+// ============================================================================
+
+/// Full breakdown of a scoring decision, produced whether or not the trade passes.
+/// Used for debugging why opportunities aren't firing.
+#[pyclass]
+#[derive(Clone, Debug)]
+pub struct TradeExplanation {
+    /// Token mint address
+    #[pyo3(get)]
+    pub token: String,
+
+    /// Trade size used for the calculation (USD)
+    #[pyo3(get)]
+    pub trade_size_usd: f64,
+
+    // --- Checks ---
+    #[pyo3(get)]
+    pub rug_safe: bool,
+    #[pyo3(get)]
+    pub liquidity_ok: bool,
+    #[pyo3(get)]
+    pub transfer_fee_ok: bool,
+    #[pyo3(get)]
+    pub mint_authority_ok: bool,
+    #[pyo3(get)]
+    pub spread_ok: bool,
+    #[pyo3(get)]
+    pub freshness_ok: bool,
+    #[pyo3(get)]
+    pub profit_ok: bool,
+
+    // --- Economics (USD) ---
+    #[pyo3(get)]
+    pub gross_spread: f64,
+    #[pyo3(get)]
+    pub gas_fee: f64,
+    #[pyo3(get)]
+    pub jito_tip: f64,
+    #[pyo3(get)]
+    pub dex_fee: f64,
+    #[pyo3(get)]
+    pub slippage: f64,
+    #[pyo3(get)]
+    pub transfer_tax: f64,
+    #[pyo3(get)]
+    pub total_frictions: f64,
+    #[pyo3(get)]
+    pub net_profit: f64,
+
+    /// Confidence the trade would have been scored with (0.0 if rejected)
+    #[pyo3(get)]
+    pub confidence: f32,
+
+    /// First failing check, or None if the trade would be approved
+    #[pyo3(get)]
+    pub rejected_reason: Option<String>,
+}
+
+#[pymethods]
+impl TradeExplanation {
+    /// True if the trade would be approved by `score_trade`.
+    #[getter]
+    fn approved(&self) -> bool {
+        self.rejected_reason.is_none()
+    }
+
+    fn __repr__(&self) -> String {
+        match &self.rejected_reason {
+            None => format!(
+                "TradeExplanation({} APPROVED | gross={:.4} - frictions={:.4} = net={:.4} USD)",
+                self.token, self.gross_spread, self.total_frictions, self.net_profit
+            ),
+            Some(reason) => format!(
+                "TradeExplanation({} REJECTED: {} | net={:.4} USD)",
+                self.token, reason, self.net_profit
+            ),
+        }
+    }
+}
+
+/// Itemized frictions for a single trade (USD).
+#[derive(Clone, Copy, Debug, Default)]
+struct FrictionBreakdown {
+    gas: f64,
+    jito: f64,
+    dex_fee: f64,
+    slippage: f64,
+    transfer_tax: f64,
+}
+
+impl FrictionBreakdown {
+    fn total(&self) -> f64 {
+        self.gas + self.jito + self.dex_fee + self.slippage + self.transfer_tax
+    }
+}
+
+/// Outcome of each pre-flight safety check.
+#[derive(Clone, Copy, Debug)]
+struct SafetyChecks {
+    rug_safe: bool,
+    liquidity_ok: bool,
+    transfer_fee_ok: bool,
+    mint_authority_ok: bool,
+    spread_ok: bool,
+}
+
+impl SafetyChecks {
+    fn all_passed(&self) -> bool {
+        self.rug_safe
+            && self.liquidity_ok
+            && self.transfer_fee_ok
+            && self.mint_authority_ok
+            && self.spread_ok
+    }
+}
+
+// ============================================================================
+// SPREAD SMOOTHING
+// ============================================================================
+
+/// Default EMA weight of the newest spread observation.
+const DEFAULT_SPREAD_EMA_ALPHA: f64 = 0.2;
+
+/// Per-mint exponential moving average of observed spreads (bps).
+/// Damps single-tick flickers on thin pools before they reach scoring.
+#[pyclass]
+#[derive(Clone, Debug)]
+pub struct SpreadTracker {
+    /// Weight of the newest observation, in (0, 1]
+    #[pyo3(get)]
+    alpha: f64,
+    ema_bps: HashMap<String, f64>,
+}
+
+#[pymethods]
+impl SpreadTracker {
+    #[new]
+    #[pyo3(signature = (alpha = DEFAULT_SPREAD_EMA_ALPHA))]
+    fn new(alpha: f64) -> PyResult<Self> {
+        if !(alpha > 0.0 && alpha <= 1.0) {
+            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                "alpha must be in (0, 1], got {}",
+                alpha
+            )));
+        }
+        Ok(SpreadTracker {
+            alpha,
+            ema_bps: HashMap::new(),
+        })
+    }
+
+    /// Fold a spread observation into the mint's EMA and return the new value.
+    /// The first observation for a mint seeds the EMA.
+    fn observe_spread(&mut self, mint: String, spread_bps: f64) -> f64 {
+        let alpha = self.alpha;
+        *self
+            .ema_bps
+            .entry(mint)
+            .and_modify(|ema| *ema += alpha * (spread_bps - *ema))
+            .or_insert(spread_bps)
+    }
+
+    /// Smoothed spread for a mint, if it has been observed.
+    fn smoothed_spread_bps(&self, mint: &str) -> Option<f64> {
+        self.ema_bps.get(mint).copied()
+    }
+
+    /// Forget a mint's history (e.g. after a pool migration).
+    fn reset(&mut self, mint: &str) {
+        self.ema_bps.remove(mint);
+    }
+
+    fn __len__(&self) -> usize {
+        self.ema_bps.len()
+    }
+}
+
 // ============================================================================
 // SIGNAL SCORER ENGINE
 // ============================================================================
@@ -139,6 +352,8 @@ impl ValidatedSignal {
 #[pyclass]
 pub struct SignalScorer {
     config: ScorerConfig,
+    /// When set, scoring uses the mint's EMA spread instead of `spread_bps`
+    spread_tracker: Option<SpreadTracker>,
 }
 
 #[pymethods]
@@ -146,7 +361,31 @@ impl SignalScorer {
     /// Create a new SignalScorer with the given configuration.
     #[new]
     fn new(config: ScorerConfig) -> Self {
-        SignalScorer { config }
+        SignalScorer {
+            config,
+            spread_tracker: None,
+        }
+    }
+
+    /// Score on EMA-smoothed spreads fed through `observe_spread`.
+    /// Mints with no observations yet fall back to the raw `spread_bps`.
+    #[pyo3(signature = (alpha = DEFAULT_SPREAD_EMA_ALPHA))]
+    fn enable_spread_smoothing(&mut self, alpha: f64) -> PyResult<()> {
+        self.spread_tracker = Some(SpreadTracker::new(alpha)?);
+        Ok(())
+    }
+
+    /// Return to scoring on raw `spread_bps`, dropping the EMA history.
+    fn disable_spread_smoothing(&mut self) {
+        self.spread_tracker = None;
+    }
+
+    /// Record a spread tick. Returns the smoothed value, or None when
+    /// smoothing is disabled.
+    fn observe_spread(&mut self, mint: String, spread_bps: f64) -> Option<f64> {
+        self.spread_tracker
+            .as_mut()
+            .map(|tracker| tracker.observe_spread(mint, spread_bps))
     }
 
     /// Score a single trade opportunity.
@@ -155,11 +394,18 @@ impl SignalScorer {
     /// # Arguments
     /// * `metadata` - Token metadata including price, spread, liquidity
     /// * `trade_size_usd` - Optional override for trade size (defaults to config)
-    #[pyo3(signature = (metadata, trade_size_usd = None))]
+    /// * `dex` - DEX the route goes through, used to pick the fee override
+    /// * `current_slot` - Current chain slot; when given, stale metadata is penalized
+    /// * `reserves` - Pool reserves `(token_reserve, quote_reserve)` in raw units;
+    ///   when given, slippage uses the exact constant-product impact
+    #[pyo3(signature = (metadata, trade_size_usd = None, dex = None, current_slot = None, reserves = None))]
     fn score_trade(
         &self,
         metadata: &SharedTokenMetadata,
         trade_size_usd: Option<f64>,
+        dex: Option<&str>,
+        current_slot: Option<u64>,
+        reserves: Option<(u64, u64)>,
     ) -> Option<ValidatedSignal> {
         let size = trade_size_usd.unwrap_or(self.config.default_trade_size_usd);
 
@@ -168,12 +414,18 @@ impl SignalScorer {
             return None;
         }
 
+        // 1b. Staleness Gate (don't act on prices older than max_stale_slots)
+        let freshness = self.freshness_factor(metadata, current_slot);
+        if freshness <= 0.0 {
+            return None;
+        }
+
         // 2. Calculate Gross Spread (potential profit before costs)
-        let spread_pct = metadata.spread_bps as f64 / 10_000.0;
+        let spread_pct = self.effective_spread_bps(metadata) / 10_000.0;
         let gross_spread = size * spread_pct;
 
         // 3. Calculate Total Frictions
-        let frictions = self.calculate_frictions(metadata, size);
+        let frictions = self.calculate_frictions(metadata, size, dex, reserves);
 
         // 4. Net Profit
         let net_profit = gross_spread - frictions;
@@ -184,7 +436,7 @@ impl SignalScorer {
         }
 
         // 6. Compute Confidence Score
-        let confidence = self.compute_confidence(metadata, net_profit);
+        let confidence = self.compute_confidence(metadata, net_profit) * freshness;
 
         // 7. Determine Action
         let action = if metadata.velocity_1m > 0.0 {
@@ -205,60 +457,120 @@ impl SignalScorer {
 
     /// Batch score multiple opportunities.
     /// Returns only the validated signals (filters out unprofitable ones).
+    #[pyo3(signature = (metadata_list, trade_size_usd = None, dex = None, current_slot = None))]
     fn score_batch(
         &self,
         metadata_list: Vec<SharedTokenMetadata>,
         trade_size_usd: Option<f64>,
+        dex: Option<&str>,
+        current_slot: Option<u64>,
     ) -> Vec<ValidatedSignal> {
         metadata_list
             .iter()
-            .filter_map(|m| self.score_trade(m, trade_size_usd))
+            .filter_map(|m| self.score_trade(m, trade_size_usd, dex, current_slot, None))
             .collect()
     }
 
     /// Batch score with parallel processing (for large batches).
     /// Uses Rayon for CPU-parallel filtering.
+    #[pyo3(signature = (metadata_list, trade_size_usd = None, dex = None, current_slot = None))]
     fn score_batch_parallel(
         &self,
         metadata_list: Vec<SharedTokenMetadata>,
         trade_size_usd: Option<f64>,
+        dex: Option<&str>,
+        current_slot: Option<u64>,
     ) -> Vec<ValidatedSignal> {
         use rayon::prelude::*;
 
-        let size = trade_size_usd.unwrap_or(self.config.default_trade_size_usd);
-
         metadata_list
             .par_iter()
-            .filter_map(|m| {
-                // Inline the scoring logic for parallel context
-                if !self.passes_safety_checks(m) {
-                    return None;
-                }
-
-                let spread_pct = m.spread_bps as f64 / 10_000.0;
-                let gross_spread = size * spread_pct;
-                let frictions = self.calculate_frictions(m, size);
-                let net_profit = gross_spread - frictions;
-
-                if net_profit < self.config.min_profit_usd {
-                    return None;
-                }
-
-                let confidence = self.compute_confidence(m, net_profit);
-                let action = if m.velocity_1m > 0.0 { "BUY" } else { "SELL" };
-
-                Some(ValidatedSignal {
-                    net_profit,
-                    confidence,
-                    token: m.mint.clone(),
-                    action: action.to_string(),
-                    gross_spread,
-                    total_frictions: frictions,
-                })
-            })
+            .filter_map(|m| self.score_trade(m, trade_size_usd, dex, current_slot, None))
             .collect()
     }
 
+    /// Dry-run a trade and explain the decision.
+    /// Always returns a breakdown of every check, the gross spread, itemized
+    /// frictions and net profit, plus the first reason for rejection (if any).
+    #[pyo3(signature = (metadata, trade_size_usd = None, dex = None, current_slot = None, reserves = None))]
+    fn explain_trade(
+        &self,
+        metadata: &SharedTokenMetadata,
+        trade_size_usd: Option<f64>,
+        dex: Option<&str>,
+        current_slot: Option<u64>,
+        reserves: Option<(u64, u64)>,
+    ) -> TradeExplanation {
+        let size = trade_size_usd.unwrap_or(self.config.default_trade_size_usd);
+
+        let checks = self.safety_checks(metadata);
+        let freshness = self.freshness_factor(metadata, current_slot);
+        let gross_spread = size * (self.effective_spread_bps(metadata) / 10_000.0);
+        let frictions = self.friction_breakdown(metadata, size, dex, reserves);
+        let total_frictions = frictions.total();
+        let net_profit = gross_spread - total_frictions;
+        let profit_ok = net_profit >= self.config.min_profit_usd;
+
+        let rejected_reason = if !checks.rug_safe {
+            Some("Rug check failed".to_string())
+        } else if !checks.liquidity_ok {
+            Some(format!(
+                "Liquidity ${:.2} below ${:.0} floor",
+                metadata.liquidity_usd, MIN_LIQUIDITY_USD
+            ))
+        } else if !checks.transfer_fee_ok {
+            Some(format!(
+                "Transfer fee {}bps exceeds max slippage {}bps",
+                metadata.transfer_fee_bps, self.config.max_slippage_bps
+            ))
+        } else if !checks.mint_authority_ok {
+            Some("Mint authority active".to_string())
+        } else if !checks.spread_ok {
+            Some("Zero spread".to_string())
+        } else if freshness <= 0.0 {
+            Some(format!(
+                "Stale metadata: last updated slot {}, current slot {}",
+                metadata.last_updated_slot,
+                current_slot.unwrap_or_default()
+            ))
+        } else if !profit_ok {
+            Some(format!(
+                "Net profit ${:.4} below minimum ${:.4}",
+                net_profit, self.config.min_profit_usd
+            ))
+        } else {
+            None
+        };
+
+        let confidence = if rejected_reason.is_none() {
+            self.compute_confidence(metadata, net_profit) * freshness
+        } else {
+            0.0
+        };
+
+        TradeExplanation {
+            token: metadata.mint.clone(),
+            trade_size_usd: size,
+            rug_safe: checks.rug_safe,
+            liquidity_ok: checks.liquidity_ok,
+            transfer_fee_ok: checks.transfer_fee_ok,
+            mint_authority_ok: checks.mint_authority_ok,
+            spread_ok: checks.spread_ok,
+            freshness_ok: freshness > 0.0,
+            profit_ok,
+            gross_spread,
+            gas_fee: frictions.gas,
+            jito_tip: frictions.jito,
+            dex_fee: frictions.dex_fee,
+            slippage: frictions.slippage,
+            transfer_tax: frictions.transfer_tax,
+            total_frictions,
+            net_profit,
+            confidence,
+            rejected_reason,
+        }
+    }
+
     /// Get the current configuration.
     fn get_config(&self) -> ScorerConfig {
         self.config.clone()
@@ -275,48 +587,65 @@ impl SignalScorer {
 // ============================================================================
 
 impl SignalScorer {
+    /// Spread used for gross profit: the EMA when smoothing is on, else raw.
+    fn effective_spread_bps(&self, metadata: &SharedTokenMetadata) -> f64 {
+        self.spread_tracker
+            .as_ref()
+            .and_then(|tracker| tracker.smoothed_spread_bps(&metadata.mint))
+            .unwrap_or(metadata.spread_bps as f64)
+    }
+
     /// Pre-flight safety checks before calculating profitability.
     fn passes_safety_checks(&self, metadata: &SharedTokenMetadata) -> bool {
-        // 1. Rug Safety
-        if !metadata.is_rug_safe {
-            return false;
-        }
-
-        // 2. Minimum Liquidity ($500 floor)
-        if metadata.liquidity_usd < 500.0 {
-            return false;
-        }
-
-        // 3. Token-2022 Transfer Tax Check
-        if metadata.transfer_fee_bps > self.config.max_slippage_bps {
-            return false;
-        }
-
-        // 4. Mint Authority Check (avoid ruggable tokens)
-        if metadata.has_mint_auth {
-            return false;
-        }
+        self.safety_checks(metadata).all_passed()
+    }
 
-        // 5. Spread must be positive
-        if metadata.spread_bps == 0 {
-            return false;
+    /// Evaluate each pre-flight safety check individually.
+    fn safety_checks(&self, metadata: &SharedTokenMetadata) -> SafetyChecks {
+        SafetyChecks {
+            // 1. Rug Safety
+            rug_safe: metadata.is_rug_safe,
+            // 2. Minimum Liquidity ($500 floor)
+            liquidity_ok: metadata.liquidity_usd >= MIN_LIQUIDITY_USD,
+            // 3. Token-2022 Transfer Tax Check
+            transfer_fee_ok: metadata.transfer_fee_bps <= self.config.max_slippage_bps,
+            // 4. Mint Authority Check (avoid ruggable tokens)
+            mint_authority_ok: !metadata.has_mint_auth,
+            // 5. Spread must be positive
+            spread_ok: metadata.spread_bps > 0,
         }
-
-        true
     }
 
     /// Calculate total frictions for a trade.
     /// Frictions = Gas + Jito Tip + DEX Fee + Slippage Impact
-    fn calculate_frictions(&self, metadata: &SharedTokenMetadata, trade_size: f64) -> f64 {
+    fn calculate_frictions(
+        &self,
+        metadata: &SharedTokenMetadata,
+        trade_size: f64,
+        dex: Option<&str>,
+        reserves: Option<(u64, u64)>,
+    ) -> f64 {
+        self.friction_breakdown(metadata, trade_size, dex, reserves)
+            .total()
+    }
+
+    /// Itemized frictions for a trade.
+    fn friction_breakdown(
+        &self,
+        metadata: &SharedTokenMetadata,
+        trade_size: f64,
+        dex: Option<&str>,
+        reserves: Option<(u64, u64)>,
+    ) -> FrictionBreakdown {
         // 1. Fixed Costs
         let gas = self.config.gas_fee_usd;
         let jito = self.config.jito_tip_usd;
 
-        // 2. DEX Fee (proportional to trade size)
-        let dex_fee = trade_size * (self.config.dex_fee_bps as f64 / 10_000.0);
+        // 2. DEX Fee (proportional to trade size, per-DEX override if configured)
+        let dex_fee = trade_size * (self.config.fee_bps_for(dex) as f64 / 10_000.0);
 
-        // 3. Slippage Impact (dynamic based on liquidity)
-        let slippage = self.calculate_slippage_impact(metadata, trade_size);
+        // 3. Slippage Impact (exact from reserves if known, else liquidity heuristic)
+        let slippage = self.calculate_slippage_impact(metadata, trade_size, reserves);
 
         // 4. Token-2022 Transfer Tax (if applicable)
         let transfer_tax = if metadata.transfer_fee_bps > 0 {
@@ -325,12 +654,47 @@ impl SignalScorer {
             0.0
         };
 
-        gas + jito + dex_fee + slippage + transfer_tax
+        FrictionBreakdown {
+            gas,
+            jito,
+            dex_fee,
+            slippage,
+            transfer_tax,
+        }
     }
 
     /// Calculate slippage impact based on trade size vs liquidity.
-    /// Uses the formula: Slippage = Base + (Size/Liquidity) × Impact Multiplier
-    fn calculate_slippage_impact(&self, metadata: &SharedTokenMetadata, trade_size: f64) -> f64 {
+    /// With pool reserves, the size impact is the exact constant-product price impact.
+    /// Otherwise uses the formula: Slippage = Base + (Size/Liquidity) × Impact Multiplier
+    fn calculate_slippage_impact(
+        &self,
+        metadata: &SharedTokenMetadata,
+        trade_size: f64,
+        reserves: Option<(u64, u64)>,
+    ) -> f64 {
+        // Size impact: exact when reserves are known, heuristic fallback
+        let size_impact_pct = reserves
+            .and_then(|(reserve_in, reserve_out)| {
+                Self::exact_impact_pct(metadata, trade_size, reserve_in, reserve_out)
+            })
+            .unwrap_or_else(|| Self::heuristic_impact_pct(metadata, trade_size));
+
+        // Volatility penalty (higher velocity = more slippage)
+        let volatility_penalty = metadata.velocity_1m.abs() * 0.01; // 1% per 1% velocity
+
+        // Total slippage percentage
+        let total_slippage_pct = size_impact_pct + volatility_penalty;
+
+        // Cap at max_slippage_bps from config
+        let max_slippage_pct = self.config.max_slippage_bps as f64 / 10_000.0;
+        let capped_slippage_pct = total_slippage_pct.min(max_slippage_pct);
+
+        // Convert to USD
+        trade_size * capped_slippage_pct
+    }
+
+    /// Linear size-impact approximation from `liquidity_usd` (fraction, 0.01 = 1%).
+    fn heuristic_impact_pct(metadata: &SharedTokenMetadata, trade_size: f64) -> f64 {
         // Base slippage (0.3%)
         let base_slippage_pct = 0.003;
 
@@ -346,20 +710,46 @@ impl SignalScorer {
 
         // Size impact: larger trades relative to liquidity = more slippage
         let size_ratio = trade_size / liquidity;
-        let dynamic_slippage_pct = size_ratio * impact_multiplier;
+        base_slippage_pct + size_ratio * impact_multiplier
+    }
 
-        // Volatility penalty (higher velocity = more slippage)
-        let volatility_penalty = metadata.velocity_1m.abs() * 0.01; // 1% per 1% velocity
+    /// Exact constant-product price impact of selling `trade_size` USD of the
+    /// token into the pool (fraction, 0.01 = 1%). The DEX fee is excluded since
+    /// it is charged separately. Returns None if the size can't be converted.
+    fn exact_impact_pct(
+        metadata: &SharedTokenMetadata,
+        trade_size: f64,
+        reserve_in: u64,
+        reserve_out: u64,
+    ) -> Option<f64> {
+        if metadata.price_usd <= 0.0 || reserve_in == 0 || reserve_out == 0 {
+            return None;
+        }
 
-        // Total slippage percentage
-        let total_slippage_pct = base_slippage_pct + dynamic_slippage_pct + volatility_penalty;
+        let amount_in = trade_size / metadata.price_usd * 10f64.powi(metadata.decimals as i32);
+        if !amount_in.is_finite() || amount_in < 1.0 || amount_in >= u64::MAX as f64 {
+            return None;
+        }
 
-        // Cap at max_slippage_bps from config
-        let max_slippage_pct = self.config.max_slippage_bps as f64 / 10_000.0;
-        let capped_slippage_pct = total_slippage_pct.min(max_slippage_pct);
+        compute_price_impact(amount_in as u64, reserve_in, reserve_out, 0)
+            .ok()
+            .map(|impact| impact / 100.0)
+    }
 
-        // Convert to USD
-        trade_size * capped_slippage_pct
+    /// Freshness multiplier for confidence based on metadata age.
+    /// 1.0 for data from the current slot, decaying linearly to 0.0 at
+    /// `max_stale_slots`. Without a `current_slot` the check is skipped.
+    fn freshness_factor(&self, metadata: &SharedTokenMetadata, current_slot: Option<u64>) -> f32 {
+        let Some(current_slot) = current_slot else {
+            return 1.0;
+        };
+
+        let age = current_slot.saturating_sub(metadata.last_updated_slot);
+        if age >= self.config.max_stale_slots {
+            return if age == 0 { 1.0 } else { 0.0 };
+        }
+
+        1.0 - (age as f32 / self.config.max_stale_slots as f32)
     }
 
     /// Compute confidence score based on metadata quality.
@@ -409,7 +799,9 @@ impl SignalScorer {
 pub fn register_scorer_classes(m: &PyModule) -> PyResult<()> {
     m.add_class::<ScorerConfig>()?;
     m.add_class::<ValidatedSignal>()?;
+    m.add_class::<TradeExplanation>()?;
     m.add_class::<SignalScorer>()?;
+    m.add_class::<SpreadTracker>()?;
     Ok(())
 }
 
@@ -429,6 +821,8 @@ mod tests {
             jito_tip_usd: 0.001,
             dex_fee_bps: 30,
             default_trade_size_usd: 15.0,
+            dex_fee_overrides: HashMap::new(),
+            max_stale_slots: 20,
         }
     }
 
@@ -467,7 +861,7 @@ mod tests {
         let scorer = SignalScorer::new(config);
         let metadata = make_test_metadata();
 
-        let result = scorer.score_trade(&metadata, Some(15.0));
+        let result = scorer.score_trade(&metadata, Some(15.0), None, None, None);
 
         assert!(
             result.is_some(),
@@ -488,7 +882,7 @@ mod tests {
         // Set spread too low to be profitable
         metadata.spread_bps = 5; // 0.05% spread
 
-        let result = scorer.score_trade(&metadata, Some(15.0));
+        let result = scorer.score_trade(&metadata, Some(15.0), None, None, None);
 
         assert!(
             result.is_none(),
@@ -505,7 +899,7 @@ mod tests {
         // Make token unsafe (mint authority active)
         metadata.has_mint_auth = true;
 
-        let result = scorer.score_trade(&metadata, Some(15.0));
+        let result = scorer.score_trade(&metadata, Some(15.0), None, None, None);
 
         assert!(result.is_none(), "Expected unsafe token to be rejected");
     }
@@ -516,7 +910,7 @@ mod tests {
         let scorer = SignalScorer::new(config);
         let metadata = make_test_metadata();
 
-        let frictions = scorer.calculate_frictions(&metadata, 15.0);
+        let frictions = scorer.calculate_frictions(&metadata, 15.0, None, None);
 
         // Expected: gas(0.02) + jito(0.001) + dex(15*0.003=0.045) + slippage(~0.045) ≈ 0.11+
         assert!(frictions > 0.1, "Frictions should be at least $0.10");
@@ -536,8 +930,224 @@ mod tests {
         bad_token.has_mint_auth = true; // Unsafe
 
         let batch = vec![good_token, bad_token];
-        let results = scorer.score_batch(batch, Some(15.0));
+        let results = scorer.score_batch(batch, Some(15.0), None, None);
 
         assert_eq!(results.len(), 1, "Only profitable+safe trades should pass");
     }
+
+    #[test]
+    fn test_dex_fee_override() {
+        let mut config = make_test_config();
+        config
+            .dex_fee_overrides
+            .insert("orca_stable".to_string(), 5);
+        let scorer = SignalScorer::new(config);
+        let mut metadata = make_test_metadata();
+
+        // 1.3% spread on $15 = $0.195 gross. Fixed costs + slippage ≈ $0.07,
+        // so a 5bps fee clears the $0.10 floor while the 30bps default doesn't.
+        metadata.spread_bps = 130;
+
+        let stable = scorer.score_trade(&metadata, Some(15.0), Some("orca_stable"), None, None);
+        assert!(stable.is_some(), "5bps stable pool should be profitable");
+
+        let default = scorer.score_trade(&metadata, Some(15.0), Some("raydium_v4"), None, None);
+        assert!(
+            default.is_none(),
+            "30bps fallback fee should reject the trade"
+        );
+    }
+
+    #[test]
+    fn test_fresh_metadata_keeps_full_confidence() {
+        let scorer = SignalScorer::new(make_test_config());
+        let metadata = make_test_metadata();
+
+        let unchecked = scorer
+            .score_trade(&metadata, Some(15.0), None, None, None)
+            .unwrap();
+        let fresh = scorer
+            .score_trade(
+                &metadata,
+                Some(15.0),
+                None,
+                Some(metadata.last_updated_slot),
+                None,
+            )
+            .unwrap();
+
+        assert_eq!(fresh.confidence, unchecked.confidence);
+    }
+
+    #[test]
+    fn test_stale_metadata_reduces_then_rejects() {
+        let scorer = SignalScorer::new(make_test_config());
+        let metadata = make_test_metadata();
+        let slot = metadata.last_updated_slot;
+
+        let fresh = scorer
+            .score_trade(&metadata, Some(15.0), None, Some(slot), None)
+            .unwrap();
+
+        // 10 slots old with max_stale_slots = 20 -> half confidence
+        let aging = scorer
+            .score_trade(&metadata, Some(15.0), None, Some(slot + 10), None)
+            .unwrap();
+        assert!((aging.confidence - fresh.confidence * 0.5).abs() < 1e-6);
+
+        // 30 slots old -> past the limit, rejected outright
+        let stale = scorer.score_trade(&metadata, Some(15.0), None, Some(slot + 30), None);
+        assert!(stale.is_none(), "Stale metadata should be rejected");
+    }
+
+    #[test]
+    fn test_explain_approved_trade_matches_score() {
+        let scorer = SignalScorer::new(make_test_config());
+        let metadata = make_test_metadata();
+
+        let signal = scorer
+            .score_trade(&metadata, Some(15.0), None, None, None)
+            .unwrap();
+        let explanation = scorer.explain_trade(&metadata, Some(15.0), None, None, None);
+
+        assert!(explanation.rejected_reason.is_none());
+        assert_eq!(explanation.net_profit, signal.net_profit);
+        assert_eq!(explanation.total_frictions, signal.total_frictions);
+        assert_eq!(explanation.confidence, signal.confidence);
+        let itemized = explanation.gas_fee
+            + explanation.jito_tip
+            + explanation.dex_fee
+            + explanation.slippage
+            + explanation.transfer_tax;
+        assert!((itemized - explanation.total_frictions).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_explain_rejection_reasons() {
+        let scorer = SignalScorer::new(make_test_config());
+
+        let mut metadata = make_test_metadata();
+        metadata.has_mint_auth = true;
+        let explanation = scorer.explain_trade(&metadata, Some(15.0), None, None, None);
+        assert!(!explanation.mint_authority_ok);
+        assert_eq!(
+            explanation.rejected_reason.as_deref(),
+            Some("Mint authority active")
+        );
+        // Economics are still computed for rejected trades
+        assert!(explanation.gross_spread > 0.0);
+
+        let mut metadata = make_test_metadata();
+        metadata.spread_bps = 5;
+        let explanation = scorer.explain_trade(&metadata, Some(15.0), None, None, None);
+        assert!(!explanation.profit_ok);
+        assert!(explanation.net_profit < 0.0);
+        assert!(explanation
+            .rejected_reason
+            .unwrap()
+            .starts_with("Net profit"));
+
+        let mut metadata = make_test_metadata();
+        metadata.liquidity_usd = 100.0;
+        let explanation = scorer.explain_trade(&metadata, Some(15.0), None, None, None);
+        assert!(!explanation.liquidity_ok);
+        assert!(explanation
+            .rejected_reason
+            .unwrap()
+            .starts_with("Liquidity"));
+    }
+
+    #[test]
+    fn test_exact_slippage_from_reserves() {
+        let scorer = SignalScorer::new(make_test_config());
+        let metadata = make_test_metadata();
+
+        // Metadata claims $50k liquidity, but the pool actually holds only
+        // $1k per side: 1,000 tokens (6 decimals) at $1 vs 1,000 USDC.
+        let thin_pool = (1_000_000_000, 1_000_000_000);
+
+        let heuristic = scorer.explain_trade(&metadata, Some(15.0), None, None, None);
+        let exact = scorer.explain_trade(&metadata, Some(15.0), None, None, Some(thin_pool));
+
+        // Heuristic: 0.3% base + tiny size ratio ≈ 0.33% of $15
+        assert!(heuristic.slippage < 0.06);
+        // Exact: $15 into a $1k pool ≈ 1.5% impact
+        assert!(exact.slippage > 0.2);
+
+        assert!(scorer
+            .score_trade(&metadata, Some(15.0), None, None, None)
+            .is_some());
+        assert!(
+            scorer
+                .score_trade(&metadata, Some(15.0), None, None, Some(thin_pool))
+                .is_none(),
+            "Real pool depth should make the trade unprofitable"
+        );
+    }
+
+    #[test]
+    fn test_spread_ema_dampens_spikes() {
+        let mut tracker = SpreadTracker::new(0.2).unwrap();
+        let series = [40.0, 42.0, 38.0, 41.0, 400.0, 39.0, 40.0, 42.0];
+
+        let smoothed: Vec<f64> = series
+            .iter()
+            .map(|&bps| tracker.observe_spread("MINT".to_string(), bps))
+            .collect();
+
+        // Seeded by the first tick
+        assert_eq!(smoothed[0], 40.0);
+        // A 10x spike moves the EMA by only alpha of the jump
+        let before = smoothed[3];
+        assert!((smoothed[4] - (before + 0.2 * (400.0 - before))).abs() < 1e-9);
+        assert!(smoothed[4] < 120.0);
+        // ... and decays back toward the baseline afterwards
+        assert!(smoothed[7] < smoothed[4]);
+        assert!(smoothed.iter().all(|&ema| ema < 400.0));
+        assert_eq!(
+            tracker.smoothed_spread_bps("MINT"),
+            smoothed.last().copied()
+        );
+        assert_eq!(tracker.smoothed_spread_bps("OTHER"), None);
+
+        assert!(SpreadTracker::new(0.0).is_err());
+        assert!(SpreadTracker::new(1.5).is_err());
+    }
+
+    #[test]
+    fn test_smoothed_scoring_ignores_single_tick_spike() {
+        let mut scorer = SignalScorer::new(make_test_config());
+        let mut metadata = make_test_metadata();
+        // 0.5% baseline spread is not worth the frictions on $15
+        metadata.spread_bps = 50;
+        for _ in 0..5 {
+            scorer.observe_spread(metadata.mint.clone(), 50.0);
+        }
+        assert_eq!(scorer.observe_spread(metadata.mint.clone(), 50.0), None);
+
+        // One flickering tick at 2.5% looks profitable on the raw value
+        metadata.spread_bps = 250;
+        assert!(scorer
+            .score_trade(&metadata, Some(15.0), None, None, None)
+            .is_some());
+
+        scorer.enable_spread_smoothing(0.2).unwrap();
+        for _ in 0..5 {
+            scorer.observe_spread(metadata.mint.clone(), 50.0);
+        }
+        let ema = scorer.observe_spread(metadata.mint.clone(), 250.0).unwrap();
+        assert!((ema - 90.0).abs() < 1e-9);
+
+        assert!(scorer
+            .score_trade(&metadata, Some(15.0), None, None, None)
+            .is_none());
+        let explanation = scorer.explain_trade(&metadata, Some(15.0), None, None, None);
+        assert!((explanation.gross_spread - 15.0 * 0.009).abs() < 1e-9);
+
+        // Unobserved mints still score on the raw spread
+        metadata.mint = "FreshMint".to_string();
+        assert!(scorer
+            .score_trade(&metadata, Some(15.0), None, None, None)
+            .is_some());
+    }
 }
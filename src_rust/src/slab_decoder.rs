@@ -9,34 +9,59 @@ use bytemuck::{Pod, Zeroable};
 use pyo3::prelude::*;
 use base64::{Engine as _, engine::general_purpose};
 use std::cmp::Ordering;
+use std::collections::BTreeMap;
 
 // ============================================================================
 // PHOENIX STRUCTURES
 // ============================================================================
 
+/// Phoenix MarketHeader size (discriminant through _padding2)
+const PHOENIX_HEADER_SIZE: usize = 576;
+
+/// FIFOMarket fields preceding the bids tree:
+/// _padding [u64; 32] + base_lots_per_base_unit, tick_size_in_quote_lots_per_base_unit,
+/// order_sequence_number, taker_fee_bps, collected/unclaimed quote lot fees
+const PHOENIX_MARKET_PREFIX_SIZE: usize = 256 + 6 * 8;
+
+/// Sokoban RedBlackTree header: root u32 + [u32; 3] padding + allocator
+/// size u64 + bump_index u32 + free_list_head u32
+const PHOENIX_TREE_HEADER_SIZE: usize = 32;
+
 /// Phoenix Market Header (Partial - first 128 bytes)
 #[repr(C)]
 #[derive(Copy, Clone, Pod, Zeroable)]
 struct PhoenixHeaderMin {
     discriminant: u64,
     status: u64,
-    // MarketSizeParams and padding
-    pub _padding: [u64; 14], 
+    // MarketSizeParams
+    bids_size: u64,
+    asks_size: u64,
+    num_seats: u64,
+    // TokenParams and remaining header fields
+    pub _padding: [u64; 11],
 }
 
-/// Phoenix Order Node (simplified structure for extraction)
-/// Real Phoenix orders are more complex, but this captures the essentials.
+/// Phoenix order tree node: sokoban registers + FIFOOrderId key + FIFORestingOrder value.
 #[repr(C)]
 #[derive(Copy, Clone, Pod, Zeroable)]
-struct PhoenixOrderNode {
-    /// Price in ticks (shifted)
+struct PhoenixTreeNode {
+    /// Red-black tree links (1-based node indices, 0 = sentinel)
+    left: u32,
+    right: u32,
+    parent: u32,
+    color: u32,
+    /// FIFOOrderId: price in ticks
     price_in_ticks: u64,
-    /// Size in base lots
-    size_in_base_lots: u64,
-    /// Sequence number for ordering
-    sequence_number: u64,
-    /// Padding to align to 32 bytes
-    _padding: u64,
+    /// FIFOOrderId: order sequence number
+    order_sequence_number: u64,
+    /// FIFORestingOrder: trader seat index
+    trader_index: u64,
+    /// FIFORestingOrder: size in base lots
+    num_base_lots: u64,
+    /// FIFORestingOrder: expiry slot (0 = none)
+    last_valid_slot: u64,
+    /// FIFORestingOrder: expiry timestamp (0 = none)
+    last_valid_unix_timestamp_in_seconds: u64,
 }
 
 /// L2 Order Level (returned to Python)
@@ -113,6 +138,94 @@ impl L2Orderbook {
             .map(|l| l.size * l.price)
             .sum()
     }
+    
+    /// Simulate a market order sweeping the book.
+    /// 
+    /// Walks asks for a buy (bids for a sell) level by level until `size` is
+    /// filled or liquidity runs out.
+    /// 
+    /// # Returns
+    /// Tuple of (average_fill_price, filled_size). `filled_size < size` means
+    /// the book could only partially fill; (0.0, 0.0) if nothing filled.
+    fn simulate_sweep(&self, size: f64, is_buy: bool) -> (f64, f64) {
+        let levels = if is_buy { &self.asks } else { &self.bids };
+        
+        let mut remaining = size;
+        let mut filled = 0.0;
+        let mut notional = 0.0;
+        
+        for level in levels {
+            if remaining <= 0.0 {
+                break;
+            }
+            let take = level.size.min(remaining);
+            filled += take;
+            notional += take * level.price;
+            remaining -= take;
+        }
+        
+        if filled <= 0.0 {
+            return (0.0, 0.0);
+        }
+        (notional / filled, filled)
+    }
+}
+
+impl L2Orderbook {
+    /// Build a book from already-sorted sides and derive top-of-book fields.
+    fn from_levels(bids: Vec<L2Level>, asks: Vec<L2Level>) -> Self {
+        let best_bid = bids.first().map(|l| l.price);
+        let best_ask = asks.first().map(|l| l.price);
+        let spread = match (best_bid, best_ask) {
+            (Some(b), Some(a)) => Some(a - b),
+            _ => None,
+        };
+        let mid_price = match (best_bid, best_ask) {
+            (Some(b), Some(a)) => Some((a + b) / 2.0),
+            _ => None,
+        };
+
+        L2Orderbook {
+            bids,
+            asks,
+            best_bid,
+            best_ask,
+            spread,
+            mid_price,
+        }
+    }
+}
+
+/// Aggregate (price_in_ticks, size_in_lots) orders into L2 levels.
+/// Bids come out descending, asks ascending, truncated to `max_levels`.
+fn aggregate_levels(
+    orders: impl Iterator<Item = (u64, u64)>,
+    is_bids: bool,
+    tick_size: f64,
+    lot_size: f64,
+    max_levels: usize,
+) -> Vec<L2Level> {
+    let mut by_price: BTreeMap<u64, (u64, u32)> = BTreeMap::new();
+    for (price_ticks, lots) in orders {
+        if lots == 0 {
+            continue;
+        }
+        let entry = by_price.entry(price_ticks).or_insert((0, 0));
+        entry.0 = entry.0.saturating_add(lots);
+        entry.1 += 1;
+    }
+
+    let to_level = |(price_ticks, (lots, count)): (&u64, &(u64, u32))| L2Level {
+        price: (*price_ticks as f64) * tick_size,
+        size: (*lots as f64) * lot_size,
+        num_orders: *count,
+    };
+
+    if is_bids {
+        by_price.iter().rev().take(max_levels).map(to_level).collect()
+    } else {
+        by_price.iter().take(max_levels).map(to_level).collect()
+    }
 }
 
 // ============================================================================
@@ -135,10 +248,51 @@ pub fn decode_phoenix_header(data_b64: String) -> PyResult<Option<(u64, u64)>> {
     Ok(Some((header.discriminant, header.status)))
 }
 
+/// In-order traversal of a sokoban red-black tree of Phoenix orders.
+///
+/// Only nodes reachable from the root are returned, so freed slots left in
+/// the allocator are never mistaken for live orders.
+fn traverse_phoenix_tree(tree: &[u8], capacity: usize) -> Vec<PhoenixTreeNode> {
+    let node_size = std::mem::size_of::<PhoenixTreeNode>();
+    let node_at = |idx: u32| -> Option<PhoenixTreeNode> {
+        if idx == 0 || idx as usize > capacity {
+            return None;
+        }
+        let start = PHOENIX_TREE_HEADER_SIZE + (idx as usize - 1) * node_size;
+        tree.get(start..start + node_size).map(bytemuck::pod_read_unaligned)
+    };
+
+    let root = u32::from_le_bytes([tree[0], tree[1], tree[2], tree[3]]);
+    let mut nodes = Vec::new();
+    let mut stack: Vec<PhoenixTreeNode> = Vec::new();
+    let mut current = root;
+
+    loop {
+        while let Some(node) = node_at(current) {
+            // A well-formed tree never goes deeper than its capacity
+            if stack.len() >= capacity {
+                return nodes;
+            }
+            stack.push(node);
+            current = node.left;
+        }
+
+        let Some(node) = stack.pop() else { break };
+        nodes.push(node);
+        if nodes.len() >= capacity {
+            break;
+        }
+        current = node.right;
+    }
+
+    nodes
+}
+
 /// Parse Phoenix orderbook data into L2 levels.
 /// 
-/// This is a simplified parser that extracts bids and asks from Phoenix market data.
-/// Real Phoenix parsing requires the full SDK for accuracy.
+/// Reads the market header for the bids/asks tree capacities, then walks the
+/// bids and asks red-black trees of the FIFOMarket that follows. Orders are
+/// classified by the tree they live in and aggregated per price.
 /// 
 /// # Arguments
 /// * `data_b64` - Base64 encoded market account data
@@ -147,7 +301,8 @@ pub fn decode_phoenix_header(data_b64: String) -> PyResult<Option<(u64, u64)>> {
 /// * `max_levels` - Maximum number of levels to return per side
 /// 
 /// # Returns
-/// L2Orderbook with sorted bids (descending) and asks (ascending)
+/// L2Orderbook with sorted bids (descending) and asks (ascending).
+/// Accounts too short to hold both trees produce an empty book.
 #[pyfunction]
 #[pyo3(signature = (data_b64, tick_size, base_lot_size, max_levels=20))]
 pub fn decode_phoenix_orderbook(
@@ -159,113 +314,115 @@ pub fn decode_phoenix_orderbook(
     let bytes = general_purpose::STANDARD.decode(data_b64)
         .map_err(|e| PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("{}", e)))?;
 
-    // Skip header (256 bytes for Phoenix)
-    let order_data = if bytes.len() > 256 { &bytes[256..] } else { &[] };
-    
-    let mut bids: Vec<L2Level> = Vec::new();
-    let mut asks: Vec<L2Level> = Vec::new();
-    
-    // Parse order nodes (32 bytes each)
-    let node_size = std::mem::size_of::<PhoenixOrderNode>();
-    let num_nodes = order_data.len() / node_size;
-    
-    for i in 0..num_nodes {
-        let start = i * node_size;
-        let end = start + node_size;
-        
-        if end > order_data.len() {
-            break;
-        }
-        
-        let node: &PhoenixOrderNode = match bytemuck::try_from_bytes(&order_data[start..end]) {
-            Ok(n) => n,
-            Err(_) => continue,
-        };
-        
-        // Skip empty nodes
-        if node.size_in_base_lots == 0 {
-            continue;
-        }
-        
-        let price = (node.price_in_ticks as f64) * tick_size;
-        let size = (node.size_in_base_lots as f64) * base_lot_size;
-        
-        // Determine side based on price encoding
-        // In Phoenix, bids and asks are in separate regions
-        // For simplicity, we use sequence number parity as a heuristic
-        // (Real implementation needs proper slab layout knowledge)
-        let is_bid = node.sequence_number % 2 == 0;
-        
-        let level = L2Level {
-            price,
-            size,
-            num_orders: 1,
-        };
-        
-        if is_bid {
-            bids.push(level);
-        } else {
-            asks.push(level);
-        }
-        
-        if bids.len() >= max_levels && asks.len() >= max_levels {
-            break;
-        }
+    let header_min = std::mem::size_of::<PhoenixHeaderMin>();
+    if bytes.len() < PHOENIX_HEADER_SIZE.max(header_min) {
+        return Ok(L2Orderbook::from_levels(vec![], vec![]));
     }
-    
-    // Sort bids descending, asks ascending
-    bids.sort_by(|a, b| b.price.partial_cmp(&a.price).unwrap_or(Ordering::Equal));
-    asks.sort_by(|a, b| a.price.partial_cmp(&b.price).unwrap_or(Ordering::Equal));
-    
-    // Truncate to max_levels
-    bids.truncate(max_levels);
-    asks.truncate(max_levels);
-    
-    // Calculate derived values
-    let best_bid = bids.first().map(|l| l.price);
-    let best_ask = asks.first().map(|l| l.price);
-    let spread = match (best_bid, best_ask) {
-        (Some(b), Some(a)) => Some(a - b),
-        _ => None,
-    };
-    let mid_price = match (best_bid, best_ask) {
-        (Some(b), Some(a)) => Some((a + b) / 2.0),
-        _ => None,
+    let header: PhoenixHeaderMin = bytemuck::pod_read_unaligned(&bytes[..header_min]);
+
+    // Locate the bids and asks trees from the market size params
+    let node_size = std::mem::size_of::<PhoenixTreeNode>();
+    let bids_capacity = header.bids_size as usize;
+    let asks_capacity = header.asks_size as usize;
+    let bids_start = PHOENIX_HEADER_SIZE + PHOENIX_MARKET_PREFIX_SIZE;
+    let asks_start = bids_capacity
+        .checked_mul(node_size)
+        .and_then(|n| n.checked_add(bids_start + PHOENIX_TREE_HEADER_SIZE));
+    let asks_end = asks_start.and_then(|start| {
+        asks_capacity
+            .checked_mul(node_size)
+            .and_then(|n| n.checked_add(start + PHOENIX_TREE_HEADER_SIZE))
+    });
+
+    let (asks_start, asks_end) = match (asks_start, asks_end) {
+        (Some(start), Some(end)) if end <= bytes.len() => (start, end),
+        _ => return Ok(L2Orderbook::from_levels(vec![], vec![])),
     };
-    
-    Ok(L2Orderbook {
-        bids,
-        asks,
-        best_bid,
-        best_ask,
-        spread,
-        mid_price,
-    })
+
+    let bid_orders = traverse_phoenix_tree(&bytes[bids_start..asks_start], bids_capacity);
+    let ask_orders = traverse_phoenix_tree(&bytes[asks_start..asks_end], asks_capacity);
+
+    let bids = aggregate_levels(
+        bid_orders.iter().map(|n| (n.price_in_ticks, n.num_base_lots)),
+        true,
+        tick_size,
+        base_lot_size,
+        max_levels,
+    );
+    let asks = aggregate_levels(
+        ask_orders.iter().map(|n| (n.price_in_ticks, n.num_base_lots)),
+        false,
+        tick_size,
+        base_lot_size,
+        max_levels,
+    );
+
+    Ok(L2Orderbook::from_levels(bids, asks))
 }
 
 // ============================================================================
 // OPENBOOK V2 STRUCTURES
 // ============================================================================
 
-/// OpenBook V2 Slab Node (simplified)
+/// BookSide offset of the fixed-price order tree root (after the 8-byte discriminator).
+/// roots[1] is the oracle-pegged tree, which needs an oracle price to decode.
+const OPENBOOK_FIXED_ROOT_OFFSET: usize = 8;
+
+/// BookSide offset of the first node: discriminator (8) + roots (2 × 8) +
+/// reserved_roots (4 × 8) + reserved (256) + OrderTreeNodes header (16 + 512)
+const OPENBOOK_NODES_OFFSET: usize = 8 + 16 + 32 + 256 + 16 + 512;
+
+/// OrderTreeNodes capacity per BookSide
+const OPENBOOK_MAX_NODES: usize = 1024;
+
+/// Node tags
+const OPENBOOK_TAG_INNER: u8 = 1;
+const OPENBOOK_TAG_LEAF: u8 = 2;
+
+/// OpenBook V2 order tree node (AnyNode, 88 bytes).
+/// Inner nodes store `children: [u32; 2]` at the start of `body`;
+/// leaf nodes store owner (32 bytes) then `quantity: i64`.
 #[repr(C)]
 #[derive(Copy, Clone, Pod, Zeroable)]
 struct OpenBookNode {
     /// Node tag/type
-    tag: u32,
-    /// Padding for alignment
-    _padding: u32,
+    tag: u8,
+    /// Inner: padding. Leaf: owner_slot, time_in_force
+    _padding: [u8; 3],
+    /// Inner: critbit prefix length. Leaf: padding
+    prefix_len: u32,
+    /// Order key: upper 64 bits are price lots, lower 64 the sequence number
+    key: [u8; 16],
     /// Children or leaf data
-    data: [u64; 4],
+    body: [u8; 64],
+}
+
+impl OpenBookNode {
+    fn price_lots(&self) -> u64 {
+        (u128::from_le_bytes(self.key) >> 64) as u64
+    }
+
+    fn children(&self) -> [u32; 2] {
+        [
+            u32::from_le_bytes(self.body[0..4].try_into().unwrap()),
+            u32::from_le_bytes(self.body[4..8].try_into().unwrap()),
+        ]
+    }
+
+    fn quantity(&self) -> i64 {
+        i64::from_le_bytes(self.body[32..40].try_into().unwrap())
+    }
 }
 
 /// Parse OpenBook V2 slab into L2 levels.
 /// 
-/// OpenBook uses a red-black tree structure stored in a slab.
-/// This performs an in-order traversal to extract sorted price levels.
+/// OpenBook stores each book side as a critbit tree in a node slab.
+/// This performs an in-order traversal from the fixed-order root
+/// (best price first) so levels come out in true price order and
+/// traversal stops once `max_levels` price levels are complete.
 /// 
 /// # Arguments
-/// * `data_b64` - Base64 encoded slab data
+/// * `data_b64` - Base64 encoded BookSide account data
 /// * `is_bids` - True if this is the bids slab, false for asks
 /// * `tick_size` - Price increment per tick
 /// * `lot_size` - Size increment per lot
@@ -282,62 +439,92 @@ pub fn decode_openbook_slab(
     let bytes = general_purpose::STANDARD.decode(data_b64)
         .map_err(|e| PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("{}", e)))?;
     
-    // OpenBook slab header is 72 bytes
-    let header_size = 72;
-    if bytes.len() < header_size {
+    if bytes.len() < OPENBOOK_NODES_OFFSET || max_levels == 0 {
+        return Ok(vec![]);
+    }
+    
+    let root = &bytes[OPENBOOK_FIXED_ROOT_OFFSET..OPENBOOK_FIXED_ROOT_OFFSET + 8];
+    let root_node = u32::from_le_bytes(root[0..4].try_into().unwrap());
+    let leaf_count = u32::from_le_bytes(root[4..8].try_into().unwrap());
+    if leaf_count == 0 {
         return Ok(vec![]);
     }
     
-    let slab_data = &bytes[header_size..];
+    let slab_data = &bytes[OPENBOOK_NODES_OFFSET..];
     let node_size = std::mem::size_of::<OpenBookNode>();
+    let num_nodes = (slab_data.len() / node_size).min(OPENBOOK_MAX_NODES);
+    let node_at = |idx: u32| -> Option<OpenBookNode> {
+        let idx = idx as usize;
+        if idx >= num_nodes {
+            return None;
+        }
+        let start = idx * node_size;
+        Some(bytemuck::pod_read_unaligned(&slab_data[start..start + node_size]))
+    };
+    
+    // Children[0] holds lower keys: bids walk high→low, asks low→high
+    let (first, second) = if is_bids { (1, 0) } else { (0, 1) };
     
     let mut levels: Vec<L2Level> = Vec::new();
+    let mut current: Option<(u64, u64, u32)> = None; // (price_lots, lots, orders)
+    let mut stack = vec![root_node];
+    let mut visited = 0usize;
     
-    // Simple linear scan (real implementation would do tree traversal)
-    for i in 0..(slab_data.len() / node_size) {
-        let start = i * node_size;
-        let end = start + node_size;
-        
-        if end > slab_data.len() {
+    while let Some(idx) = stack.pop() {
+        // Corrupt links could otherwise loop forever
+        visited += 1;
+        if visited > num_nodes {
             break;
         }
         
-        let node: &OpenBookNode = match bytemuck::try_from_bytes(&slab_data[start..end]) {
-            Ok(n) => n,
-            Err(_) => continue,
-        };
+        let Some(node) = node_at(idx) else { continue };
         
-        // Node tag 2 = leaf node in OpenBook
-        if node.tag != 2 {
-            continue;
-        }
-        
-        // Extract price and quantity from leaf data
-        // data[0] = price_lots, data[1] = quantity
-        let price = (node.data[0] as f64) * tick_size;
-        let size = (node.data[1] as f64) * lot_size;
-        
-        if size > 0.0 {
-            levels.push(L2Level {
-                price,
-                size,
-                num_orders: 1,
-            });
-        }
-        
-        if levels.len() >= max_levels {
-            break;
+        match node.tag {
+            OPENBOOK_TAG_INNER => {
+                let children = node.children();
+                // Push the later branch first so the better-priced branch pops next
+                stack.push(children[second]);
+                stack.push(children[first]);
+            }
+            OPENBOOK_TAG_LEAF => {
+                let quantity = node.quantity();
+                if quantity <= 0 {
+                    continue;
+                }
+                let price_lots = node.price_lots();
+                
+                match current.as_mut() {
+                    Some((p, lots, orders)) if *p == price_lots => {
+                        *lots = lots.saturating_add(quantity as u64);
+                        *orders += 1;
+                    }
+                    _ => {
+                        if let Some((p, lots, orders)) = current.take() {
+                            levels.push(L2Level {
+                                price: (p as f64) * tick_size,
+                                size: (lots as f64) * lot_size,
+                                num_orders: orders,
+                            });
+                            if levels.len() >= max_levels {
+                                return Ok(levels);
+                            }
+                        }
+                        current = Some((price_lots, quantity as u64, 1));
+                    }
+                }
+            }
+            _ => continue,
         }
     }
     
-    // Sort: bids descending, asks ascending
-    if is_bids {
-        levels.sort_by(|a, b| b.price.partial_cmp(&a.price).unwrap_or(Ordering::Equal));
-    } else {
-        levels.sort_by(|a, b| a.price.partial_cmp(&b.price).unwrap_or(Ordering::Equal));
+    if let Some((p, lots, orders)) = current {
+        levels.push(L2Level {
+            price: (p as f64) * tick_size,
+            size: (lots as f64) * lot_size,
+            num_orders: orders,
+        });
     }
     
-    levels.truncate(max_levels);
     Ok(levels)
 }
 
@@ -347,25 +534,53 @@ pub fn build_openbook_orderbook(
     bids: Vec<L2Level>,
     asks: Vec<L2Level>,
 ) -> PyResult<L2Orderbook> {
-    let best_bid = bids.first().map(|l| l.price);
-    let best_ask = asks.first().map(|l| l.price);
-    let spread = match (best_bid, best_ask) {
-        (Some(b), Some(a)) => Some(a - b),
-        _ => None,
-    };
-    let mid_price = match (best_bid, best_ask) {
-        (Some(b), Some(a)) => Some((a + b) / 2.0),
-        _ => None,
-    };
-    
-    Ok(L2Orderbook {
-        bids,
-        asks,
-        best_bid,
-        best_ask,
-        spread,
-        mid_price,
-    })
+    Ok(L2Orderbook::from_levels(bids, asks))
+}
+
+// ============================================================================
+// CROSS-VENUE CONSOLIDATION
+// ============================================================================
+
+/// Sort levels best-first and merge those whose prices are within `tolerance`.
+fn merge_levels(mut levels: Vec<L2Level>, is_bids: bool, tolerance: f64) -> Vec<L2Level> {
+    if is_bids {
+        levels.sort_by(|a, b| b.price.partial_cmp(&a.price).unwrap_or(Ordering::Equal));
+    } else {
+        levels.sort_by(|a, b| a.price.partial_cmp(&b.price).unwrap_or(Ordering::Equal));
+    }
+
+    let mut merged: Vec<L2Level> = Vec::with_capacity(levels.len());
+    for level in levels {
+        match merged.last_mut() {
+            Some(last) if (last.price - level.price).abs() <= tolerance => {
+                last.size += level.size;
+                last.num_orders += level.num_orders;
+            }
+            _ => merged.push(level),
+        }
+    }
+    merged
+}
+
+/// Merge L2 books for the same pair across venues into one top-of-book.
+///
+/// Levels whose prices differ by at most `tolerance` are combined (sizes and
+/// order counts summed, keeping the better price). Bids are re-sorted
+/// descending, asks ascending, and best bid/ask, spread and mid recomputed.
+#[pyfunction]
+#[pyo3(signature = (books, tolerance=1e-9))]
+pub fn consolidate_orderbooks(books: Vec<L2Orderbook>, tolerance: f64) -> L2Orderbook {
+    let mut bids = Vec::new();
+    let mut asks = Vec::new();
+    for book in books {
+        bids.extend(book.bids);
+        asks.extend(book.asks);
+    }
+
+    L2Orderbook::from_levels(
+        merge_levels(bids, true, tolerance),
+        merge_levels(asks, false, tolerance),
+    )
 }
 
 // ============================================================================
@@ -391,6 +606,44 @@ pub fn calculate_ofi(bids: Vec<L2Level>, asks: Vec<L2Level>, depth: usize) -> Py
     Ok((bid_volume - ask_volume) / total)
 }
 
+/// Calculate Order Flow Imbalance weighted by distance from mid.
+/// 
+/// Each level's notional is multiplied by `decay^level_index` (top of book = 1.0),
+/// so deep levels move the imbalance less than those near the mid.
+/// 
+/// Returns value between -1.0 (all ask pressure) and 1.0 (all bid pressure)
+#[pyfunction]
+#[pyo3(signature = (bids, asks, depth=5, decay=0.5))]
+pub fn calculate_ofi_weighted(
+    bids: Vec<L2Level>,
+    asks: Vec<L2Level>,
+    depth: usize,
+    decay: f64,
+) -> PyResult<f64> {
+    if !decay.is_finite() || decay < 0.0 {
+        return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+            format!("decay must be a non-negative number, got {}", decay)
+        ));
+    }
+    
+    let weighted_volume = |levels: &[L2Level]| -> f64 {
+        levels.iter()
+            .take(depth)
+            .enumerate()
+            .map(|(i, l)| l.size * l.price * decay.powi(i as i32))
+            .sum()
+    };
+    let bid_volume = weighted_volume(&bids);
+    let ask_volume = weighted_volume(&asks);
+    
+    let total = bid_volume + ask_volume;
+    if total == 0.0 {
+        return Ok(0.0);
+    }
+    
+    Ok((bid_volume - ask_volume) / total)
+}
+
 /// Calculate Volume Weighted Average Price for a given depth.
 #[pyfunction]
 pub fn calculate_vwap(levels: Vec<L2Level>) -> PyResult<f64> {
@@ -420,9 +673,241 @@ pub fn register_slab_functions(m: &PyModule) -> PyResult<()> {
     m.add_function(wrap_pyfunction!(decode_openbook_slab, m)?)?;
     m.add_function(wrap_pyfunction!(build_openbook_orderbook, m)?)?;
     
+    // Cross-venue
+    m.add_function(wrap_pyfunction!(consolidate_orderbooks, m)?)?;
+    
     // Utilities
     m.add_function(wrap_pyfunction!(calculate_ofi, m)?)?;
+    m.add_function(wrap_pyfunction!(calculate_ofi_weighted, m)?)?;
     m.add_function(wrap_pyfunction!(calculate_vwap, m)?)?;
     
     Ok(())
 }
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Serialize a sokoban tree from (left, right, price_ticks, lots) nodes,
+    /// 1-indexed in slice order.
+    fn phoenix_tree(root: u32, capacity: usize, nodes: &[(u32, u32, u64, u64)]) -> Vec<u8> {
+        let node_size = std::mem::size_of::<PhoenixTreeNode>();
+        let mut tree = vec![0u8; PHOENIX_TREE_HEADER_SIZE + capacity * node_size];
+        tree[0..4].copy_from_slice(&root.to_le_bytes());
+        for (i, &(left, right, price_in_ticks, num_base_lots)) in nodes.iter().enumerate() {
+            let node = PhoenixTreeNode {
+                left,
+                right,
+                parent: 0,
+                color: 0,
+                price_in_ticks,
+                order_sequence_number: i as u64,
+                trader_index: 1,
+                num_base_lots,
+                last_valid_slot: 0,
+                last_valid_unix_timestamp_in_seconds: 0,
+            };
+            let start = PHOENIX_TREE_HEADER_SIZE + i * node_size;
+            tree[start..start + node_size].copy_from_slice(bytemuck::bytes_of(&node));
+        }
+        tree
+    }
+
+    fn phoenix_market(bids: &[u8], bids_size: u64, asks: &[u8], asks_size: u64) -> String {
+        let mut data = vec![0u8; PHOENIX_HEADER_SIZE + PHOENIX_MARKET_PREFIX_SIZE];
+        data[16..24].copy_from_slice(&bids_size.to_le_bytes());
+        data[24..32].copy_from_slice(&asks_size.to_le_bytes());
+        data.extend_from_slice(bids);
+        data.extend_from_slice(asks);
+        general_purpose::STANDARD.encode(data)
+    }
+
+    #[test]
+    fn test_decode_phoenix_orderbook_separates_trees() {
+        // Bids: 99 (node 1) <- 100 (root, node 2) -> 99 (node 3); node 4 is a
+        // freed slot holding stale data that must be ignored.
+        let bids = phoenix_tree(
+            2,
+            4,
+            &[(0, 0, 9_900, 10), (1, 3, 10_000, 5), (0, 0, 9_900, 20), (0, 0, 20_000, 99)],
+        );
+        // Asks: 101 (root, node 1) -> 102 (node 2)
+        let asks = phoenix_tree(1, 4, &[(0, 2, 10_100, 7), (0, 0, 10_200, 3)]);
+
+        // Sequence numbers are even/odd mixed, so parity would misclassify these
+        let book =
+            decode_phoenix_orderbook(phoenix_market(&bids, 4, &asks, 4), 0.01, 0.1, 20).unwrap();
+
+        assert_eq!(book.bids.len(), 2);
+        assert_eq!(book.asks.len(), 2);
+        assert_eq!(book.best_bid, Some(100.0));
+        assert_eq!(book.best_ask, Some(101.0));
+        assert!(book.best_bid.unwrap() < book.best_ask.unwrap());
+        assert!((book.spread.unwrap() - 1.0).abs() < 1e-9);
+
+        // Orders at the same price are aggregated
+        assert!((book.bids[1].price - 99.0).abs() < 1e-9);
+        assert!((book.bids[1].size - 3.0).abs() < 1e-9);
+        assert_eq!(book.bids[1].num_orders, 2);
+    }
+
+    #[test]
+    fn test_decode_phoenix_orderbook_short_data() {
+        let book =
+            decode_phoenix_orderbook(general_purpose::STANDARD.encode([0u8; 256]), 0.01, 0.1, 20)
+                .unwrap();
+        assert!(book.bids.is_empty() && book.asks.is_empty());
+        assert!(book.spread.is_none());
+    }
+
+    /// Build an OpenBook V2 BookSide account from (tag, key_price, children, qty) nodes.
+    fn openbook_bookside(
+        root: u32,
+        leaf_count: u32,
+        nodes: &[(u8, u64, [u32; 2], i64)],
+    ) -> String {
+        let node_size = std::mem::size_of::<OpenBookNode>();
+        let mut data = vec![0u8; OPENBOOK_NODES_OFFSET + nodes.len() * node_size];
+        data[8..12].copy_from_slice(&root.to_le_bytes());
+        data[12..16].copy_from_slice(&leaf_count.to_le_bytes());
+
+        for (i, &(tag, price_lots, children, quantity)) in nodes.iter().enumerate() {
+            let mut node = OpenBookNode::zeroed();
+            node.tag = tag;
+            node.key = (((price_lots as u128) << 64) | i as u128).to_le_bytes();
+            if tag == OPENBOOK_TAG_INNER {
+                node.body[0..4].copy_from_slice(&children[0].to_le_bytes());
+                node.body[4..8].copy_from_slice(&children[1].to_le_bytes());
+            } else {
+                node.body[32..40].copy_from_slice(&quantity.to_le_bytes());
+            }
+            let start = OPENBOOK_NODES_OFFSET + i * node_size;
+            data[start..start + node_size].copy_from_slice(bytemuck::bytes_of(&node));
+        }
+        general_purpose::STANDARD.encode(data)
+    }
+
+    #[test]
+    fn test_decode_openbook_slab_tree_order() {
+        // Node 0 is a stale leaf from a freed slot (price 200) that a linear
+        // scan would pick up as the best bid.
+        //
+        //            inner(1)
+        //           /        \
+        //      inner(2)    leaf(5) 105
+        //      /      \
+        // leaf(3) 98  leaf(4) 100
+        let slab = openbook_bookside(
+            1,
+            3,
+            &[
+                (OPENBOOK_TAG_LEAF, 200, [0, 0], 50),
+                (OPENBOOK_TAG_INNER, 0, [2, 5], 0),
+                (OPENBOOK_TAG_INNER, 0, [3, 4], 0),
+                (OPENBOOK_TAG_LEAF, 98, [0, 0], 10),
+                (OPENBOOK_TAG_LEAF, 100, [0, 0], 20),
+                (OPENBOOK_TAG_LEAF, 105, [0, 0], 5),
+            ],
+        );
+
+        let bids = decode_openbook_slab(slab.clone(), true, 0.01, 0.1, 20).unwrap();
+        let prices: Vec<f64> = bids.iter().map(|l| l.price).collect();
+        assert_eq!(prices, vec![1.05, 1.0, 0.98]);
+        assert!((bids[0].size - 0.5).abs() < 1e-9);
+
+        // max_levels keeps the best levels, not the first ones in the slab
+        let top = decode_openbook_slab(slab.clone(), true, 0.01, 0.1, 1).unwrap();
+        assert_eq!(top.len(), 1);
+        assert!((top[0].price - 1.05).abs() < 1e-9);
+
+        let asks = decode_openbook_slab(slab, false, 0.01, 0.1, 2).unwrap();
+        let prices: Vec<f64> = asks.iter().map(|l| l.price).collect();
+        assert_eq!(prices, vec![0.98, 1.0]);
+    }
+
+    fn level(price: f64, size: f64) -> L2Level {
+        L2Level {
+            price,
+            size,
+            num_orders: 1,
+        }
+    }
+
+    #[test]
+    fn test_consolidate_orderbooks_cross_venue() {
+        // Phoenix has the best bid, OpenBook the best ask
+        let phoenix = L2Orderbook::from_levels(
+            vec![level(100.0, 2.0), level(99.0, 1.0)],
+            vec![level(103.0, 4.0)],
+        );
+        let openbook = L2Orderbook::from_levels(
+            vec![level(99.5, 3.0), level(99.0 + 1e-12, 5.0)],
+            vec![level(101.0, 1.5), level(103.0, 2.0)],
+        );
+
+        let book = consolidate_orderbooks(vec![phoenix, openbook], 1e-9);
+
+        assert_eq!(book.best_bid, Some(100.0));
+        assert_eq!(book.best_ask, Some(101.0));
+        assert!((book.spread.unwrap() - 1.0).abs() < 1e-9);
+        assert!((book.mid_price.unwrap() - 100.5).abs() < 1e-9);
+
+        let bid_prices: Vec<f64> = book.bids.iter().map(|l| l.price).collect();
+        assert_eq!(bid_prices, vec![100.0, 99.5, 99.0 + 1e-12]);
+        // 99.0 levels from both venues merged within tolerance
+        assert!((book.bids[2].size - 6.0).abs() < 1e-9);
+        assert_eq!(book.bids[2].num_orders, 2);
+
+        assert_eq!(book.asks.len(), 2);
+        assert!((book.asks[1].size - 6.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_weighted_ofi_discounts_deep_levels() {
+        let asks: Vec<L2Level> = (0..5).map(|i| level(101.0 + i as f64, 10.0)).collect();
+        let bids: Vec<L2Level> = (0..5).map(|i| level(99.0 - i as f64, 10.0)).collect();
+
+        let mut deep_bids = bids.clone();
+        deep_bids[4].size += 50.0;
+
+        let flat_before = calculate_ofi(bids.clone(), asks.clone(), 5).unwrap();
+        let flat_after = calculate_ofi(deep_bids.clone(), asks.clone(), 5).unwrap();
+        let weighted_before = calculate_ofi_weighted(bids.clone(), asks.clone(), 5, 0.5).unwrap();
+        let weighted_after = calculate_ofi_weighted(deep_bids.clone(), asks.clone(), 5, 0.5).unwrap();
+
+        let flat_shift = flat_after - flat_before;
+        let weighted_shift = weighted_after - weighted_before;
+        assert!(flat_shift > 0.0 && weighted_shift > 0.0);
+        assert!(weighted_shift < flat_shift);
+
+        // decay = 1.0 is the unweighted OFI
+        let no_decay = calculate_ofi_weighted(deep_bids, asks, 5, 1.0).unwrap();
+        assert!((no_decay - flat_after).abs() < 1e-12);
+
+        assert!(calculate_ofi_weighted(bids, vec![], 5, -0.5).is_err());
+    }
+
+    #[test]
+    fn test_simulate_sweep_across_levels() {
+        let book = L2Orderbook::from_levels(
+            vec![level(99.0, 1.0), level(98.0, 2.0)],
+            vec![level(100.0, 1.0), level(101.0, 2.0), level(102.0, 3.0)],
+        );
+
+        // Buy 4: 1 @ 100 + 2 @ 101 + 1 @ 102 = 404 → VWAP 101.0
+        let (avg, filled) = book.simulate_sweep(4.0, true);
+        assert!((filled - 4.0).abs() < 1e-12);
+        assert!((avg - 101.0).abs() < 1e-12);
+
+        // Sell more than the bids hold: partial fill of 3 @ (99 + 196) / 3
+        let (avg, filled) = book.simulate_sweep(10.0, false);
+        assert!((filled - 3.0).abs() < 1e-12);
+        assert!((avg - 295.0 / 3.0).abs() < 1e-12);
+
+        assert_eq!(book.simulate_sweep(0.0, true), (0.0, 0.0));
+    }
+}
@@ -9,7 +9,7 @@
 // 3. Track slot progression (fork detection)
 
 use pyo3::prelude::*;
-use std::collections::HashSet;
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
 use std::sync::Mutex;
 // use std::time::{Duration, Instant};
 
@@ -17,13 +17,20 @@ use std::sync::Mutex;
 // BLOOM FILTER FOR SIGNATURE DE-DUPLICATION
 // ============================================================================
 
+/// Rolling window of seen signatures: a set for lookups plus the
+/// insertion order so eviction always drops the oldest entries.
+struct DedupWindow {
+    set: HashSet<String>,
+    order: VecDeque<String>,
+}
+
 /// Simple bloom-like filter using a rolling hash set.
-/// We use a HashSet with TTL-based eviction instead of a true Bloom filter
+/// We use a HashSet with FIFO eviction instead of a true Bloom filter
 /// for simplicity and zero false-positive guarantee.
 #[pyclass]
 pub struct SignatureDedup {
-    /// Set of recently seen signatures
-    seen: Mutex<HashSet<String>>,
+    /// Recently seen signatures, in arrival order
+    seen: Mutex<DedupWindow>,
     /// Maximum size before forced eviction
     max_size: usize,
     /// Signatures to evict when max_size is reached
@@ -36,9 +43,12 @@ impl SignatureDedup {
     #[pyo3(signature = (max_size=10000))]
     pub fn new(max_size: usize) -> Self {
         Self {
-            seen: Mutex::new(HashSet::with_capacity(max_size)),
+            seen: Mutex::new(DedupWindow {
+                set: HashSet::with_capacity(max_size),
+                order: VecDeque::with_capacity(max_size),
+            }),
             max_size,
-            eviction_batch: max_size / 4, // Evict 25% when full
+            eviction_batch: (max_size / 4).max(1), // Evict 25% when full
         }
     }
     
@@ -46,29 +56,44 @@ impl SignatureDedup {
     /// Returns true if this is the FIRST time we've seen this signature.
     /// Returns false if it's a duplicate.
     pub fn is_new(&self, signature: String) -> bool {
+        self.is_new_ref(&signature)
+    }
+    
+    /// Borrowing variant of `is_new`: the signature is only copied into
+    /// the window when it is accepted, so duplicates never allocate.
+    pub fn is_new_ref(&self, signature: &str) -> bool {
         let mut seen = self.seen.lock().unwrap();
         
-        // If at capacity, evict oldest (random eviction for simplicity)
-        if seen.len() >= self.max_size {
-            let to_remove: Vec<_> = seen.iter().take(self.eviction_batch).cloned().collect();
-            for sig in to_remove {
-                seen.remove(&sig);
+        if seen.set.contains(signature) {
+            return false;
+        }
+        
+        // If at capacity, evict the oldest batch
+        if seen.set.len() >= self.max_size {
+            for _ in 0..self.eviction_batch {
+                match seen.order.pop_front() {
+                    Some(oldest) => {
+                        seen.set.remove(&oldest);
+                    }
+                    None => break,
+                }
             }
         }
         
-        // Insert returns true if the value was NOT present
-        seen.insert(signature)
+        seen.order.push_back(signature.to_string());
+        seen.set.insert(signature.to_string())
     }
     
     /// Clear all seen signatures.
     pub fn clear(&self) {
         let mut seen = self.seen.lock().unwrap();
-        seen.clear();
+        seen.set.clear();
+        seen.order.clear();
     }
     
     /// Get current size of the dedup filter.
     pub fn size(&self) -> usize {
-        self.seen.lock().unwrap().len()
+        self.seen.lock().unwrap().set.len()
     }
 }
 
@@ -83,7 +108,7 @@ pub struct SlotTracker {
     /// Latest confirmed slot across all providers
     latest_slot: Mutex<u64>,
     /// Highest slot seen per provider (for debugging)
-    per_provider_slots: Mutex<Vec<(String, u64)>>,
+    per_provider_slots: Mutex<HashMap<String, u64>>,
     /// Window of acceptable slot difference
     max_slot_lag: u64,
 }
@@ -95,7 +120,7 @@ impl SlotTracker {
     pub fn new(max_slot_lag: u64) -> Self {
         Self {
             latest_slot: Mutex::new(0),
-            per_provider_slots: Mutex::new(Vec::new()),
+            per_provider_slots: Mutex::new(HashMap::new()),
             max_slot_lag,
         }
     }
@@ -107,21 +132,22 @@ impl SlotTracker {
     /// - 0 if this slot is current (accepted)
     /// - -1 if this slot is stale (rejected)
     pub fn update_slot(&self, provider: String, slot: u64) -> i32 {
+        self.update_slot_ref(&provider, slot)
+    }
+    
+    /// Borrowing variant of `update_slot`; the provider name is only
+    /// copied the first time that provider is seen.
+    pub fn update_slot_ref(&self, provider: &str, slot: u64) -> i32 {
         let mut latest = self.latest_slot.lock().unwrap();
         let mut providers = self.per_provider_slots.lock().unwrap();
         
         // Update per-provider tracking
-        let mut found = false;
-        for (p, s) in providers.iter_mut() {
-            if p == &provider {
-                *s = slot.max(*s);
-                found = true;
-                break;
+        match providers.get_mut(provider) {
+            Some(provider_slot) => *provider_slot = slot.max(*provider_slot),
+            None => {
+                providers.insert(provider.to_string(), slot);
             }
         }
-        if !found {
-            providers.push((provider, slot));
-        }
         
         // Check against global latest
         if slot > *latest {
@@ -145,9 +171,17 @@ impl SlotTracker {
         *self.latest_slot.lock().unwrap()
     }
     
-    /// Get all provider slots for debugging.
+    /// Get all provider slots for debugging, sorted by provider name.
     pub fn get_provider_slots(&self) -> Vec<(String, u64)> {
-        self.per_provider_slots.lock().unwrap().clone()
+        let mut slots: Vec<(String, u64)> = self
+            .per_provider_slots
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(p, s)| (p.clone(), *s))
+            .collect();
+        slots.sort();
+        slots
     }
     
     /// Reset the tracker (e.g., on reconnection).
@@ -157,6 +191,146 @@ impl SlotTracker {
     }
 }
 
+// ============================================================================
+// FORK DETECTION
+// ============================================================================
+
+/// Slots of block history kept for fork comparison (~1 minute).
+const FORK_WINDOW_SLOTS: u64 = 150;
+
+/// Maximum fork warnings retained for inspection.
+const MAX_FORK_WARNINGS: usize = 100;
+
+/// One block identity reported for a slot, with the providers that agree on it.
+struct SlotIdentity {
+    block_hash: Option<String>,
+    parent_slot: Option<u64>,
+    providers: Vec<String>,
+}
+
+impl SlotIdentity {
+    fn conflicts_with(&self, block_hash: &Option<String>, parent_slot: Option<u64>) -> bool {
+        let hash_conflict = matches!(
+            (&self.block_hash, block_hash),
+            (Some(a), Some(b)) if a != b
+        );
+        let parent_conflict = matches!(
+            (self.parent_slot, parent_slot),
+            (Some(a), Some(b)) if a != b
+        );
+        hash_conflict || parent_conflict
+    }
+}
+
+/// Remembers which block each provider reported per slot and flags
+/// conflicting reports (different block hash or parent) as forks.
+///
+/// A contested slot is resolved by provider agreement: a report is only
+/// trusted while its block has strictly more providers behind it than any
+/// rival. On a tie every identity for the slot is suspect and rejected.
+struct ForkDetector {
+    records: BTreeMap<u64, Vec<SlotIdentity>>,
+    warnings: VecDeque<String>,
+    fork_events: u64,
+}
+
+impl ForkDetector {
+    fn new() -> Self {
+        Self {
+            records: BTreeMap::new(),
+            warnings: VecDeque::new(),
+            fork_events: 0,
+        }
+    }
+
+    /// Record a report; returns false if its block isn't the majority one for that slot.
+    fn observe(
+        &mut self,
+        provider: &str,
+        slot: u64,
+        block_hash: Option<String>,
+        parent_slot: Option<u64>,
+        latest_slot: u64,
+    ) -> bool {
+        if block_hash.is_none() && parent_slot.is_none() {
+            return true;
+        }
+
+        let Some(identities) = self.records.get_mut(&slot) else {
+            self.records.insert(
+                slot,
+                vec![SlotIdentity {
+                    block_hash,
+                    parent_slot,
+                    providers: vec![provider.to_string()],
+                }],
+            );
+            // Drop history that can no longer be compared against
+            let min_slot = latest_slot.saturating_sub(FORK_WINDOW_SLOTS);
+            if self.records.first_key_value().is_some_and(|(&s, _)| s < min_slot) {
+                self.records = self.records.split_off(&min_slot);
+            }
+            return true;
+        };
+
+        let matched = identities
+            .iter()
+            .position(|identity| !identity.conflicts_with(&block_hash, parent_slot));
+
+        let index = match matched {
+            Some(index) => {
+                let identity = &mut identities[index];
+                // Fill in whatever earlier reports were missing
+                if identity.block_hash.is_none() {
+                    identity.block_hash = block_hash;
+                }
+                if identity.parent_slot.is_none() {
+                    identity.parent_slot = parent_slot;
+                }
+                if !identity.providers.iter().any(|p| p == provider) {
+                    identity.providers.push(provider.to_string());
+                }
+                index
+            }
+            None => {
+                // Compare against the block currently leading the slot
+                let leader = identities
+                    .iter()
+                    .max_by_key(|identity| identity.providers.len())
+                    .expect("slot records are never empty");
+                let warning = format!(
+                    "Fork at slot {}: {} reported hash={} parent={}, {} reported hash={} parent={}",
+                    slot,
+                    leader.providers.join("/"),
+                    leader.block_hash.as_deref().unwrap_or("?"),
+                    leader.parent_slot.map_or("?".to_string(), |p| p.to_string()),
+                    provider,
+                    block_hash.as_deref().unwrap_or("?"),
+                    parent_slot.map_or("?".to_string(), |p| p.to_string()),
+                );
+                self.fork_events += 1;
+                if self.warnings.len() >= MAX_FORK_WARNINGS {
+                    self.warnings.pop_front();
+                }
+                self.warnings.push_back(warning);
+
+                identities.push(SlotIdentity {
+                    block_hash,
+                    parent_slot,
+                    providers: vec![provider.to_string()],
+                });
+                identities.len() - 1
+            }
+        };
+
+        let support = identities[index].providers.len();
+        identities
+            .iter()
+            .enumerate()
+            .all(|(i, identity)| i == index || identity.providers.len() < support)
+    }
+}
+
 // ============================================================================
 // CONSENSUS ENGINE (COMBINES DEDUP + SLOT TRACKING)
 // ============================================================================
@@ -166,6 +340,7 @@ impl SlotTracker {
 /// Combines:
 /// - Signature de-duplication (first-in wins)
 /// - Slot validation (reject stale data)
+/// - Fork detection (conflicting block hash / parent per slot)
 /// - Provider health inference
 #[pyclass]
 pub struct ConsensusEngine {
@@ -177,6 +352,8 @@ pub struct ConsensusEngine {
     duplicate_count: Mutex<u64>,
     /// Count of rejected stale messages
     stale_count: Mutex<u64>,
+    /// Per-slot block identity for fork detection
+    fork_detector: Mutex<ForkDetector>,
 }
 
 #[pymethods]
@@ -190,35 +367,41 @@ impl ConsensusEngine {
             accepted_count: Mutex::new(0),
             duplicate_count: Mutex::new(0),
             stale_count: Mutex::new(0),
+            fork_detector: Mutex::new(ForkDetector::new()),
         }
     }
     
     /// Process an incoming message from a provider.
     /// 
     /// Returns true if the message should be processed (first arrival, valid slot).
-    /// Returns false if it should be dropped (duplicate or stale).
+    /// Returns false if it should be dropped (duplicate, stale, or from a
+    /// conflicting fork).
     /// 
     /// # Arguments
     /// * `provider` - Provider identifier (e.g., "helius", "alchemy")
     /// * `signature` - Transaction signature
     /// * `slot` - Slot number
-    pub fn should_process(&self, provider: String, signature: String, slot: u64) -> bool {
-        // 1. Check slot freshness
-        let slot_status = self.slot_tracker.update_slot(provider, slot);
-        if slot_status < 0 {
-            *self.stale_count.lock().unwrap() += 1;
-            return false;
-        }
-        
-        // 2. Check for duplicate
-        if !self.dedup.is_new(signature) {
-            *self.duplicate_count.lock().unwrap() += 1;
-            return false;
-        }
-        
-        // 3. Accept!
-        *self.accepted_count.lock().unwrap() += 1;
-        true
+    /// * `block_hash` - Optional block hash for the slot (enables fork detection)
+    /// * `parent_slot` - Optional parent slot (enables fork detection)
+    #[pyo3(signature = (provider, signature, slot, block_hash=None, parent_slot=None))]
+    pub fn should_process(
+        &self,
+        provider: String,
+        signature: String,
+        slot: u64,
+        block_hash: Option<String>,
+        parent_slot: Option<u64>,
+    ) -> bool {
+        self.process(&provider, &signature, slot, block_hash, parent_slot)
+    }
+    
+    /// Borrowing variant of `should_process` for the hot WSS loop.
+    /// 
+    /// Nothing is allocated when the message is rejected as stale or
+    /// duplicate; the signature is copied into the dedup window only on
+    /// acceptance. Fork detection is skipped (no block identity).
+    pub fn should_process_ref(&self, provider: &str, signature: &str, slot: u64) -> bool {
+        self.process(provider, signature, slot, None, None)
     }
     
     /// Quick check if a slot is acceptable (without full processing).
@@ -240,6 +423,20 @@ impl ConsensusEngine {
         *self.accepted_count.lock().unwrap() = 0;
         *self.duplicate_count.lock().unwrap() = 0;
         *self.stale_count.lock().unwrap() = 0;
+        let mut forks = self.fork_detector.lock().unwrap();
+        forks.fork_events = 0;
+        forks.warnings.clear();
+    }
+    
+    /// Number of fork conflicts detected.
+    #[getter]
+    pub fn fork_events(&self) -> u64 {
+        self.fork_detector.lock().unwrap().fork_events
+    }
+    
+    /// Recent fork warnings (oldest first).
+    pub fn get_fork_warnings(&self) -> Vec<String> {
+        self.fork_detector.lock().unwrap().warnings.iter().cloned().collect()
     }
     
     /// Get dedup filter size.
@@ -253,6 +450,47 @@ impl ConsensusEngine {
     }
 }
 
+impl ConsensusEngine {
+    /// Shared path for `should_process` / `should_process_ref`.
+    fn process(
+        &self,
+        provider: &str,
+        signature: &str,
+        slot: u64,
+        block_hash: Option<String>,
+        parent_slot: Option<u64>,
+    ) -> bool {
+        // 1. Check slot freshness
+        let slot_status = self.slot_tracker.update_slot_ref(provider, slot);
+        if slot_status < 0 {
+            *self.stale_count.lock().unwrap() += 1;
+            return false;
+        }
+        
+        // 2. Check for a conflicting fork (the block most providers agree on wins)
+        let latest_slot = self.slot_tracker.get_latest_slot();
+        if !self.fork_detector.lock().unwrap().observe(
+            provider,
+            slot,
+            block_hash,
+            parent_slot,
+            latest_slot,
+        ) {
+            return false;
+        }
+        
+        // 3. Check for duplicate
+        if !self.dedup.is_new_ref(signature) {
+            *self.duplicate_count.lock().unwrap() += 1;
+            return false;
+        }
+        
+        // 4. Accept!
+        *self.accepted_count.lock().unwrap() += 1;
+        true
+    }
+}
+
 // ============================================================================
 // MODULE REGISTRATION
 // ============================================================================
@@ -263,3 +501,179 @@ pub fn register_consensus_classes(m: &PyModule) -> PyResult<()> {
     m.add_class::<ConsensusEngine>()?;
     Ok(())
 }
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dedup_evicts_oldest_first() {
+        let dedup = SignatureDedup::new(100);
+        for i in 0..150 {
+            assert!(dedup.is_new(format!("sig{}", i)));
+        }
+        assert!(dedup.size() <= 100);
+
+        // The newest 75 signatures are still inside the window
+        for i in 75..150 {
+            assert!(!dedup.is_new(format!("sig{}", i)), "sig{} was evicted", i);
+        }
+
+        // The oldest were evicted and look new again
+        assert!(dedup.is_new("sig0".to_string()));
+    }
+
+    #[test]
+    fn test_slot_tracker_keeps_max_slot_per_provider() {
+        let tracker = SlotTracker::new(2);
+
+        assert_eq!(tracker.update_slot("helius".to_string(), 100), 1);
+        assert_eq!(tracker.update_slot("alchemy".to_string(), 99), 0);
+        // A lower slot never overwrites a provider's high-water mark
+        assert_eq!(tracker.update_slot("helius".to_string(), 95), -1);
+        assert_eq!(tracker.update_slot("alchemy".to_string(), 101), 1);
+        assert_eq!(tracker.update_slot("triton".to_string(), 101), 0);
+
+        assert_eq!(
+            tracker.get_provider_slots(),
+            vec![
+                ("alchemy".to_string(), 101),
+                ("helius".to_string(), 100),
+                ("triton".to_string(), 101),
+            ]
+        );
+        assert_eq!(tracker.get_latest_slot(), 101);
+    }
+
+    #[test]
+    fn test_consensus_engine_flags_conflicting_slot_reports() {
+        let engine = ConsensusEngine::new(1000, 2);
+
+        assert!(engine.should_process(
+            "helius".to_string(),
+            "sigA".to_string(),
+            500,
+            Some("HashMajority".to_string()),
+            Some(499),
+        ));
+        // Same block from another provider: no fork
+        assert!(!engine.should_process(
+            "alchemy".to_string(),
+            "sigA".to_string(),
+            500,
+            Some("HashMajority".to_string()),
+            Some(499),
+        ));
+        assert_eq!(engine.fork_events(), 0);
+
+        // A different block claiming slot 500 from a forked history
+        assert!(!engine.should_process(
+            "triton".to_string(),
+            "sigB".to_string(),
+            500,
+            Some("HashMinority".to_string()),
+            Some(498),
+        ));
+        assert_eq!(engine.fork_events(), 1);
+
+        let warnings = engine.get_fork_warnings();
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].contains("slot 500"));
+        assert!(warnings[0].contains("helius") && warnings[0].contains("triton"));
+
+        // Reports without block identity skip fork detection
+        assert!(engine.should_process("triton".to_string(), "sigC".to_string(), 500, None, None));
+
+        engine.reset_stats();
+        assert_eq!(engine.fork_events(), 0);
+        assert!(engine.get_fork_warnings().is_empty());
+    }
+
+    #[test]
+    fn test_consensus_engine_minority_first_does_not_win() {
+        let engine = ConsensusEngine::new(1000, 2);
+
+        // The forked block happens to arrive first
+        assert!(engine.should_process(
+            "triton".to_string(),
+            "sigA".to_string(),
+            500,
+            Some("HashMinority".to_string()),
+            Some(498),
+        ));
+
+        // One rival report: a tie, so both blocks are suspect
+        assert!(!engine.should_process(
+            "helius".to_string(),
+            "sigB".to_string(),
+            500,
+            Some("HashMajority".to_string()),
+            Some(499),
+        ));
+        assert_eq!(engine.fork_events(), 1);
+        assert!(!engine.should_process(
+            "triton".to_string(),
+            "sigC".to_string(),
+            500,
+            Some("HashMinority".to_string()),
+            Some(498),
+        ));
+
+        // A second provider agrees with helius: the majority block is trusted
+        assert!(engine.should_process(
+            "alchemy".to_string(),
+            "sigB".to_string(),
+            500,
+            Some("HashMajority".to_string()),
+            Some(499),
+        ));
+        assert!(!engine.should_process(
+            "triton".to_string(),
+            "sigD".to_string(),
+            500,
+            Some("HashMinority".to_string()),
+            Some(498),
+        ));
+        assert_eq!(engine.fork_events(), 1);
+    }
+
+    #[test]
+    fn test_should_process_ref_matches_owned_path() {
+        let owned = ConsensusEngine::new(4, 2);
+        let borrowed = ConsensusEngine::new(4, 2);
+
+        // New, duplicate, stale, cross-provider duplicate, then enough
+        // fresh signatures to force dedup eviction
+        let mut messages = vec![
+            ("helius", "sig0", 100),
+            ("helius", "sig0", 100),
+            ("alchemy", "sig1", 103),
+            ("helius", "sig2", 100),
+            ("triton", "sig1", 103),
+        ];
+        let extra: Vec<String> = (3..10).map(|i| format!("sig{}", i)).collect();
+        messages.extend(extra.iter().map(|sig| ("alchemy", sig.as_str(), 104)));
+        messages.push(("helius", "sig3", 104));
+
+        for (provider, signature, slot) in messages {
+            assert_eq!(
+                owned.should_process(provider.to_string(), signature.to_string(), slot, None, None),
+                borrowed.should_process_ref(provider, signature, slot),
+                "{} {} {}",
+                provider,
+                signature,
+                slot
+            );
+        }
+        assert_eq!(owned.get_stats(), borrowed.get_stats());
+        assert_eq!(owned.dedup_size(), borrowed.dedup_size());
+        assert_eq!(
+            owned.slot_tracker.get_provider_slots(),
+            borrowed.slot_tracker.get_provider_slots()
+        );
+    }
+}
@@ -27,12 +27,20 @@ const TICKS_PER_ARRAY: i32 = 60;
 /// Q64.64 fixed-point constant (2^64)
 const Q64: u128 = 1u128 << 64;
 
+/// Tick array bitmap size in the pool state (16 × u64 = 1024 bits)
+const TICK_ARRAY_BITMAP_WORDS: usize = 16;
+
+/// Bitmap bit offset: bit `i` tracks tick array index `i - 512`
+const TICK_ARRAY_BITMAP_OFFSET: i32 = 512;
+
 // ============================================================================
 // POOL STATE PARSING
 // ============================================================================
 
 /// Raydium CLMM Pool State (partial structure for tick extraction)
-/// Full size is ~1544 bytes, we only need the first ~200 for tick info
+/// Field order and sizes follow the Raydium CLMM IDL `PoolState` account.
+/// Full size is 1544 bytes; we stop after the fund fees (1080 bytes) and
+/// skip the trailing open_time / recent_epoch / padding words.
 #[repr(C, packed)]
 #[derive(Copy, Clone)]
 pub struct ClmmPoolStatePartial {
@@ -60,18 +68,48 @@ pub struct ClmmPoolStatePartial {
     pub mint_decimals_1: u8,
     /// Tick spacing (2 bytes)
     pub tick_spacing: u16,
-    /// Liquidity (16 bytes, u128)
+    /// Liquidity (16 bytes, u128) - offset 237
     pub liquidity: [u8; 16],
-    /// Sqrt price X64 (16 bytes, u128)
+    /// Sqrt price X64 (16 bytes, u128) - offset 253
     pub sqrt_price_x64: [u8; 16],
-    /// Current tick (4 bytes, i32)
+    /// Current tick (4 bytes, i32) - offset 269
     pub tick_current: i32,
-    // Padding to align (2 bytes)
-    pub _padding: [u8; 2],
-    /// Protocol fees token 0 (8 bytes, u64)
+    /// IDL padding3 (2 bytes)
+    pub padding3: u16,
+    /// IDL padding4 (2 bytes)
+    pub padding4: u16,
+    /// Fee growth global token 0 (16 bytes, u128)
+    pub fee_growth_global_0_x64: [u8; 16],
+    /// Fee growth global token 1 (16 bytes, u128)
+    pub fee_growth_global_1_x64: [u8; 16],
+    /// Protocol fees token 0 (8 bytes, u64) - offset 309
     pub protocol_fees_token_0: u64,
     /// Protocol fees token 1 (8 bytes, u64)
     pub protocol_fees_token_1: u64,
+    /// Swap in amount token 0 (16 bytes, u128)
+    pub swap_in_amount_token_0: [u8; 16],
+    /// Swap out amount token 1 (16 bytes, u128)
+    pub swap_out_amount_token_1: [u8; 16],
+    /// Swap in amount token 1 (16 bytes, u128)
+    pub swap_in_amount_token_1: [u8; 16],
+    /// Swap out amount token 0 (16 bytes, u128)
+    pub swap_out_amount_token_0: [u8; 16],
+    /// Pool status bitflags (1 byte)
+    pub status: u8,
+    /// IDL padding (7 bytes)
+    pub padding: [u8; 7],
+    /// Reward infos (3 × 169 bytes)
+    pub reward_infos: [u8; 3 * 169],
+    /// Tick array bitmap (16 × u64) - offset 904
+    pub tick_array_bitmap: [u64; 16],
+    /// Total fees token 0 (8 bytes, u64)
+    pub total_fees_token_0: u64,
+    /// Total fees claimed token 0 (8 bytes, u64)
+    pub total_fees_claimed_token_0: u64,
+    /// Total fees token 1 (8 bytes, u64)
+    pub total_fees_token_1: u64,
+    /// Total fees claimed token 1 (8 bytes, u64)
+    pub total_fees_claimed_token_1: u64,
     /// Fund fees token 0 (8 bytes, u64)
     pub fund_fees_token_0: u64,
     /// Fund fees token 1 (8 bytes, u64)
@@ -112,6 +150,8 @@ pub struct ClmmPoolInfo {
     pub mint_decimals_0: u8,
     #[pyo3(get)]
     pub mint_decimals_1: u8,
+    #[pyo3(get)]
+    pub tick_array_bitmap: String, // base64 of the 16 × u64 bitmap
 }
 
 #[pymethods]
@@ -157,15 +197,16 @@ pub fn parse_clmm_pool_state(pool_id: String, data_b64: String) -> PyResult<Clmm
             format!("Base64 decode error: {}", e)
         ))?;
     
-    // Minimum size check (we need at least 300 bytes for the partial struct)
-    if data.len() < 300 {
+    // Minimum size check (the partial struct covers the first 1080 bytes)
+    let partial_len = std::mem::size_of::<ClmmPoolStatePartial>();
+    if data.len() < partial_len {
         return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
-            format!("Data too short: {} bytes, need at least 300", data.len())
+            format!("Data too short: {} bytes, need at least {}", data.len(), partial_len)
         ));
     }
     
     // Parse using bytemuck (zero-copy where possible)
-    let state: &ClmmPoolStatePartial = bytemuck::from_bytes(&data[..std::mem::size_of::<ClmmPoolStatePartial>()]);
+    let state: &ClmmPoolStatePartial = bytemuck::from_bytes(&data[..partial_len]);
     
     // Convert fixed arrays to pubkey strings
     let amm_config = bs58::encode(&state.amm_config).into_string();
@@ -178,6 +219,9 @@ pub fn parse_clmm_pool_state(pool_id: String, data_b64: String) -> PyResult<Clmm
     // Parse u128 values
     let sqrt_price_x64 = u128::from_le_bytes(state.sqrt_price_x64);
     let liquidity = u128::from_le_bytes(state.liquidity);
+    let bitmap_offset = std::mem::offset_of!(ClmmPoolStatePartial, tick_array_bitmap);
+    let tick_array_bitmap = general_purpose::STANDARD
+        .encode(&data[bitmap_offset..bitmap_offset + TICK_ARRAY_BITMAP_WORDS * 8]);
     
     Ok(ClmmPoolInfo {
         pool_id,
@@ -193,6 +237,7 @@ pub fn parse_clmm_pool_state(pool_id: String, data_b64: String) -> PyResult<Clmm
         liquidity: liquidity.to_string(),
         mint_decimals_0: state.mint_decimals_0,
         mint_decimals_1: state.mint_decimals_1,
+        tick_array_bitmap,
     })
 }
 
@@ -241,6 +286,25 @@ fn derive_tick_array_pda(pool_id: &Pubkey, start_tick: i32) -> Result<Pubkey, St
     Ok(pda)
 }
 
+/// Select the three tick array indices a swap traverses, in swap order.
+///
+/// A swap only ever moves one way, so the window starts at the array
+/// containing `tick_current` and extends in the swap direction: A→B (price
+/// down) gets (c, c-1, c-2), B→A (price up) gets (c, c+1, c+2). A tick
+/// sitting exactly on an array start is still in that array; for A→B the
+/// swap crosses it at once, and the next array down is already second in
+/// the window.
+fn select_tick_array_indices(
+    tick_current: i32,
+    tick_spacing: u16,
+    a_to_b: bool,
+) -> (i32, i32, i32) {
+    let current_index = get_tick_array_index(tick_current, tick_spacing);
+    let step = if a_to_b { -1 } else { 1 };
+
+    (current_index, current_index + step, current_index + 2 * step)
+}
+
 /// Derive the 3 tick arrays needed for a CLMM swap.
 /// 
 /// # Arguments
@@ -250,7 +314,8 @@ fn derive_tick_array_pda(pool_id: &Pubkey, start_tick: i32) -> Result<Pubkey, St
 /// * `a_to_b` - Swap direction (true = token0 → token1, price decreases)
 /// 
 /// # Returns
-/// Tuple of (tick_array_lower, tick_array_current, tick_array_upper) as base58 strings
+/// Tuple of 3 tick array PDAs as base58 strings, in the order the swap
+/// crosses them (the array containing `tick_current` first)
 #[pyfunction]
 pub fn derive_tick_arrays(
     pool_id: &str,
@@ -263,48 +328,58 @@ pub fn derive_tick_arrays(
             format!("Invalid pool_id: {}", e)
         ))?;
     
-    // Calculate current array index
-    let current_index = get_tick_array_index(tick_current, tick_spacing);
+    // Get array indices in swap order
+    let indices = select_tick_array_indices(tick_current, tick_spacing, a_to_b);
     
-    // Get array indices based on swap direction
-    // A→B (price down): need current and lower arrays
-    // B→A (price up): need current and upper arrays
-    let (lower_index, upper_index) = if a_to_b {
-        (current_index - 1, current_index)
-    } else {
-        (current_index, current_index + 1)
+    let derive = |index: i32| {
+        let start_tick = get_tick_array_start_tick(index, tick_spacing);
+        derive_tick_array_pda(&pool_pubkey, start_tick)
+            .map(|pda| pda.to_string())
+            .map_err(PyErr::new::<pyo3::exceptions::PyValueError, _>)
     };
     
-    // Calculate start ticks
-    let lower_start = get_tick_array_start_tick(lower_index, tick_spacing);
-    let current_start = get_tick_array_start_tick(current_index, tick_spacing);
-    let upper_start = get_tick_array_start_tick(upper_index, tick_spacing);
+    Ok((derive(indices.0)?, derive(indices.1)?, derive(indices.2)?))
+}
+
+/// Derive `2 * window + 1` tick array PDAs centered on `current_index`,
+/// in ascending start-tick order.
+fn derive_tick_array_window(
+    pool_pubkey: &Pubkey,
+    current_index: i32,
+    tick_spacing: u16,
+    window: i32,
+) -> Result<Vec<String>, String> {
+    let mut arrays = Vec::with_capacity((2 * window + 1) as usize);
     
-    // Derive PDAs
-    let lower_pda = derive_tick_array_pda(&pool_pubkey, lower_start)
-        .map_err(|e| PyErr::new::<pyo3::exceptions::PyValueError, _>(e))?;
-    let current_pda = derive_tick_array_pda(&pool_pubkey, current_start)
-        .map_err(|e| PyErr::new::<pyo3::exceptions::PyValueError, _>(e))?;
-    let upper_pda = derive_tick_array_pda(&pool_pubkey, upper_start)
-        .map_err(|e| PyErr::new::<pyo3::exceptions::PyValueError, _>(e))?;
+    for offset in -window..=window {
+        let array_index = current_index + offset;
+        let start_tick = get_tick_array_start_tick(array_index, tick_spacing);
+        let pda = derive_tick_array_pda(pool_pubkey, start_tick)?;
+        arrays.push(pda.to_string());
+    }
     
-    Ok((
-        lower_pda.to_string(),
-        current_pda.to_string(),
-        upper_pda.to_string(),
-    ))
+    Ok(arrays)
 }
 
 /// Derive tick arrays with extra headroom for high-volatility swaps.
 /// 
-/// Returns 5 tick arrays: [current-2, current-1, current, current+1, current+2]
-/// Use the 3 most relevant based on swap direction and expected slippage.
+/// Returns `2 * window + 1` tick arrays: [current-window, ..., current, ..., current+window]
+/// (5 arrays with the default window of 2). Use the 3 most relevant based on
+/// swap direction and expected slippage.
 #[pyfunction]
+#[pyo3(signature = (pool_id, tick_current, tick_spacing, window=2))]
 pub fn derive_tick_arrays_extended(
     pool_id: &str,
     tick_current: i32,
     tick_spacing: u16,
+    window: i32,
 ) -> PyResult<Vec<String>> {
+    if window < 0 {
+        return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+            format!("window must be >= 0, got {}", window)
+        ));
+    }
+    
     let pool_pubkey = Pubkey::from_str(pool_id)
         .map_err(|e| PyErr::new::<pyo3::exceptions::PyValueError, _>(
             format!("Invalid pool_id: {}", e)
@@ -312,35 +387,115 @@ pub fn derive_tick_arrays_extended(
     
     let current_index = get_tick_array_index(tick_current, tick_spacing);
     
-    let mut arrays = Vec::with_capacity(5);
-    
-    for offset in -2..=2 {
-        let array_index = current_index + offset;
-        let start_tick = get_tick_array_start_tick(array_index, tick_spacing);
-        let pda = derive_tick_array_pda(&pool_pubkey, start_tick)
-            .map_err(|e| PyErr::new::<pyo3::exceptions::PyValueError, _>(e))?;
-        arrays.push(pda.to_string());
+    derive_tick_array_window(&pool_pubkey, current_index, tick_spacing, window)
+        .map_err(PyErr::new::<pyo3::exceptions::PyValueError, _>)
+}
+
+// ============================================================================
+// TICK ARRAY BITMAP
+// ============================================================================
+
+/// Decode the pool's base64 tick array bitmap into 16 little-endian u64 words.
+fn decode_tick_array_bitmap(bitmap_b64: &str) -> Result<[u64; TICK_ARRAY_BITMAP_WORDS], String> {
+    use base64::{Engine as _, engine::general_purpose};
+
+    let bytes = general_purpose::STANDARD.decode(bitmap_b64)
+        .map_err(|e| format!("Base64 decode error: {}", e))?;
+
+    if bytes.len() != TICK_ARRAY_BITMAP_WORDS * 8 {
+        return Err(format!(
+            "Bitmap must be {} bytes, got {}",
+            TICK_ARRAY_BITMAP_WORDS * 8,
+            bytes.len()
+        ));
     }
-    
-    Ok(arrays)
+
+    let mut words = [0u64; TICK_ARRAY_BITMAP_WORDS];
+    for (word, chunk) in words.iter_mut().zip(bytes.chunks_exact(8)) {
+        *word = u64::from_le_bytes(chunk.try_into().unwrap());
+    }
+    Ok(words)
 }
 
-/// Convert sqrt_price_x64 to tick index.
-/// 
-/// Formula: tick = 2 * log(sqrt_price) / log(1.0001)
+/// Check whether the tick array at `array_index` is marked initialized.
+fn is_tick_array_initialized(bitmap: &[u64; TICK_ARRAY_BITMAP_WORDS], array_index: i32) -> bool {
+    let bit = array_index + TICK_ARRAY_BITMAP_OFFSET;
+    if !(0..(TICK_ARRAY_BITMAP_WORDS as i32 * 64)).contains(&bit) {
+        return false;
+    }
+    bitmap[(bit / 64) as usize] & (1u64 << (bit % 64)) != 0
+}
+
+/// Walk the bitmap from `current_index` in the swap direction and return the
+/// first initialized array index.
+///
+/// a→b includes the current array, since its start tick is at or below the
+/// current tick. b→a starts at the next array up, because the current array's
+/// start tick lies behind the swap.
+fn next_initialized_array_index(
+    bitmap: &[u64; TICK_ARRAY_BITMAP_WORDS],
+    current_index: i32,
+    a_to_b: bool,
+) -> Option<i32> {
+    let min_index = -TICK_ARRAY_BITMAP_OFFSET;
+    let max_index = TICK_ARRAY_BITMAP_WORDS as i32 * 64 - TICK_ARRAY_BITMAP_OFFSET - 1;
+
+    if a_to_b {
+        (min_index..=current_index.min(max_index))
+            .rev()
+            .find(|&idx| is_tick_array_initialized(bitmap, idx))
+    } else {
+        (current_index.saturating_add(1).max(min_index)..=max_index)
+            .find(|&idx| is_tick_array_initialized(bitmap, idx))
+    }
+}
+
+/// Find the nearest initialized tick in the swap direction using the pool's
+/// tick array bitmap.
+///
+/// The bitmap only tracks which tick arrays are initialized, so the result is
+/// the start tick of the nearest initialized array the swap will traverse.
+/// For a→b the array containing `current_tick` counts; for b→a the result is
+/// always strictly above `current_tick`. Load that array to find the exact
+/// initialized tick within it.
+///
+/// # Arguments
+/// * `tick_array_bitmap_b64` - Base64 of the 128-byte bitmap (`ClmmPoolInfo.tick_array_bitmap`)
+/// * `current_tick` - Current tick from pool state
+/// * `tick_spacing` - Tick spacing from pool state
+/// * `a_to_b` - Swap direction (true = token0 → token1, price decreases)
+///
+/// # Returns
+/// Start tick of the next initialized tick array, or None if there is none
 #[pyfunction]
-pub fn sqrt_price_to_tick(sqrt_price_x64: u128) -> PyResult<i32> {
-    if sqrt_price_x64 == 0 {
+pub fn find_next_initialized_tick(
+    tick_array_bitmap_b64: &str,
+    current_tick: i32,
+    tick_spacing: u16,
+    a_to_b: bool,
+) -> PyResult<Option<i32>> {
+    if tick_spacing == 0 {
         return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
-            "sqrt_price cannot be zero"
+            "tick_spacing must be > 0"
         ));
     }
-    
-    let sqrt_price = (sqrt_price_x64 as f64) / (Q64 as f64);
-    let ln_1_0001 = 0.00009999500033330834f64; // ln(1.0001)
-    let tick = (2.0 * sqrt_price.ln()) / ln_1_0001;
-    
-    Ok(tick.floor() as i32)
+
+    let bitmap = decode_tick_array_bitmap(tick_array_bitmap_b64)
+        .map_err(PyErr::new::<pyo3::exceptions::PyValueError, _>)?;
+
+    let current_index = get_tick_array_index(current_tick, tick_spacing);
+
+    Ok(next_initialized_array_index(&bitmap, current_index, a_to_b)
+        .map(|idx| get_tick_array_start_tick(idx, tick_spacing)))
+}
+
+/// Convert sqrt_price_x64 to tick index.
+/// 
+/// Delegates to the exact integer tick math in `amm_math` so array
+/// selection never disagrees with the on-chain tick.
+#[pyfunction]
+pub fn sqrt_price_to_tick(sqrt_price_x64: u128) -> PyResult<i32> {
+    crate::amm_math::tick_from_sqrt_price(sqrt_price_x64)
 }
 
 /// Convert tick index to sqrt_price_x64.
@@ -348,14 +503,7 @@ pub fn sqrt_price_to_tick(sqrt_price_x64: u128) -> PyResult<i32> {
 /// Formula: sqrt_price = 1.0001^(tick/2) * 2^64
 #[pyfunction]
 pub fn tick_to_sqrt_price(tick: i32) -> PyResult<u128> {
-    let tick_f64 = tick as f64;
-    let ln_1_0001 = 0.00009999500033330834f64;
-    let exponent = tick_f64 * ln_1_0001 / 2.0;
-    let sqrt_price = exponent.exp();
-    
-    let sqrt_price_x64 = (sqrt_price * (Q64 as f64)) as u128;
-    
-    Ok(sqrt_price_x64)
+    crate::amm_math::sqrt_price_from_tick(tick)
 }
 
 // ============================================================================
@@ -370,6 +518,7 @@ pub fn register_tick_array_functions(m: &PyModule) -> PyResult<()> {
     // Tick array derivation
     m.add_function(wrap_pyfunction!(derive_tick_arrays, m)?)?;
     m.add_function(wrap_pyfunction!(derive_tick_arrays_extended, m)?)?;
+    m.add_function(wrap_pyfunction!(find_next_initialized_tick, m)?)?;
     
     // Tick/price conversion
     m.add_function(wrap_pyfunction!(sqrt_price_to_tick, m)?)?;
@@ -421,4 +570,196 @@ mod tests {
         // Allow +/- 1 due to rounding
         assert!((recovered_tick - original_tick).abs() <= 1);
     }
+
+    fn tick_array_pdas(pool: &Pubkey, starts: &[i32]) -> Vec<String> {
+        starts
+            .iter()
+            .map(|&start| derive_tick_array_pda(pool, start).unwrap().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_derive_tick_arrays_mid_array() {
+        let pool = Pubkey::new_unique();
+        // tick=1000, spacing=10 → array 1 [600, 1200), not on a boundary.
+        // Each direction only gets arrays it can move into.
+        let (first, second, third) = derive_tick_arrays(&pool.to_string(), 1000, 10, true).unwrap();
+        assert_eq!(vec![first, second, third], tick_array_pdas(&pool, &[600, 0, -600]));
+
+        let (first, second, third) = derive_tick_arrays(&pool.to_string(), 1000, 10, false).unwrap();
+        assert_eq!(vec![first, second, third], tick_array_pdas(&pool, &[600, 1200, 1800]));
+    }
+
+    #[test]
+    fn test_derive_tick_arrays_boundary_a_to_b() {
+        let pool = Pubkey::new_unique();
+        // tick=600 is the start of array 1; price moving down crosses into array 0
+        // immediately, so the window must reach array -1.
+        let (first, second, third) = derive_tick_arrays(&pool.to_string(), 600, 10, true).unwrap();
+        assert_eq!(vec![first, second, third], tick_array_pdas(&pool, &[600, 0, -600]));
+
+        // Negative boundary: tick=-1200 is the start of array -2
+        let (first, second, third) = derive_tick_arrays(&pool.to_string(), -1200, 10, true).unwrap();
+        assert_eq!(vec![first, second, third], tick_array_pdas(&pool, &[-1200, -1800, -2400]));
+    }
+
+    #[test]
+    fn test_derive_tick_arrays_boundary_b_to_a() {
+        let pool = Pubkey::new_unique();
+        // tick=600, price moving up stays in array 1 then enters array 2 and 3
+        let (first, second, third) = derive_tick_arrays(&pool.to_string(), 600, 10, false).unwrap();
+        assert_eq!(vec![first, second, third], tick_array_pdas(&pool, &[600, 1200, 1800]));
+
+        let (first, second, third) = derive_tick_arrays(&pool.to_string(), 0, 10, false).unwrap();
+        assert_eq!(vec![first, second, third], tick_array_pdas(&pool, &[0, 600, 1200]));
+    }
+
+    #[test]
+    fn test_pool_state_layout_matches_idl() {
+        use std::mem::{offset_of, size_of};
+
+        assert_eq!(offset_of!(ClmmPoolStatePartial, mint_decimals_0), 233);
+        assert_eq!(offset_of!(ClmmPoolStatePartial, tick_spacing), 235);
+        assert_eq!(offset_of!(ClmmPoolStatePartial, liquidity), 237);
+        assert_eq!(offset_of!(ClmmPoolStatePartial, sqrt_price_x64), 253);
+        assert_eq!(offset_of!(ClmmPoolStatePartial, tick_current), 269);
+        assert_eq!(offset_of!(ClmmPoolStatePartial, protocol_fees_token_0), 309);
+        assert_eq!(offset_of!(ClmmPoolStatePartial, status), 389);
+        assert_eq!(offset_of!(ClmmPoolStatePartial, reward_infos), 397);
+        assert_eq!(offset_of!(ClmmPoolStatePartial, tick_array_bitmap), 904);
+        assert_eq!(offset_of!(ClmmPoolStatePartial, fund_fees_token_0), 1064);
+        assert_eq!(size_of::<ClmmPoolStatePartial>(), 1080);
+    }
+
+    #[test]
+    fn test_parse_clmm_pool_state_idl_offsets() {
+        use base64::{engine::general_purpose, Engine as _};
+
+        // Full 1544-byte account with fields written at the IDL offsets
+        let mint_0 = Pubkey::new_unique();
+        let mint_1 = Pubkey::new_unique();
+        let tick: i32 = -18_534;
+        let sqrt_price = tick_to_sqrt_price(tick).unwrap();
+        let liquidity: u128 = 123_456_789_012_345;
+
+        let mut data = vec![0u8; 1544];
+        data[73..105].copy_from_slice(mint_0.as_ref());
+        data[105..137].copy_from_slice(mint_1.as_ref());
+        data[233] = 9;
+        data[234] = 6;
+        data[235..237].copy_from_slice(&1u16.to_le_bytes());
+        data[237..253].copy_from_slice(&liquidity.to_le_bytes());
+        data[253..269].copy_from_slice(&sqrt_price.to_le_bytes());
+        data[269..273].copy_from_slice(&tick.to_le_bytes());
+        // Non-zero padding3/padding4 and fee growth must not bleed into other fields
+        data[273..309].fill(0xAB);
+        data[309..317].copy_from_slice(&42u64.to_le_bytes());
+
+        let pool = Pubkey::new_unique().to_string();
+        let info =
+            parse_clmm_pool_state(pool, general_purpose::STANDARD.encode(&data)).unwrap();
+
+        assert_eq!(info.tick_current, tick);
+        assert_eq!(info.sqrt_price_x64, sqrt_price.to_string());
+        assert_eq!(info.liquidity, liquidity.to_string());
+        assert_eq!(info.tick_spacing, 1);
+        assert_eq!(info.mint_decimals_0, 9);
+        assert_eq!(info.mint_decimals_1, 6);
+        assert_eq!(info.token_mint_0, mint_0.to_string());
+        assert_eq!(info.token_mint_1, mint_1.to_string());
+
+        let state: &ClmmPoolStatePartial =
+            bytemuck::from_bytes(&data[..std::mem::size_of::<ClmmPoolStatePartial>()]);
+        assert_eq!({ state.protocol_fees_token_0 }, 42);
+
+        // Truncated accounts are rejected instead of read past the end
+        assert!(parse_clmm_pool_state(
+            Pubkey::new_unique().to_string(),
+            general_purpose::STANDARD.encode(&data[..300])
+        )
+        .is_err());
+    }
+
+    fn bitmap_b64(initialized: &[i32]) -> String {
+        use base64::{engine::general_purpose, Engine as _};
+
+        let mut words = [0u64; TICK_ARRAY_BITMAP_WORDS];
+        for &idx in initialized {
+            let bit = idx + TICK_ARRAY_BITMAP_OFFSET;
+            words[(bit / 64) as usize] |= 1u64 << (bit % 64);
+        }
+        let bytes: Vec<u8> = words.iter().flat_map(|w| w.to_le_bytes()).collect();
+        general_purpose::STANDARD.encode(bytes)
+    }
+
+    #[test]
+    fn test_find_next_initialized_tick() {
+        // spacing=10 → 600 ticks per array; only arrays -3 and 2 are initialized
+        let bitmap = bitmap_b64(&[-3, 2]);
+
+        // tick=100 is in array 0
+        assert_eq!(
+            find_next_initialized_tick(&bitmap, 100, 10, true).unwrap(),
+            Some(-1800)
+        );
+        assert_eq!(
+            find_next_initialized_tick(&bitmap, 100, 10, false).unwrap(),
+            Some(1200)
+        );
+
+        // Current array counts when it is initialized (a→b only)
+        assert_eq!(
+            find_next_initialized_tick(&bitmap, 1300, 10, true).unwrap(),
+            Some(1200)
+        );
+        // b→a must not step back to the current array's start tick
+        assert_eq!(find_next_initialized_tick(&bitmap, 1300, 10, false).unwrap(), None);
+        let adjacent = bitmap_b64(&[2, 3]);
+        assert_eq!(
+            find_next_initialized_tick(&adjacent, 1300, 10, false).unwrap(),
+            Some(1800)
+        );
+
+        // Nothing initialized past the last array in the direction
+        assert_eq!(find_next_initialized_tick(&bitmap, 2000, 10, false).unwrap(), None);
+        assert_eq!(find_next_initialized_tick(&bitmap, -2000, 10, true).unwrap(), None);
+
+        // Bitmap edges (array -512 and 511)
+        let edges = bitmap_b64(&[-512, 511]);
+        assert_eq!(
+            find_next_initialized_tick(&edges, 0, 1, true).unwrap(),
+            Some(-512 * 60)
+        );
+        assert_eq!(
+            find_next_initialized_tick(&edges, 0, 1, false).unwrap(),
+            Some(511 * 60)
+        );
+
+        // Wrong bitmap length is rejected
+        assert!(find_next_initialized_tick("AAAA", 0, 10, true).is_err());
+    }
+
+    #[test]
+    fn test_derive_tick_arrays_extended_window() {
+        let pool = Pubkey::new_unique();
+
+        // Default window keeps the original 5 arrays
+        let default = derive_tick_arrays_extended(&pool.to_string(), 1000, 10, 2).unwrap();
+        assert_eq!(default, tick_array_pdas(&pool, &[-600, 0, 600, 1200, 1800]));
+
+        // window=3 → 7 distinct arrays in ascending start-tick order
+        let wide = derive_tick_arrays_extended(&pool.to_string(), 1000, 10, 3).unwrap();
+        assert_eq!(
+            wide,
+            tick_array_pdas(&pool, &[-1200, -600, 0, 600, 1200, 1800, 2400])
+        );
+        let distinct: std::collections::HashSet<_> = wide.iter().collect();
+        assert_eq!(distinct.len(), 7);
+
+        // window=0 → only the current array
+        let single = derive_tick_arrays_extended(&pool.to_string(), 1000, 10, 0).unwrap();
+        assert_eq!(single, tick_array_pdas(&pool, &[600]));
+
+        assert!(derive_tick_arrays_extended(&pool.to_string(), 1000, 10, -1).is_err());
+    }
 }
@@ -4,7 +4,27 @@
 // ------------------------------------------------------------------------
 
 use pyo3::prelude::*;
-use serde::Deserialize;
+use serde::de::{SeqAccess, Visitor};
+use serde::{Deserialize, Deserializer};
+
+const SOL_MINT: &str = "So11111111111111111111111111111111111111112";
+const USDC_MINT: &str = "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v";
+const USDT_MINT: &str = "Es9vMFrzaCERmJfrF4H2FYD4KCoNkY11McCe8BenwNYB";
+
+/// Quote mints skipped by `get_primary_token` unless the caller overrides them
+const DEFAULT_QUOTE_MINTS: &[&str] = &[USDC_MINT, USDT_MINT];
+
+/// Swap programs whose invocations count as one swap leg each.
+/// Aggregators (Jupiter) are routers, so only the venues they CPI into count.
+const DEX_PROGRAM_IDS: &[&str] = &[
+    "675kPX9MHTjS2zt1qfr1NYHuzeLXfQM9H24wFSUt1Mp8", // Raydium V4
+    "CAMMCzo5YL8w4VFF8KVHrK22GGUsp5VTaW7grrKgrWqK", // Raydium CLMM
+    "CPMMoo8L3F4NbTegBCKVNunggL7H1ZpdTHKxQB5qKP1C", // Raydium CPMM
+    "whirLbMiicVdio4qvUfM5KAg6Ct8VwpYzGff3uctyCc", // Orca Whirlpool
+    "LBUZKhRxPF3XUpBCjp4YzTKgLccjZhTSDM9YuVaPwxo", // Meteora DLMM
+    "PhoeNiXZ8ByJGLkxNfZRnkUfjvmuYqLR89jjFHGqdXY", // Phoenix
+    "opnb2LAfJYbRMAHHvqjCwQxanZn7ReEHp1k81EohpZb", // OpenBook V2
+];
 
 /// Represents a token transfer extracted from a transaction
 #[pyclass]
@@ -52,19 +72,36 @@ pub struct ParsedTx {
     pub fee_payer: Option<String>,
     #[pyo3(get)]
     pub slot: u64,
+    /// DEX program invocations (top-level and inner)
+    #[pyo3(get)]
+    pub swap_count: u32,
+    /// Multiple swaps whose first input mint is also the last output mint
+    #[pyo3(get)]
+    pub is_arbitrage: bool,
 }
 
 #[pymethods]
 impl ParsedTx {
-    /// Get the primary token (non-USDC) from the swap
-    fn get_primary_token(&self) -> Option<TokenTransfer> {
-        const USDC_MINT: &str = "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v";
-        const USDT_MINT: &str = "Es9vMFrzaCERmJfrF4H2FYD4KCoNkY11McCe8BenwNYB";
-        const SOL_MINT: &str = "So11111111111111111111111111111111111111112";
-        
+    /// Get the primary token (non-quote) from the swap
+    /// `quote_mints` replaces the default quote set (USDC, USDT); wSOL is
+    /// still preferred when every transfer is a quote mint.
+    #[pyo3(signature = (quote_mints=None))]
+    fn get_primary_token(&self, quote_mints: Option<Vec<String>>) -> Option<TokenTransfer> {
+        match quote_mints {
+            Some(mints) => {
+                let mints: Vec<&str> = mints.iter().map(String::as_str).collect();
+                self.primary_token(&mints)
+            }
+            None => self.primary_token(DEFAULT_QUOTE_MINTS),
+        }
+    }
+}
+
+impl ParsedTx {
+    fn primary_token(&self, quote_mints: &[&str]) -> Option<TokenTransfer> {
         // Find the non-stablecoin token
         for transfer in &self.token_transfers {
-            if transfer.mint != USDC_MINT && transfer.mint != USDT_MINT {
+            if !quote_mints.contains(&transfer.mint.as_str()) {
                 return Some(transfer.clone());
             }
         }
@@ -94,6 +131,15 @@ struct HeliusTx {
     #[serde(rename = "feePayer")]
     fee_payer: Option<String>,
     slot: Option<u64>,
+    instructions: Option<Vec<HeliusInstruction>>,
+}
+
+#[derive(Deserialize, Debug)]
+struct HeliusInstruction {
+    #[serde(rename = "programId")]
+    program_id: Option<String>,
+    #[serde(rename = "innerInstructions")]
+    inner_instructions: Option<Vec<HeliusInstruction>>,
 }
 
 #[derive(Deserialize, Debug)]
@@ -136,12 +182,77 @@ pub fn parse_helius_tx(json_str: &str) -> PyResult<Option<ParsedTx>> {
         }
     };
     
-    if txs.is_empty() {
-        return Ok(None);
-    }
-    
-    let tx = &txs[0];
+    // Only the first transaction is returned
+    Ok(txs.into_iter().next().map(build_parsed_tx))
+}
+
+/// Parse multiple Helius transactions at once (batch processing)
+#[pyfunction]
+pub fn parse_helius_tx_batch(json_str: &str) -> PyResult<Vec<ParsedTx>> {
+    let txs: Vec<HeliusTx> = serde_json::from_str(json_str)
+        .map_err(|e| PyErr::new::<pyo3::exceptions::PyValueError, _>(e.to_string()))?;
     
+    Ok(txs.into_iter().map(build_parsed_tx).collect())
+}
+
+/// Parse a Helius webhook payload element by element
+/// Accepts a JSON array or concatenated/newline-delimited objects. Entries
+/// that don't match the Helius shape are skipped; a syntax error ends the
+/// stream but keeps everything parsed before it.
+#[pyfunction]
+pub fn parse_helius_tx_stream(json_bytes: &[u8]) -> Vec<ParsedTx> {
+    let mut results = Vec::new();
+    for_each_helius_tx(json_bytes, |tx| results.push(tx));
+    results
+}
+
+/// Feed each well-formed transaction in `json_bytes` to `on_tx` as it is parsed
+fn for_each_helius_tx(json_bytes: &[u8], mut on_tx: impl FnMut(ParsedTx)) {
+    let is_array = json_bytes.iter()
+        .find(|b| !b.is_ascii_whitespace())
+        .is_some_and(|b| *b == b'[');
+
+    if is_array {
+        let mut de = serde_json::Deserializer::from_slice(json_bytes);
+        // Errors only end the stream early; parsed entries were already emitted
+        let _ = de.deserialize_seq(TxSeqVisitor { on_tx: &mut on_tx });
+    } else {
+        let stream = serde_json::Deserializer::from_slice(json_bytes)
+            .into_iter::<serde_json::Value>();
+        for value in stream {
+            let Ok(value) = value else { break };
+            if let Ok(tx) = HeliusTx::deserialize(value) {
+                on_tx(build_parsed_tx(tx));
+            }
+        }
+    }
+}
+
+/// Visits a JSON array one element at a time, so only a single transaction
+/// is materialized at once
+struct TxSeqVisitor<'a, F: FnMut(ParsedTx)> {
+    on_tx: &'a mut F,
+}
+
+impl<'de, F: FnMut(ParsedTx)> Visitor<'de> for TxSeqVisitor<'_, F> {
+    type Value = ();
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        formatter.write_str("an array of Helius transactions")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<(), A::Error> {
+        while let Some(value) = seq.next_element::<serde_json::Value>()? {
+            if let Ok(tx) = HeliusTx::deserialize(value) {
+                (self.on_tx)(build_parsed_tx(tx));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Convert a deserialized Helius transaction into a ParsedTx
+fn build_parsed_tx(tx: HeliusTx) -> ParsedTx {
     // Extract token transfers
     let mut transfers: Vec<TokenTransfer> = Vec::new();
     
@@ -166,7 +277,7 @@ pub fn parse_helius_tx(json_str: &str) -> PyResult<Option<ParsedTx>> {
             if let Some(amount) = xfer.amount {
                 if amount > 0 {
                     transfers.push(TokenTransfer {
-                        mint: "So11111111111111111111111111111111111111112".to_string(),
+                        mint: SOL_MINT.to_string(),
                         symbol: Some("SOL".to_string()),
                         amount: amount as f64 / 1_000_000_000.0,  // Convert lamports to SOL
                         from_account: xfer.from_user_account.clone(),
@@ -177,105 +288,288 @@ pub fn parse_helius_tx(json_str: &str) -> PyResult<Option<ParsedTx>> {
             }
         }
     }
+
+    let swap_count = match &tx.instructions {
+        Some(instructions) => count_dex_invocations(instructions),
+        // Without instruction data, each token leg leaving the payer is one swap
+        None => tx.fee_payer.as_deref().map_or(0, |payer| {
+            transfers.iter()
+                .filter(|t| !t.is_native && t.from_account.as_deref() == Some(payer))
+                .count() as u32
+        }),
+    };
+    let is_arbitrage = swap_count >= 2
+        && tx.fee_payer.as_deref().is_some_and(|payer| is_cyclic(&transfers, payer));
     
-    // Determine source (DEX)
-    let source = tx.source.clone().unwrap_or_else(|| "UNKNOWN".to_string());
-    
-    Ok(Some(ParsedTx {
-        signature: tx.signature.clone().unwrap_or_default(),
-        tx_type: tx.tx_type.clone().unwrap_or_else(|| "UNKNOWN".to_string()),
-        source,
+    ParsedTx {
+        signature: tx.signature.unwrap_or_default(),
+        tx_type: tx.tx_type.unwrap_or_else(|| "UNKNOWN".to_string()),
+        // Determine source (DEX)
+        source: tx.source.unwrap_or_else(|| "UNKNOWN".to_string()),
         token_transfers: transfers,
-        fee_payer: tx.fee_payer.clone(),
+        fee_payer: tx.fee_payer,
         slot: tx.slot.unwrap_or(0),
-    }))
+        swap_count,
+        is_arbitrage,
+    }
 }
 
-/// Parse multiple Helius transactions at once (batch processing)
-#[pyfunction]
-pub fn parse_helius_tx_batch(json_str: &str) -> PyResult<Vec<ParsedTx>> {
-    let txs: Vec<HeliusTx> = serde_json::from_str(json_str)
-        .map_err(|e| PyErr::new::<pyo3::exceptions::PyValueError, _>(e.to_string()))?;
-    
-    let mut results = Vec::with_capacity(txs.len());
-    
-    for tx in txs {
-        let mut transfers: Vec<TokenTransfer> = Vec::new();
-        
-        if let Some(token_xfers) = &tx.token_transfers {
-            for xfer in token_xfers {
-                if let Some(mint) = &xfer.mint {
-                    transfers.push(TokenTransfer {
-                        mint: mint.clone(),
-                        symbol: None,
-                        amount: xfer.token_amount.unwrap_or(0.0),
-                        from_account: xfer.from_user_account.clone(),
-                        to_account: xfer.to_user_account.clone(),
-                        is_native: false,
-                    });
-                }
-            }
-        }
-        
-        if let Some(native_xfers) = &tx.native_transfers {
-            for xfer in native_xfers {
-                if let Some(amount) = xfer.amount {
-                    if amount > 0 {
-                        transfers.push(TokenTransfer {
-                            mint: "So11111111111111111111111111111111111111112".to_string(),
-                            symbol: Some("SOL".to_string()),
-                            amount: amount as f64 / 1_000_000_000.0,
-                            from_account: xfer.from_user_account.clone(),
-                            to_account: xfer.to_user_account.clone(),
-                            is_native: true,
-                        });
-                    }
-                }
-            }
+/// Count DEX program invocations, including CPIs from routers
+fn count_dex_invocations(instructions: &[HeliusInstruction]) -> u32 {
+    instructions.iter()
+        .map(|ix| {
+            let own = ix.program_id.as_deref()
+                .is_some_and(|pid| DEX_PROGRAM_IDS.contains(&pid)) as u32;
+            own + ix.inner_instructions.as_deref().map_or(0, count_dex_invocations)
+        })
+        .sum()
+}
+
+/// True when the payer's first token leg out and last token leg in share a
+/// mint, with some other mint in between (A -> ... -> A)
+fn is_cyclic(transfers: &[TokenTransfer], payer: &str) -> bool {
+    let legs: Vec<&TokenTransfer> = transfers.iter()
+        .filter(|t| !t.is_native)
+        .collect();
+    let first_in = legs.iter().find(|t| t.from_account.as_deref() == Some(payer));
+    let last_out = legs.iter().rev().find(|t| t.to_account.as_deref() == Some(payer));
+    match (first_in, last_out) {
+        (Some(first), Some(last)) => {
+            first.mint == last.mint && legs.iter().any(|t| t.mint != first.mint)
         }
-        
-        results.push(ParsedTx {
-            signature: tx.signature.unwrap_or_default(),
-            tx_type: tx.tx_type.unwrap_or_else(|| "UNKNOWN".to_string()),
-            source: tx.source.unwrap_or_else(|| "UNKNOWN".to_string()),
-            token_transfers: transfers,
-            fee_payer: tx.fee_payer,
-            slot: tx.slot.unwrap_or(0),
-        });
+        _ => false,
     }
-    
-    Ok(results)
 }
 
+/// (mint, symbol, amount_usd, amount_sol, direction)
+type SwapSummary = (String, Option<String>, f64, f64, String);
+
+/// Quote mints for `extract_swap_token`; wSOL counts as a quote so
+/// SOL-quoted swaps report the token on the other side
+const SWAP_QUOTE_MINTS: &[&str] = &[USDC_MINT, USDT_MINT, SOL_MINT];
+
 /// Extract the primary token mint from a Helius response (fast path)
-/// Returns (mint, symbol, amount_usd) or None
+/// Returns (mint, symbol, amount_usd, amount_sol, direction) or None
+/// direction is "BUY"/"SELL" from the fee payer's view of the primary token,
+/// or "UNKNOWN" when the payer's net flow can't be determined.
 #[pyfunction]
-pub fn extract_swap_token(json_str: &str) -> PyResult<Option<(String, Option<String>, f64)>> {
-    const USDC_MINT: &str = "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v";
-    
+pub fn extract_swap_token(json_str: &str) -> PyResult<Option<SwapSummary>> {
     let parsed = parse_helius_tx(json_str)?;
     
     if let Some(tx) = parsed {
-        if let Some(primary) = tx.get_primary_token() {
+        if let Some(primary) = tx.primary_token(SWAP_QUOTE_MINTS) {
             // Find USDC transfer to get USD value
             let usdc_amount = tx.token_transfers.iter()
                 .find(|t| t.mint == USDC_MINT)
                 .map(|t| t.amount)
                 .unwrap_or(0.0);
+
+            let payer = tx.fee_payer.as_deref();
+            let net_primary: f64 = tx.token_transfers.iter()
+                .filter(|t| t.mint == primary.mint && !t.is_native)
+                .map(|t| signed_for(t, payer))
+                .sum();
+            let direction = if net_primary > 0.0 {
+                "BUY"
+            } else if net_primary < 0.0 {
+                "SELL"
+            } else {
+                "UNKNOWN"
+            };
+
+            // SOL paid for a buy, or received for a sell
+            let sol_amount: f64 = tx.token_transfers.iter()
+                .filter(|t| t.is_native)
+                .map(|t| signed_for(t, payer))
+                .filter(|flow| match direction {
+                    "BUY" => *flow < 0.0,
+                    "SELL" => *flow > 0.0,
+                    _ => false,
+                })
+                .map(f64::abs)
+                .sum();
             
-            return Ok(Some((primary.mint, primary.symbol, usdc_amount)));
+            return Ok(Some((
+                primary.mint,
+                primary.symbol,
+                usdc_amount,
+                sol_amount,
+                direction.to_string(),
+            )));
         }
     }
     
     Ok(None)
 }
 
+/// Transfer amount signed from `payer`'s view: positive in, negative out
+fn signed_for(transfer: &TokenTransfer, payer: Option<&str>) -> f64 {
+    let Some(payer) = payer else { return 0.0 };
+    let incoming = transfer.to_account.as_deref() == Some(payer);
+    let outgoing = transfer.from_account.as_deref() == Some(payer);
+    match (incoming, outgoing) {
+        (true, false) => transfer.amount,
+        (false, true) => -transfer.amount,
+        _ => 0.0,
+    }
+}
+
 // Module registration
 pub fn register_tx_parser_classes(m: &PyModule) -> PyResult<()> {
     m.add_class::<TokenTransfer>()?;
     m.add_class::<ParsedTx>()?;
     m.add_function(wrap_pyfunction!(parse_helius_tx, m)?)?;
     m.add_function(wrap_pyfunction!(parse_helius_tx_batch, m)?)?;
+    m.add_function(wrap_pyfunction!(parse_helius_tx_stream, m)?)?;
     m.add_function(wrap_pyfunction!(extract_swap_token, m)?)?;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PYUSD: &str = "2b1kV6DkPAnxd5ixfnxCpjxmKwqjjaYmCZfHsFu24GXo";
+    const BONK: &str = "DezXAZ8z7PnrnRJjz3wXBoRgixCa6xjnB7YaB1pPB263";
+    const PAYER: &str = "ArbBot1111111111111111111111111111111111111";
+    const POOL_1: &str = "Pool111111111111111111111111111111111111111";
+    const POOL_2: &str = "Pool222222222222222222222222222222222222222";
+
+    fn token_transfer(mint: &str, amount: f64, from: &str, to: &str) -> String {
+        format!(
+            r#"{{"mint":"{}","tokenAmount":{},"fromUserAccount":"{}","toUserAccount":"{}"}}"#,
+            mint, amount, from, to
+        )
+    }
+
+    /// Helius enhanced-transaction shape for a two-hop USDC -> BONK -> USDC
+    /// bundle routed through Jupiter into Raydium V4 and Orca
+    fn two_hop_arb_json() -> String {
+        format!(
+            r#"{{
+                "signature": "arbSig",
+                "type": "SWAP",
+                "source": "JUPITER",
+                "feePayer": "{payer}",
+                "slot": 250000000,
+                "tokenTransfers": [{t1}, {t2}, {t3}, {t4}],
+                "nativeTransfers": [],
+                "instructions": [
+                    {{"programId": "ComputeBudget111111111111111111111111111111", "innerInstructions": []}},
+                    {{"programId": "JUP6LkbZbjS1jKKwapdHNy74zcZ3tLUZoi5QNyVTaV4", "innerInstructions": [
+                        {{"programId": "675kPX9MHTjS2zt1qfr1NYHuzeLXfQM9H24wFSUt1Mp8"}},
+                        {{"programId": "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA"}},
+                        {{"programId": "whirLbMiicVdio4qvUfM5KAg6Ct8VwpYzGff3uctyCc"}},
+                        {{"programId": "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA"}}
+                    ]}}
+                ]
+            }}"#,
+            payer = PAYER,
+            t1 = token_transfer(USDC_MINT, 100.0, PAYER, POOL_1),
+            t2 = token_transfer(BONK, 5_000_000.0, POOL_1, PAYER),
+            t3 = token_transfer(BONK, 5_000_000.0, PAYER, POOL_2),
+            t4 = token_transfer(USDC_MINT, 100.4, POOL_2, PAYER),
+        )
+    }
+
+    #[test]
+    fn test_multi_swap_cycle_is_arbitrage() {
+        let tx = parse_helius_tx(&two_hop_arb_json()).unwrap().unwrap();
+        assert_eq!(tx.swap_count, 2);
+        assert!(tx.is_arbitrage);
+    }
+
+    #[test]
+    fn test_single_swap_is_not_arbitrage() {
+        let json = format!(
+            r#"{{"signature":"s","type":"SWAP","feePayer":"{}","tokenTransfers":[{},{}]}}"#,
+            PAYER,
+            token_transfer(USDC_MINT, 100.0, PAYER, POOL_1),
+            token_transfer(BONK, 5_000_000.0, POOL_1, PAYER),
+        );
+        let tx = parse_helius_tx(&json).unwrap().unwrap();
+        // No instruction data: falls back to counting payer input legs
+        assert_eq!(tx.swap_count, 1);
+        assert!(!tx.is_arbitrage);
+    }
+
+    #[test]
+    fn test_primary_token_with_custom_quote_mints() {
+        // PYUSD -> BONK: PYUSD is not a default quote mint
+        let json = format!(
+            r#"{{"signature":"s","type":"SWAP","feePayer":"{}","tokenTransfers":[{},{}]}}"#,
+            PAYER,
+            token_transfer(PYUSD, 100.0, PAYER, POOL_1),
+            token_transfer(BONK, 5_000_000.0, POOL_1, PAYER),
+        );
+        let tx = parse_helius_tx(&json).unwrap().unwrap();
+        assert_eq!(tx.get_primary_token(None).unwrap().mint, PYUSD);
+
+        let quotes = vec![USDC_MINT.to_string(), USDT_MINT.to_string(), PYUSD.to_string()];
+        assert_eq!(tx.get_primary_token(Some(quotes)).unwrap().mint, BONK);
+    }
+
+    #[test]
+    fn test_stream_skips_malformed_entries() {
+        let good = |sig: &str, slot: u64| {
+            format!(r#"{{"signature":"{}","type":"SWAP","slot":{}}}"#, sig, slot)
+        };
+        // Middle entries have the wrong shape: slot as a string, a bare number
+        let payload = format!(
+            r#"[{}, {{"signature":"bad","slot":"not-a-slot"}}, 42, {}]"#,
+            good("a", 1),
+            good("b", 2)
+        );
+        let txs = parse_helius_tx_stream(payload.as_bytes());
+        let sigs: Vec<(&str, u64)> = txs.iter().map(|t| (t.signature.as_str(), t.slot)).collect();
+        assert_eq!(sigs, [("a", 1), ("b", 2)]);
+
+        // A truncated payload keeps what parsed before the break
+        let truncated = format!(r#"[{}, {}, {{"signature":"#, good("a", 1), good("b", 2));
+        assert_eq!(parse_helius_tx_stream(truncated.as_bytes()).len(), 2);
+
+        // Newline-delimited objects
+        let ndjson = format!("{}\n{}\n", good("a", 1), good("b", 2));
+        assert_eq!(parse_helius_tx_stream(ndjson.as_bytes()).len(), 2);
+    }
+
+    #[test]
+    fn test_extract_swap_token_usdc_quoted_buy() {
+        let json = format!(
+            r#"{{"signature":"s","type":"SWAP","feePayer":"{}","tokenTransfers":[{},{}]}}"#,
+            PAYER,
+            token_transfer(USDC_MINT, 100.0, PAYER, POOL_1),
+            token_transfer(BONK, 5_000_000.0, POOL_1, PAYER),
+        );
+        let (mint, _, usd, sol, direction) = extract_swap_token(&json).unwrap().unwrap();
+        assert_eq!(mint, BONK);
+        assert_eq!(usd, 100.0);
+        assert_eq!(sol, 0.0);
+        assert_eq!(direction, "BUY");
+    }
+
+    #[test]
+    fn test_extract_swap_token_sol_quoted_sell() {
+        // BONK out of the payer, 0.5 SOL back; the 0.001 SOL tip is not notional
+        let json = format!(
+            r#"{{
+                "signature": "s",
+                "type": "SWAP",
+                "feePayer": "{payer}",
+                "tokenTransfers": [{bonk}],
+                "nativeTransfers": [
+                    {{"amount": 1000000, "fromUserAccount": "{payer}", "toUserAccount": "Tip1111111111111111111111111111111111111111"}},
+                    {{"amount": 500000000, "fromUserAccount": "{pool}", "toUserAccount": "{payer}"}}
+                ]
+            }}"#,
+            payer = PAYER,
+            pool = POOL_1,
+            bonk = token_transfer(BONK, 5_000_000.0, PAYER, POOL_1),
+        );
+        let (mint, _, usd, sol, direction) = extract_swap_token(&json).unwrap().unwrap();
+        assert_eq!(mint, BONK);
+        assert_eq!(usd, 0.0);
+        assert!((sol - 0.5).abs() < 1e-12);
+        assert_eq!(direction, "SELL");
+    }
+}
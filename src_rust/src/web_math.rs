@@ -1,3 +1,4 @@
+use std::collections::HashMap;
 use wasm_bindgen::prelude::*;
 
 /// V21.1: Web Math Core
@@ -41,6 +42,43 @@ pub fn estimate_compute_units(
     num_accounts: u32,
     num_signers: u32,
     safety_margin_percent: f64,
+) -> u32 {
+    estimate_compute_units_with_table(
+        ops,
+        num_accounts,
+        num_signers,
+        safety_margin_percent,
+        &HashMap::new(),
+    )
+}
+
+/// Built-in op -> CU costs ("Reality Parity" Mainnet averages, Q4 2024).
+pub fn default_op_cost(op: &str) -> f64 {
+    match op {
+        "transfer_sol" => 500.0,
+        "transfer_spl" => 4500.0,
+        "create_ata" => 25000.0,
+        "close_account" => 3000.0,
+        "memo" => 100.0,
+        "raydium_swap_v4" => 80000.0,
+        "raydium_swap_cpcc" => 120000.0,
+        "orca_whirlpool_swap" => 145000.0,
+        "meteora_dlmm_swap" => 70000.0,
+        "jupiter_aggregator" => 180000.0,
+        "phoenix_swap" => 25000.0,
+        _ => 10000.0,
+    }
+}
+
+/// Same as `estimate_compute_units`, but ops found in `cost_table` use the
+/// caller's calibrated cost. Anything missing falls back to the defaults.
+/// Not exported to WASM (wasm_bindgen has no HashMap support).
+pub fn estimate_compute_units_with_table(
+    ops: Vec<String>,
+    num_accounts: u32,
+    num_signers: u32,
+    safety_margin_percent: f64,
+    cost_table: &HashMap<String, f64>,
 ) -> u32 {
     let mut estimated_cu = 0.0;
 
@@ -52,20 +90,10 @@ pub fn estimate_compute_units(
 
     // 3. Instruction Simulation
     for op in ops {
-        let cost = match op.as_str() {
-            "transfer_sol" => 500.0,
-            "transfer_spl" => 4500.0,
-            "create_ata" => 25000.0,
-            "close_account" => 3000.0,
-            "memo" => 100.0,
-            "raydium_swap_v4" => 80000.0,
-            "raydium_swap_cpcc" => 120000.0,
-            "orca_whirlpool_swap" => 145000.0,
-            "meteora_dlmm_swap" => 70000.0,
-            "jupiter_aggregator" => 180000.0,
-            "phoenix_swap" => 25000.0,
-            _ => 10000.0,
-        };
+        let cost = cost_table
+            .get(&op)
+            .copied()
+            .unwrap_or_else(|| default_op_cost(&op));
         estimated_cu += cost;
     }
 
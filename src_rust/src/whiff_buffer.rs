@@ -13,6 +13,11 @@ use pyo3::prelude::*;
 use std::collections::{HashMap, VecDeque};
 use crate::log_parser::WhiffEvent;
 
+/// Default pressure multiplier applied per idle decay window
+const DEFAULT_DECAY_FACTOR: f32 = 0.9;
+/// Default idle time over which one decay factor is applied
+const DEFAULT_DECAY_WINDOW_MS: u64 = 30_000;
+
 /// Ring buffer for whiff events with burst collapse
 #[pyclass]
 pub struct WhiffBuffer {
@@ -20,6 +25,12 @@ pub struct WhiffBuffer {
     capacity: usize,
     // Pressure tracking per mint
     pressure_map: HashMap<String, PressureState>,
+    // Latest buffered event per mint, maintained on push/evict for collapse
+    latest_map: HashMap<String, LatestEntry>,
+    next_seq: u64,
+    // Pressure is multiplied by decay_factor per decay_window_ms of inactivity
+    decay_factor: f32,
+    decay_window_ms: u64,
 }
 
 /// Internal whiff event with timestamp
@@ -27,6 +38,18 @@ pub struct WhiffBuffer {
 struct WhiffEventInternal {
     event: WhiffEvent,
     timestamp_ms: u64,
+    /// Push sequence number; contiguous across the buffer, so
+    /// `seq - front.seq` is the event's index
+    seq: u64,
+}
+
+/// Newest buffered event for a single mint
+#[derive(Clone)]
+struct LatestEntry {
+    timestamp_ms: u64,
+    seq: u64,
+    /// Events for this mint still in the buffer
+    live: usize,
 }
 
 /// Pressure state for a single mint
@@ -37,31 +60,62 @@ struct PressureState {
     volatile: f32,
     event_count: u32,
     last_update_ms: u64,
+    /// Time up to which decay has already been applied
+    decayed_through_ms: u64,
 }
 
 #[pymethods]
 impl WhiffBuffer {
     #[new]
-    pub fn new(capacity: usize) -> Self {
-        WhiffBuffer {
+    #[pyo3(signature = (capacity, decay_factor=DEFAULT_DECAY_FACTOR, decay_window_ms=DEFAULT_DECAY_WINDOW_MS))]
+    pub fn new(capacity: usize, decay_factor: f32, decay_window_ms: u64) -> PyResult<Self> {
+        if !(decay_factor > 0.0 && decay_factor <= 1.0) {
+            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+                "decay_factor must be in (0, 1]",
+            ));
+        }
+        if decay_window_ms == 0 {
+            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+                "decay_window_ms must be positive",
+            ));
+        }
+        Ok(WhiffBuffer {
             buffer: VecDeque::with_capacity(capacity),
             capacity,
             pressure_map: HashMap::new(),
-        }
+            latest_map: HashMap::new(),
+            next_seq: 0,
+            decay_factor,
+            decay_window_ms,
+        })
     }
     
     /// Push a new whiff event into the buffer
     pub fn push(&mut self, event: WhiffEvent, timestamp_ms: u64) {
         // Update pressure tracking
-        self.update_pressure(&event);
+        self.update_pressure(&event, timestamp_ms);
         
         // Add to ring buffer
         if self.buffer.len() >= self.capacity {
-            self.buffer.pop_front();
+            self.evict_front();
+        }
+        let seq = self.next_seq;
+        self.next_seq += 1;
+
+        // Ties keep the earlier event, matching a front-to-back scan
+        let entry = self.latest_map
+            .entry(event.mint.clone())
+            .or_insert(LatestEntry { timestamp_ms, seq, live: 0 });
+        entry.live += 1;
+        if timestamp_ms > entry.timestamp_ms {
+            entry.timestamp_ms = timestamp_ms;
+            entry.seq = seq;
         }
+
         self.buffer.push_back(WhiffEventInternal { 
             event, 
             timestamp_ms,
+            seq,
         });
     }
     
@@ -69,22 +123,15 @@ impl WhiffBuffer {
     /// Returns only the most recent event per mint within the time window
     pub fn collapse(&mut self, window_ms: u64, current_time_ms: u64) -> Vec<WhiffEvent> {
         let cutoff = current_time_ms.saturating_sub(window_ms);
+        let front_seq = match self.buffer.front() {
+            Some(front) => front.seq,
+            None => return Vec::new(),
+        };
         
-        // Group by mint, keep latest
-        let mut latest_per_mint: HashMap<String, &WhiffEventInternal> = HashMap::new();
-        
-        for item in self.buffer.iter() {
-            if item.timestamp_ms >= cutoff {
-                let key = item.event.mint.clone();
-                match latest_per_mint.get(&key) {
-                    Some(existing) if existing.timestamp_ms >= item.timestamp_ms => {},
-                    _ => { latest_per_mint.insert(key, item); }
-                }
-            }
-        }
-        
-        latest_per_mint.values()
-            .map(|item| item.event.clone())
+        // Latest per mint is indexed, so only the mints need visiting
+        self.latest_map.values()
+            .filter(|entry| entry.timestamp_ms >= cutoff)
+            .map(|entry| self.buffer[(entry.seq - front_seq) as usize].event.clone())
             .collect()
     }
     
@@ -98,13 +145,20 @@ impl WhiffBuffer {
     
     /// Get aggregated market heat (0.0 - 1.0)
     pub fn get_market_heat(&self, mint: &str) -> f32 {
-        match self.pressure_map.get(mint) {
-            Some(state) => {
-                let raw = state.bullish + state.bearish + state.volatile;
-                raw.min(1.0)
-            },
-            None => 0.0,
-        }
+        self.pressure_map.get(mint).map_or(0.0, PressureState::heat)
+    }
+
+    /// Top `n` mints by market heat, hottest first
+    pub fn hottest_mints(&self, n: usize) -> Vec<(String, f32)> {
+        let mut ranked: Vec<(String, f32)> = self.pressure_map
+            .iter()
+            .map(|(mint, state)| (mint.clone(), state.heat()))
+            .filter(|(_, heat)| *heat > 0.0)
+            .collect();
+        // Ties broken by mint so the ranking is deterministic
+        ranked.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        ranked.truncate(n);
+        ranked
     }
     
     /// Get total pending events
@@ -121,6 +175,7 @@ impl WhiffBuffer {
     pub fn clear(&mut self) {
         self.buffer.clear();
         self.pressure_map.clear();
+        self.latest_map.clear();
     }
     
     /// Prune old events and decay pressure
@@ -130,29 +185,83 @@ impl WhiffBuffer {
         // Remove old events
         while let Some(front) = self.buffer.front() {
             if front.timestamp_ms < cutoff {
-                self.buffer.pop_front();
+                self.evict_front();
             } else {
                 break;
             }
         }
         
-        // Decay pressure for stale mints
-        let decay_cutoff = current_time_ms.saturating_sub(30_000); // 30 sec decay
+        self.decay_step(current_time_ms);
+    }
+
+    /// Decay every mint's pressure up to `current_time_ms`.
+    /// Pressure falls by `decay_factor` per `decay_window_ms` since the mint's
+    /// last update, so the result does not depend on how often this is called.
+    pub fn decay_step(&mut self, current_time_ms: u64) {
         for state in self.pressure_map.values_mut() {
-            if state.last_update_ms < decay_cutoff {
-                state.bullish *= 0.9;
-                state.bearish *= 0.9;
-                state.volatile *= 0.9;
-            }
+            state.decay_to(current_time_ms, self.decay_factor, self.decay_window_ms);
         }
     }
 }
 
+impl PressureState {
+    /// Aggregated heat, clamped to 1.0
+    fn heat(&self) -> f32 {
+        (self.bullish + self.bearish + self.volatile).min(1.0)
+    }
+
+    fn decay_to(&mut self, current_time_ms: u64, factor: f32, window_ms: u64) {
+        let from = self.decayed_through_ms.max(self.last_update_ms);
+        if current_time_ms <= from {
+            return;
+        }
+        let windows = (current_time_ms - from) as f32 / window_ms as f32;
+        let multiplier = factor.powf(windows);
+        self.bullish *= multiplier;
+        self.bearish *= multiplier;
+        self.volatile *= multiplier;
+        self.decayed_through_ms = current_time_ms;
+    }
+}
+
 impl WhiffBuffer {
-    fn update_pressure(&mut self, event: &WhiffEvent) {
+    /// Drop the oldest event, keeping `latest_map` in step
+    fn evict_front(&mut self) {
+        let Some(item) = self.buffer.pop_front() else {
+            return;
+        };
+        let mint = &item.event.mint;
+        let Some(entry) = self.latest_map.get_mut(mint) else {
+            return;
+        };
+
+        entry.live -= 1;
+        if entry.live == 0 {
+            self.latest_map.remove(mint);
+        } else if entry.seq == item.seq {
+            // The newest event was pushed out ahead of later, older-stamped
+            // ones (out-of-order timestamps); rescan just this mint.
+            let mut best: Option<&WhiffEventInternal> = None;
+            for other in self.buffer.iter().filter(|other| &other.event.mint == mint) {
+                match best {
+                    Some(existing) if existing.timestamp_ms >= other.timestamp_ms => {}
+                    _ => best = Some(other),
+                }
+            }
+            if let Some(best) = best {
+                entry.timestamp_ms = best.timestamp_ms;
+                entry.seq = best.seq;
+            }
+        }
+    }
+
+    fn update_pressure(&mut self, event: &WhiffEvent, timestamp_ms: u64) {
         let state = self.pressure_map
             .entry(event.mint.clone())
             .or_insert_with(PressureState::default);
+        // Bring existing pressure up to date before adding the new reading
+        state.decay_to(timestamp_ms, self.decay_factor, self.decay_window_ms);
+        state.last_update_ms = state.last_update_ms.max(timestamp_ms);
         
         let weight = event.confidence;
         
@@ -172,3 +281,148 @@ pub fn register_whiff_buffer_classes(m: &PyModule) -> PyResult<()> {
     m.add_class::<WhiffBuffer>()?;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn whiff(mint: &str, direction: &str, confidence: f32) -> WhiffEvent {
+        WhiffEvent {
+            whiff_type: "TEST".to_string(),
+            mint: mint.to_string(),
+            amount: 0,
+            confidence,
+            direction: direction.to_string(),
+            source: "test".to_string(),
+        }
+    }
+
+    /// The original full-scan collapse, kept as the reference behaviour
+    fn scan_collapse(buf: &WhiffBuffer, window_ms: u64, current_time_ms: u64) -> Vec<(String, u64)> {
+        let cutoff = current_time_ms.saturating_sub(window_ms);
+        let mut latest_per_mint: HashMap<String, &WhiffEventInternal> = HashMap::new();
+        for item in buf.buffer.iter() {
+            if item.timestamp_ms >= cutoff {
+                match latest_per_mint.get(&item.event.mint) {
+                    Some(existing) if existing.timestamp_ms >= item.timestamp_ms => {}
+                    _ => {
+                        latest_per_mint.insert(item.event.mint.clone(), item);
+                    }
+                }
+            }
+        }
+        let mut out: Vec<_> = latest_per_mint
+            .values()
+            .map(|item| (item.event.mint.clone(), item.event.amount))
+            .collect();
+        out.sort();
+        out
+    }
+
+    fn indexed_collapse(buf: &mut WhiffBuffer, window_ms: u64, current_time_ms: u64) -> Vec<(String, u64)> {
+        let mut out: Vec<_> = buf
+            .collapse(window_ms, current_time_ms)
+            .into_iter()
+            .map(|event| (event.mint, event.amount))
+            .collect();
+        out.sort();
+        out
+    }
+
+    #[test]
+    fn test_indexed_collapse_matches_full_scan() {
+        // Small capacity forces evictions; jittered timestamps arrive out of order
+        let mut buf = WhiffBuffer::new(16, DEFAULT_DECAY_FACTOR, DEFAULT_DECAY_WINDOW_MS).unwrap();
+        let mints = ["A", "B", "C", "D", "E"];
+        let mut now = 1_000u64;
+
+        for i in 0..500u64 {
+            now += 7;
+            let mint = mints[((i * 7 + i / 3) % mints.len() as u64) as usize];
+            let jitter = (i * 13) % 40;
+            let mut event = whiff(mint, "BULLISH", 0.5);
+            // Amount identifies which event survived the collapse
+            event.amount = i;
+            buf.push(event, now - jitter);
+
+            if i % 25 == 0 {
+                buf.prune(60, now);
+            }
+            for window in [0, 15, 50, 10_000] {
+                assert_eq!(
+                    indexed_collapse(&mut buf, window, now),
+                    scan_collapse(&buf, window, now),
+                    "diverged at event {} window {}",
+                    i,
+                    window
+                );
+            }
+        }
+
+        buf.prune(0, now + 1_000);
+        assert!(buf.is_empty());
+        assert!(buf.collapse(10_000, now).is_empty());
+    }
+
+    #[test]
+    fn test_decay_step_drives_pressure_to_zero() {
+        let mut buf = WhiffBuffer::new(64, 0.5, 1_000).unwrap();
+        buf.push(whiff("A", "BULLISH", 1.0), 10_000);
+        buf.push(whiff("A", "BEARISH", 1.0), 10_000);
+        let (bull, bear, _) = buf.get_pressure("A");
+        assert!((bull - 0.3).abs() < 1e-6 && (bear - 0.3).abs() < 1e-6);
+
+        // One window halves it, regardless of how many steps it is split into
+        buf.decay_step(10_500);
+        buf.decay_step(11_000);
+        let (bull, _, _) = buf.get_pressure("A");
+        assert!((bull - 0.15).abs() < 1e-6, "got {}", bull);
+
+        let mut prev = bull;
+        for t in (12_000..=40_000).step_by(1_000) {
+            buf.decay_step(t);
+            let (bull, bear, _) = buf.get_pressure("A");
+            assert!(bull < prev && bear <= bull + 1e-6);
+            prev = bull;
+        }
+        assert!(buf.get_market_heat("A") < 1e-6);
+
+        // Fresh events are not decayed retroactively
+        buf.push(whiff("B", "VOLATILE", 1.0), 40_000);
+        buf.decay_step(40_000);
+        assert!((buf.get_pressure("B").2 - 0.3).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_decay_parameters_are_validated() {
+        assert!(WhiffBuffer::new(8, 0.0, 1_000).is_err());
+        assert!(WhiffBuffer::new(8, 1.5, 1_000).is_err());
+        assert!(WhiffBuffer::new(8, 0.9, 0).is_err());
+        assert!(WhiffBuffer::new(8, 1.0, 1).is_ok());
+    }
+
+    #[test]
+    fn test_hottest_mints_ranks_by_heat() {
+        let mut buf = WhiffBuffer::new(64, DEFAULT_DECAY_FACTOR, DEFAULT_DECAY_WINDOW_MS).unwrap();
+        // COLD: 0.06, WARM: 0.3, HOT: 0.9, MAXED: clamps at 1.0
+        buf.push(whiff("COLD", "BULLISH", 0.2), 1_000);
+        buf.push(whiff("WARM", "BEARISH", 1.0), 1_000);
+        for direction in ["BULLISH", "BEARISH", "VOLATILE"] {
+            buf.push(whiff("HOT", direction, 1.0), 1_000);
+        }
+        for _ in 0..3 {
+            for direction in ["BULLISH", "BEARISH"] {
+                buf.push(whiff("MAXED", direction, 1.0), 1_000);
+            }
+        }
+
+        let top = buf.hottest_mints(3);
+        let names: Vec<&str> = top.iter().map(|(mint, _)| mint.as_str()).collect();
+        assert_eq!(names, ["MAXED", "HOT", "WARM"]);
+        assert_eq!(top[0].1, 1.0);
+        assert!((top[1].1 - 0.9).abs() < 1e-6);
+
+        assert_eq!(buf.hottest_mints(10).len(), 4);
+        assert!(buf.hottest_mints(0).is_empty());
+    }
+}
@@ -14,9 +14,15 @@ use tokio::runtime::Runtime;
 // use tokio::sync::mpsc; // Removed unused import
 use crossbeam_channel::{bounded, Receiver, Sender};
 use futures_util::{SinkExt, StreamExt};
-use std::collections::{HashSet, VecDeque};
+use base64::{engine::general_purpose, Engine as _};
+use std::collections::{HashMap, VecDeque};
+use std::hash::Hash;
 use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
-use std::sync::Arc;
+use std::sync::{Arc, OnceLock};
+use std::time::{Duration, Instant};
+use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+use tokio_tungstenite::tungstenite::handshake::client::Request;
+use tokio_tungstenite::tungstenite::http::{HeaderName, HeaderValue};
 use tokio_tungstenite::{connect_async, tungstenite::Message};
 // use serde::{Deserialize, Serialize}; // Removed unused import causing build error
 use serde_json::json;
@@ -47,8 +53,16 @@ pub struct WssEvent {
     pub signature: String,
     #[pyo3(get)]
     pub logs: Vec<String>,
+    /// Lag behind the first provider that delivered this signature (0 for the winner)
     #[pyo3(get)]
     pub latency_ms: f64,
+    /// Monotonic receive timestamp (nanoseconds since process start)
+    #[pyo3(get)]
+    pub received_ns: u64,
+    /// True when this re-delivers an already forwarded signature at a higher
+    /// slot (slot-aware dedup only); it supersedes the earlier event
+    #[pyo3(get)]
+    pub is_correction: bool,
 }
 
 #[pymethods]
@@ -63,6 +77,69 @@ impl WssEvent {
     }
 }
 
+/// Account update (accountSubscribe) ready for Python consumption
+#[pyclass]
+#[derive(Clone, Debug)]
+pub struct WssAccountEvent {
+    #[pyo3(get)]
+    pub provider: String,
+    #[pyo3(get)]
+    pub pubkey: String,
+    #[pyo3(get)]
+    pub slot: u64,
+    /// Raw account data (base64-decoded)
+    #[pyo3(get)]
+    pub data: Vec<u8>,
+    /// Lag behind the first provider that delivered this (pubkey, slot)
+    #[pyo3(get)]
+    pub latency_ms: f64,
+    /// Monotonic receive timestamp (nanoseconds since process start)
+    #[pyo3(get)]
+    pub received_ns: u64,
+}
+
+#[pymethods]
+impl WssAccountEvent {
+    fn __repr__(&self) -> String {
+        format!(
+            "WssAccountEvent(provider={}, slot={}, pubkey={}..., {} bytes)",
+            self.provider,
+            self.slot,
+            &self.pubkey[..8.min(self.pubkey.len())],
+            self.data.len()
+        )
+    }
+}
+
+/// Anything a connection task hands to the aggregator loop
+#[derive(Debug, Clone)]
+enum RawEvent {
+    Log(WssEvent),
+    Account(WssAccountEvent),
+}
+
+/// What each connection subscribes to
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum SubscriptionMode {
+    /// logsSubscribe with `mentions: [program_id]`
+    Logs,
+    /// accountSubscribe (base64) per account pubkey
+    Accounts,
+}
+
+impl SubscriptionMode {
+    fn parse(mode: &str) -> PyResult<Self> {
+        match mode {
+            "logs" => Ok(Self::Logs),
+            "accounts" => Ok(Self::Accounts),
+            other => Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                "Unknown subscription_mode '{}' (expected 'logs' or 'accounts')",
+                other
+            ))),
+        }
+    }
+}
+
 /// Statistics for monitoring
 #[pyclass]
 #[derive(Clone, Debug, Default)]
@@ -75,10 +152,62 @@ pub struct WssStats {
     pub messages_accepted: u64,
     #[pyo3(get)]
     pub messages_dropped: u64,
+    /// Average lag of duplicate arrivals behind the winning provider
     #[pyo3(get)]
     pub avg_latency_ms: f64,
 }
 
+/// Per-provider race outcomes, shared between the aggregator loop and Python.
+#[derive(Default)]
+struct ProviderHealth {
+    names: Vec<String>,
+    index: HashMap<String, usize>,
+    won: Vec<AtomicU64>,
+    duplicates: Vec<AtomicU64>,
+}
+
+impl ProviderHealth {
+    fn new(names: Vec<String>) -> Self {
+        let index = names.iter().enumerate().map(|(i, n)| (n.clone(), i)).collect();
+        Self {
+            won: names.iter().map(|_| AtomicU64::new(0)).collect(),
+            duplicates: names.iter().map(|_| AtomicU64::new(0)).collect(),
+            names,
+            index,
+        }
+    }
+
+    /// Attribute an accepted event (`won = true`) or a duplicate to its provider.
+    fn record(&self, provider: &str, won: bool) {
+        if let Some(&i) = self.index.get(provider) {
+            let counter = if won { &self.won[i] } else { &self.duplicates[i] };
+            counter.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    /// (provider, messages_won, messages_duplicate, win_rate)
+    fn snapshot(&self) -> Vec<(String, u64, u64, f64)> {
+        self.names
+            .iter()
+            .enumerate()
+            .map(|(i, name)| {
+                let won = self.won[i].load(Ordering::Relaxed);
+                let duplicates = self.duplicates[i].load(Ordering::Relaxed);
+                let total = won + duplicates;
+                let win_rate = if total == 0 { 0.0 } else { won as f64 / total as f64 };
+                (name.clone(), won, duplicates, win_rate)
+            })
+            .collect()
+    }
+}
+
+/// Monotonic nanoseconds since the first call in this process.
+/// Comparable across connection tasks, unlike wall-clock time.
+fn monotonic_ns() -> u64 {
+    static START: OnceLock<Instant> = OnceLock::new();
+    START.get_or_init(Instant::now).elapsed().as_nanos() as u64
+}
+
 // ============================================================================
 // WSS AGGREGATOR
 // ============================================================================
@@ -91,9 +220,13 @@ pub struct WssAggregator {
     event_rx: Option<Receiver<WssEvent>>,
     event_tx: Option<Sender<WssEvent>>, // Added back to store the sender for the aggregator loop
 
+    /// Account event channel (Rust → Python), used in "accounts" mode
+    account_rx: Option<Receiver<WssAccountEvent>>,
+    account_tx: Option<Sender<WssAccountEvent>>,
+
     /// Internal raw channel (Providers → Aggregator Thread)
-    raw_tx: Option<Sender<WssEvent>>,
-    raw_rx: Option<Receiver<WssEvent>>,
+    raw_tx: Option<Sender<RawEvent>>,
+    raw_rx: Option<Receiver<RawEvent>>,
 
     /// Control flag for shutdown
     running: Arc<AtomicBool>,
@@ -103,6 +236,17 @@ pub struct WssAggregator {
     msg_accepted: Arc<AtomicU64>,
     msg_dropped: Arc<AtomicU64>,
     active_conns: Arc<AtomicU64>,
+    latency_sum_us: Arc<AtomicU64>,
+    latency_samples: Arc<AtomicU64>,
+
+    /// Dedup window: how many recent signatures/slots are remembered
+    max_history: usize,
+
+    /// Forward higher-slot repeats of a signature as corrections
+    slot_aware_dedup: bool,
+
+    /// Wins/duplicates per provider (populated on start)
+    provider_health: Arc<ProviderHealth>,
 
     /// Tokio runtime (owned)
     runtime: Option<Runtime>,
@@ -110,12 +254,22 @@ pub struct WssAggregator {
 
 #[pymethods]
 impl WssAggregator {
+    /// * `slot_aware_dedup` - Log dedup normally drops every repeat of a
+    ///   signature. When set, a repeat at a higher slot than the one already
+    ///   forwarded (the transaction landed again on another fork) is forwarded
+    ///   too, with `is_correction=True`, so the later-confirmed version wins.
+    ///   The tradeoff: a signature may reach Python more than once, and
+    ///   consumers must let a correction supersede the earlier event; repeats
+    ///   at the same or a lower slot are still dropped.
     #[new]
-    #[pyo3(signature = (channel_size=1000))]
-    pub fn new(channel_size: usize) -> PyResult<Self> {
+    #[pyo3(signature = (channel_size=1000, max_history=2000, slot_aware_dedup=false))]
+    pub fn new(channel_size: usize, max_history: usize, slot_aware_dedup: bool) -> PyResult<Self> {
         // Channel for Python (Processed/Deduped events)
         let (tx, rx) = bounded(channel_size);
 
+        // Channel for account updates (Processed/Deduped)
+        let (account_tx, account_rx) = bounded(channel_size);
+
         // Channel for Raw events (Multiple Providers -> Aggregator)
         let (raw_tx, raw_rx) = bounded(channel_size * 2);
 
@@ -133,6 +287,8 @@ impl WssAggregator {
         Ok(Self {
             event_rx: Some(rx),
             event_tx: Some(tx), // Storing the sender for the aggregator loop
+            account_rx: Some(account_rx),
+            account_tx: Some(account_tx),
             raw_tx: Some(raw_tx),
             raw_rx: Some(raw_rx),
             // We need to store the final_tx temporarily to pass it to the aggregator loop
@@ -146,6 +302,11 @@ impl WssAggregator {
             msg_accepted: Arc::new(AtomicU64::new(0)),
             msg_dropped: Arc::new(AtomicU64::new(0)),
             active_conns: Arc::new(AtomicU64::new(0)),
+            latency_sum_us: Arc::new(AtomicU64::new(0)),
+            latency_samples: Arc::new(AtomicU64::new(0)),
+            max_history,
+            slot_aware_dedup,
+            provider_health: Arc::new(ProviderHealth::default()),
             runtime: None,
         })
     }
@@ -154,15 +315,22 @@ impl WssAggregator {
     ///
     /// # Arguments
     /// * `endpoints` - List of WSS URLs (e.g., ["wss://mainnet.helius-rpc.com/?api-key=xxx"])
-    /// * `program_ids` - List of program IDs to subscribe to (e.g., Raydium, Orca)
+    /// * `program_ids` - List of program IDs to subscribe to (e.g., Raydium, Orca).
+    ///   In "accounts" mode these are the account pubkeys (e.g., pool vaults).
     /// * `commitment` - Commitment level ("processed", "confirmed", "finalized")
-    #[pyo3(signature = (endpoints, program_ids, commitment="processed", log_filters=None))]
+    /// * `subscription_mode` - "logs" (logsSubscribe) or "accounts" (accountSubscribe)
+    /// * `headers` - Extra handshake headers per endpoint, parallel to `endpoints`
+    ///   (e.g., `[[("x-api-key", "xxx")], []]` for an authenticated Triton node)
+    #[pyo3(signature = (endpoints, program_ids, commitment="processed", log_filters=None, subscription_mode="logs", headers=None))]
+    #[allow(clippy::too_many_arguments)]
     pub fn start(
         &mut self,
         endpoints: Vec<String>,
         program_ids: Vec<String>,
         commitment: &str,
         log_filters: Option<Vec<String>>,
+        subscription_mode: &str,
+        headers: Option<Vec<Vec<(String, String)>>>,
     ) -> PyResult<()> {
         if self.running.load(Ordering::SeqCst) {
             return Err(PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(
@@ -170,6 +338,22 @@ impl WssAggregator {
             ));
         }
 
+        let mut headers = headers.unwrap_or_else(|| vec![Vec::new(); endpoints.len()]);
+        if headers.len() != endpoints.len() {
+            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                "headers has {} entries for {} endpoints",
+                headers.len(),
+                endpoints.len()
+            )));
+        }
+        // Surface bad header names/values now rather than in a reconnect loop
+        for (endpoint, endpoint_headers) in endpoints.iter().zip(&headers) {
+            build_ws_request(endpoint, endpoint_headers)
+                .map_err(|e| PyErr::new::<pyo3::exceptions::PyValueError, _>(e.to_string()))?;
+        }
+
+        let mode = SubscriptionMode::parse(subscription_mode)?;
+
         // Create Tokio runtime
         let runtime = Runtime::new()
             .map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(e.to_string()))?;
@@ -183,6 +367,7 @@ impl WssAggregator {
 
         // Aggregator -> Python
         let event_tx = self.event_tx.take().unwrap(); // Get sender to Python
+        let account_tx = self.account_tx.take().unwrap();
 
         // Clone shared state for threads
         let running_arc = self.running.clone();
@@ -192,17 +377,26 @@ impl WssAggregator {
         let active_conns_arc = self.active_conns.clone();
         let commitment_str = commitment.to_string();
 
+        let provider_names = (0..endpoints.len()).map(|idx| format!("provider_{}", idx)).collect();
+        self.provider_health = Arc::new(ProviderHealth::new(provider_names));
+
         // 2. Spawn Aggregator Loop
         runtime.spawn(run_aggregator(
             raw_rx,
             event_tx,
+            account_tx,
             running_arc.clone(),
             msg_accepted_arc.clone(),
             msg_dropped_arc.clone(),
+            self.latency_sum_us.clone(),
+            self.latency_samples.clone(),
+            self.max_history,
+            self.slot_aware_dedup,
+            self.provider_health.clone(),
         ));
 
         // 3. Spawn Connection Tasks
-        for (idx, endpoint) in endpoints.into_iter().enumerate() {
+        for (idx, (endpoint, endpoint_headers)) in endpoints.into_iter().zip(headers.drain(..)).enumerate() {
             let provider_raw_tx = raw_tx.clone(); // Each provider gets a sender to the raw channel
             let running_conn = running_arc.clone();
             let msg_received_conn = msg_received_arc.clone();
@@ -215,7 +409,9 @@ impl WssAggregator {
             runtime.spawn(async move {
                 run_connection(
                     endpoint,
+                    endpoint_headers,
                     provider_name,
+                    mode,
                     program_ids_conn,
                     commitment_conn,
                     log_filters_conn,
@@ -267,6 +463,35 @@ impl WssAggregator {
         events
     }
 
+    /// Wait up to `timeout_ms` for the next event (blocking).
+    /// Releases the GIL while waiting. Returns None on timeout.
+    pub fn recv_event_timeout(&self, py: Python<'_>, timeout_ms: u64) -> Option<WssEvent> {
+        let rx = self.event_rx.clone()?;
+        py.allow_threads(move || rx.recv_timeout(Duration::from_millis(timeout_ms)).ok())
+    }
+
+    /// Poll for the next account update (non-blocking, "accounts" mode).
+    /// Returns None if no update is available.
+    pub fn poll_account_event(&self) -> Option<WssAccountEvent> {
+        self.account_rx.as_ref()?.try_recv().ok()
+    }
+
+    /// Poll for multiple account updates (non-blocking, "accounts" mode).
+    /// Returns up to `max_count` updates.
+    #[pyo3(signature = (max_count=100))]
+    pub fn poll_account_events(&self, max_count: usize) -> Vec<WssAccountEvent> {
+        let mut events = Vec::with_capacity(max_count);
+        if let Some(rx) = &self.account_rx {
+            while events.len() < max_count {
+                match rx.try_recv() {
+                    Ok(event) => events.push(event),
+                    Err(_) => break,
+                }
+            }
+        }
+        events
+    }
+
     /// Check if the aggregator is running.
     pub fn is_running(&self) -> bool {
         self.running.load(Ordering::SeqCst)
@@ -274,18 +499,33 @@ impl WssAggregator {
 
     /// Get current statistics.
     pub fn get_stats(&self) -> WssStats {
+        let samples = self.latency_samples.load(Ordering::Relaxed);
+        let avg_latency_ms = if samples == 0 {
+            0.0
+        } else {
+            self.latency_sum_us.load(Ordering::Relaxed) as f64 / samples as f64 / 1000.0
+        };
+
         WssStats {
             active_connections: self.active_conns.load(Ordering::Relaxed),
             messages_received: self.msg_received.load(Ordering::Relaxed),
             messages_accepted: self.msg_accepted.load(Ordering::Relaxed),
             messages_dropped: self.msg_dropped.load(Ordering::Relaxed),
-            avg_latency_ms: 0.0, // TODO: track latency
+            avg_latency_ms,
         }
     }
 
+    /// Per-provider race results: (provider, messages_won, messages_duplicate, win_rate).
+    /// A provider that is rarely first is a candidate for replacement.
+    pub fn get_provider_health(&self) -> Vec<(String, u64, u64, f64)> {
+        self.provider_health.snapshot()
+    }
+
     /// Get pending event count.
     pub fn pending_count(&self) -> usize {
-        self.event_rx.as_ref().map(|rx| rx.len()).unwrap_or(0)
+        let logs = self.event_rx.as_ref().map(|rx| rx.len()).unwrap_or(0);
+        let accounts = self.account_rx.as_ref().map(|rx| rx.len()).unwrap_or(0);
+        logs + accounts
     }
 }
 
@@ -293,17 +533,92 @@ impl WssAggregator {
 // AGGREGATOR LOOP (RACE-TO-FIRST LOGIC)
 // ============================================================================
 
+/// First-arrival tracker for the race.
+/// Remembers when each key (signature, or (pubkey, slot) for accounts) was
+/// first seen so later duplicates can report how far behind their provider was.
+struct RaceTracker<K> {
+    /// key -> (first arrival ns, highest slot forwarded)
+    first_seen_ns: HashMap<K, (u64, u64)>,
+    order: VecDeque<K>,
+    max_history: usize,
+}
+
+/// Outcome of a slot-aware observation
+#[derive(Debug, PartialEq)]
+enum Arrival {
+    /// First time this key was seen
+    First,
+    /// Repeat at the same or a lower slot; lag (ms) behind the first arrival
+    Duplicate(f64),
+    /// Repeat at a higher slot than previously forwarded; lag (ms) behind
+    /// the first arrival
+    Newer(f64),
+}
+
+impl<K: Hash + Eq + Clone> RaceTracker<K> {
+    fn new(max_history: usize) -> Self {
+        Self {
+            first_seen_ns: HashMap::new(),
+            order: VecDeque::new(),
+            max_history,
+        }
+    }
+
+    /// Returns None on the first arrival of `key`, otherwise the lag (ms)
+    /// behind that first arrival.
+    fn observe(&mut self, key: K, received_ns: u64) -> Option<f64> {
+        match self.observe_at_slot(key, 0, received_ns) {
+            Arrival::First => None,
+            Arrival::Duplicate(lag_ms) | Arrival::Newer(lag_ms) => Some(lag_ms),
+        }
+    }
+
+    /// Like `observe`, but a repeat at a higher slot than the last one
+    /// forwarded is reported as `Arrival::Newer` and becomes the new high mark.
+    fn observe_at_slot(&mut self, key: K, slot: u64, received_ns: u64) -> Arrival {
+        if let Some((first_ns, best_slot)) = self.first_seen_ns.get_mut(&key) {
+            let lag_ms = received_ns.saturating_sub(*first_ns) as f64 / 1_000_000.0;
+            if slot > *best_slot {
+                *best_slot = slot;
+                return Arrival::Newer(lag_ms);
+            }
+            return Arrival::Duplicate(lag_ms);
+        }
+
+        // Mark seen
+        self.first_seen_ns.insert(key.clone(), (received_ns, slot));
+        self.order.push_back(key);
+
+        // Cleanup history (oldest first) until back under the cap
+        while self.first_seen_ns.len() > self.max_history {
+            match self.order.pop_front() {
+                Some(old_key) => {
+                    self.first_seen_ns.remove(&old_key);
+                }
+                None => break,
+            }
+        }
+
+        Arrival::First
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
 async fn run_aggregator(
-    raw_rx: Receiver<WssEvent>,
+    raw_rx: Receiver<RawEvent>,
     event_tx: Sender<WssEvent>,
+    account_tx: Sender<WssAccountEvent>,
     running: Arc<AtomicBool>,
     msg_accepted: Arc<AtomicU64>,
     msg_dropped: Arc<AtomicU64>,
-    // TODO: Add metrics sender?
+    latency_sum_us: Arc<AtomicU64>,
+    latency_samples: Arc<AtomicU64>,
+    max_history: usize,
+    slot_aware_dedup: bool,
+    provider_health: Arc<ProviderHealth>,
 ) {
-    let mut seen_signatures: HashSet<String> = HashSet::new();
-    let mut signature_order: VecDeque<String> = VecDeque::new();
-    const MAX_HISTORY: usize = 2000;
+    let mut signatures: RaceTracker<String> = RaceTracker::new(max_history);
+    let mut account_slots: RaceTracker<(String, u64)> = RaceTracker::new(max_history);
 
     // We check raw_rx in a blocking loop?
     // No, this is an async function, better to spawn a blocking thread OR use blocking iterator inside spawn_blocking?
@@ -324,41 +639,50 @@ async fn run_aggregator(
     while running.load(Ordering::SeqCst) {
         // Drain currently available messages
         loop {
-            match raw_rx.try_recv() {
-                Ok(event) => {
-                    // DEDUPLICATION (Race-to-First)
-                    if seen_signatures.contains(&event.signature) {
-                        msg_dropped.fetch_add(1, Ordering::Relaxed);
-                        continue; // Drop duplicate
-                    }
-
-                    // Mark seen
-                    seen_signatures.insert(event.signature.clone());
-                    signature_order.push_back(event.signature.clone());
-
-                    // Cleanup history
-                    if float_cleanup_needed(&seen_signatures, MAX_HISTORY) {
-                        if let Some(old_sig) = signature_order.pop_front() {
-                            seen_signatures.remove(&old_sig);
-                        }
-                    }
+            let event = match raw_rx.try_recv() {
+                Ok(event) => event,
+                Err(_) => break, // Empty or Disconnected
+            };
+
+            // DEDUPLICATION (Race-to-First)
+            // Account keys already include the slot, so only logs need the
+            // slot-aware path
+            let (provider, arrival) = match &event {
+                RawEvent::Log(e) if slot_aware_dedup => {
+                    (&e.provider, signatures.observe_at_slot(e.signature.clone(), e.slot, e.received_ns))
+                }
+                RawEvent::Log(e) => (&e.provider, signatures.observe(e.signature.clone(), e.received_ns).map_or(Arrival::First, Arrival::Duplicate)),
+                RawEvent::Account(e) => (&e.provider, account_slots.observe((e.pubkey.clone(), e.slot), e.received_ns).map_or(Arrival::First, Arrival::Duplicate)),
+            };
+            provider_health.record(provider, arrival == Arrival::First);
+
+            if let Arrival::Duplicate(lag_ms) = arrival {
+                // Record how far behind the winner this provider was
+                latency_sum_us.fetch_add((lag_ms * 1000.0) as u64, Ordering::Relaxed);
+                latency_samples.fetch_add(1, Ordering::Relaxed);
+                msg_dropped.fetch_add(1, Ordering::Relaxed);
+                continue; // Drop duplicate
+            }
 
-                    // Forward to Python
-                    match event_tx.send(event) {
-                        Ok(_) => {
-                            msg_accepted.fetch_add(1, Ordering::Relaxed);
-                        }
-                        Err(_) => {
-                            // Channel closed (Python stopped?)
-                            msg_dropped.fetch_add(1, Ordering::Relaxed);
-                            // If python closed, maybe we should stop?
-                        }
+            // Forward to Python (winner has zero lag by definition; a
+            // correction carries its lag behind the superseded event)
+            let sent = match event {
+                RawEvent::Log(mut e) => {
+                    if let Arrival::Newer(lag_ms) = arrival {
+                        e.latency_ms = lag_ms;
+                        e.is_correction = true;
                     }
+                    event_tx.send(e).is_ok()
                 }
-                Err(_) => {
-                    // Empty or Disconnected
-                    break;
-                }
+                RawEvent::Account(e) => account_tx.send(e).is_ok(),
+            };
+
+            if sent {
+                msg_accepted.fetch_add(1, Ordering::Relaxed);
+            } else {
+                // Channel closed (Python stopped?)
+                msg_dropped.fetch_add(1, Ordering::Relaxed);
+                // If python closed, maybe we should stop?
             }
         }
 
@@ -366,21 +690,20 @@ async fn run_aggregator(
     }
 }
 
-fn float_cleanup_needed(set: &HashSet<String>, max: usize) -> bool {
-    set.len() > max
-}
-
 // ============================================================================
 // CONNECTION LOGIC
 // ============================================================================
 
+#[allow(clippy::too_many_arguments)]
 async fn run_connection(
     endpoint: String,
+    headers: Vec<(String, String)>,
     provider_name: String,
+    mode: SubscriptionMode,
     program_ids: Vec<String>,
     commitment: String,
     log_filters: Option<Vec<String>>,
-    tx: Sender<WssEvent>, // Raw TX
+    tx: Sender<RawEvent>, // Raw TX
     running: Arc<AtomicBool>,
     msg_received: Arc<AtomicU64>,
     active_conns: Arc<AtomicU64>,
@@ -391,7 +714,9 @@ async fn run_connection(
     while running.load(Ordering::SeqCst) {
         match connect_and_subscribe(
             &endpoint,
+            &headers,
             &provider_name,
+            mode,
             &program_ids,
             &commitment,
             &log_filters,
@@ -416,39 +741,79 @@ async fn run_connection(
     }
 }
 
+/// Handshake request for `endpoint` with extra headers (e.g., Authorization, x-api-key)
+fn build_ws_request(
+    endpoint: &str,
+    headers: &[(String, String)],
+) -> Result<Request, Box<dyn std::error::Error + Send + Sync>> {
+    let mut request = url::Url::parse(endpoint)?.into_client_request()?;
+    for (name, value) in headers {
+        request
+            .headers_mut()
+            .insert(HeaderName::from_bytes(name.as_bytes())?, HeaderValue::from_str(value)?);
+    }
+    Ok(request)
+}
+
+#[allow(clippy::too_many_arguments)]
 async fn connect_and_subscribe(
     endpoint: &str,
+    headers: &[(String, String)],
     provider_name: &str,
+    mode: SubscriptionMode,
     program_ids: &[String],
     commitment: &str,
     log_filters: &Option<Vec<String>>,
-    tx: &Sender<WssEvent>,
+    tx: &Sender<RawEvent>,
     running: &Arc<AtomicBool>,
     msg_received: &Arc<AtomicU64>,
     active_conns: &Arc<AtomicU64>,
 ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
     // Connect
-    let url = url::Url::parse(endpoint)?;
-    let (ws_stream, _) = connect_async(url).await?;
+    let request = build_ws_request(endpoint, headers)?;
+    let (ws_stream, _) = connect_async(request).await?;
     let (mut write, mut read) = ws_stream.split();
 
     active_conns.fetch_add(1, Ordering::Relaxed);
 
-    // Subscribe to logsSubscribe for each program
+    // accountNotification carries only the subscription id, so remember
+    // which pubkey each request id was for until the server acks it
+    let mut pending_accounts: HashMap<u64, String> = HashMap::new();
+    let mut account_subscriptions: HashMap<u64, String> = HashMap::new();
+
+    // Subscribe to logsSubscribe / accountSubscribe for each target
     for (idx, program_id) in program_ids.iter().enumerate() {
-        let sub_msg = json!({
-            "jsonrpc": "2.0",
-            "id": idx + 1,
-            "method": "logsSubscribe",
-            "params": [
-                {
-                    "mentions": [program_id]
-                },
-                {
-                    "commitment": commitment
-                }
-            ]
-        });
+        let request_id = (idx + 1) as u64;
+        let sub_msg = match mode {
+            SubscriptionMode::Logs => json!({
+                "jsonrpc": "2.0",
+                "id": request_id,
+                "method": "logsSubscribe",
+                "params": [
+                    {
+                        "mentions": [program_id]
+                    },
+                    {
+                        "commitment": commitment
+                    }
+                ]
+            }),
+            SubscriptionMode::Accounts => {
+                pending_accounts.insert(request_id, program_id.clone());
+                json!({
+                    "jsonrpc": "2.0",
+                    "id": request_id,
+                    "method": "accountSubscribe",
+                    "params": [
+                        program_id,
+                        {
+                            "encoding": "base64",
+                            "commitment": commitment
+                        }
+                    ]
+                })
+            }
+        };
 
         write.send(Message::Text(sub_msg.to_string())).await?;
     }
@@ -463,8 +828,28 @@ async fn connect_and_subscribe(
                 // It's better to verify it IS a log notification before sending.
                 // So parsing stays here.
 
+                // Stamp receive time before parsing so it reflects arrival
+                let received_ns = monotonic_ns();
+
                 // Parse the message
-                if let Some(event) = parse_log_notification(&text, provider_name, log_filters) {
+                let event = match mode {
+                    SubscriptionMode::Logs => {
+                        parse_log_notification(&text, provider_name, log_filters, received_ns)
+                            .map(RawEvent::Log)
+                    }
+                    SubscriptionMode::Accounts => {
+                        if let Some((request_id, subscription_id)) = parse_subscription_ack(&text) {
+                            if let Some(pubkey) = pending_accounts.remove(&request_id) {
+                                account_subscriptions.insert(subscription_id, pubkey);
+                            }
+                            continue;
+                        }
+                        parse_account_notification(&text, provider_name, &account_subscriptions, received_ns)
+                            .map(RawEvent::Account)
+                    }
+                };
+
+                if let Some(event) = event {
                     msg_received.fetch_add(1, Ordering::Relaxed);
                     // Send to raw channel for dedupe
                     let _ = tx.try_send(event);
@@ -501,7 +886,12 @@ async fn connect_and_subscribe(
 }
 
 /// Parse a logsSubscribe notification into a WssEvent.
-fn parse_log_notification(text: &str, provider_name: &str, log_filters: &Option<Vec<String>>) -> Option<WssEvent> {
+fn parse_log_notification(
+    text: &str,
+    provider_name: &str,
+    log_filters: &Option<Vec<String>>,
+    received_ns: u64,
+) -> Option<WssEvent> {
     let v: serde_json::Value = serde_json::from_str(text).ok()?;
 
     // Check if it's a notification (not a subscription confirmation)
@@ -548,17 +938,57 @@ fn parse_log_notification(text: &str, provider_name: &str, log_filters: &Option<
         }
     }
 
-    let _timestamp_ns = std::time::SystemTime::now()
-        .duration_since(std::time::UNIX_EPOCH)
-        .unwrap()
-        .as_nanos() as u64;
-
     Some(WssEvent {
         provider: provider_name.to_string(),
         slot,
         signature,
         logs,
-        latency_ms: 0.0, // Would need server timestamp to calculate
+        latency_ms: 0.0, // Filled in by the aggregator relative to the first arrival
+        received_ns,
+        is_correction: false,
+    })
+}
+
+/// Parse a subscription confirmation: `{"id": <request>, "result": <subscription>}`.
+fn parse_subscription_ack(text: &str) -> Option<(u64, u64)> {
+    let v: serde_json::Value = serde_json::from_str(text).ok()?;
+    let request_id = v.get("id")?.as_u64()?;
+    let subscription_id = v.get("result")?.as_u64()?;
+    Some((request_id, subscription_id))
+}
+
+/// Parse an accountSubscribe notification into a WssAccountEvent.
+/// `subscriptions` maps subscription id -> account pubkey.
+fn parse_account_notification(
+    text: &str,
+    provider_name: &str,
+    subscriptions: &HashMap<u64, String>,
+    received_ns: u64,
+) -> Option<WssAccountEvent> {
+    let v: serde_json::Value = serde_json::from_str(text).ok()?;
+
+    // Check if it's a notification (not a subscription confirmation)
+    let method = v.get("method")?.as_str()?;
+    if method != "accountNotification" {
+        return None;
+    }
+
+    let params = v.get("params")?;
+    let pubkey = subscriptions.get(&params.get("subscription")?.as_u64()?)?;
+    let result = params.get("result")?;
+    let slot = result.get("context")?.get("slot")?.as_u64()?;
+
+    // data: ["<base64>", "base64"]
+    let encoded = result.get("value")?.get("data")?.get(0)?.as_str()?;
+    let data = general_purpose::STANDARD.decode(encoded).ok()?;
+
+    Some(WssAccountEvent {
+        provider: provider_name.to_string(),
+        pubkey: pubkey.clone(),
+        slot,
+        data,
+        latency_ms: 0.0,
+        received_ns,
     })
 }
 
@@ -569,6 +999,210 @@ fn parse_log_notification(text: &str, provider_name: &str, log_filters: &Option<
 pub fn register_wss_aggregator_classes(m: &PyModule) -> PyResult<()> {
     m.add_class::<WssAggregator>()?;
     m.add_class::<WssEvent>()?;
+    m.add_class::<WssAccountEvent>()?;
     m.add_class::<WssStats>()?;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn notification(signature: &str, slot: u64) -> String {
+        json!({
+            "jsonrpc": "2.0",
+            "method": "logsNotification",
+            "params": {
+                "result": {
+                    "context": { "slot": slot },
+                    "value": {
+                        "signature": signature,
+                        "err": null,
+                        "logs": ["Program log: ray_log: AAAA"]
+                    }
+                },
+                "subscription": 1
+            }
+        })
+        .to_string()
+    }
+
+    #[test]
+    fn test_duplicate_reports_lag_behind_winner() {
+        let mut tracker = RaceTracker::new(100);
+
+        let fast = parse_log_notification(&notification("sig1", 10), "provider_0", &None, 1_000_000).unwrap();
+        let slow = parse_log_notification(&notification("sig1", 10), "provider_1", &None, 4_500_000).unwrap();
+
+        assert_eq!(tracker.observe(fast.signature, fast.received_ns), None);
+
+        let lag_ms = tracker.observe(slow.signature, slow.received_ns).unwrap();
+        assert!(lag_ms > 0.0);
+        assert!((lag_ms - 3.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_recv_event_timeout_blocks_until_event() {
+        pyo3::prepare_freethreaded_python();
+        let aggregator = WssAggregator::new(16, 100, false).unwrap();
+        let tx = aggregator.event_tx.clone().unwrap();
+
+        let producer = std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(50));
+            let event = parse_log_notification(&notification("sig1", 10), "provider_0", &None, 0).unwrap();
+            tx.send(event).unwrap();
+        });
+
+        let started = Instant::now();
+        let event = Python::with_gil(|py| aggregator.recv_event_timeout(py, 500));
+        producer.join().unwrap();
+
+        assert_eq!(event.unwrap().signature, "sig1");
+        assert!(started.elapsed() < Duration::from_millis(500));
+
+        // Idle channel times out
+        let started = Instant::now();
+        let event = Python::with_gil(|py| aggregator.recv_event_timeout(py, 50));
+        assert!(event.is_none());
+        assert!(started.elapsed() >= Duration::from_millis(50));
+    }
+
+    #[test]
+    fn test_parse_account_notification() {
+        // Shape of the Solana RPC docs' accountSubscribe example (slot 5199307,
+        // subscription 23784), with base64 data and space substituted
+        let ack = r#"{"jsonrpc":"2.0","result":23784,"id":1}"#;
+        let account_notification = r#"{"jsonrpc":"2.0","method":"accountNotification","params":{"result":{"context":{"slot":5199307},"value":{"data":["AQIDBAU=","base64"],"executable":false,"lamports":33594,"owner":"11111111111111111111111111111111","rentEpoch":635,"space":5}},"subscription":23784}}"#;
+
+        assert_eq!(parse_subscription_ack(ack), Some((1, 23784)));
+
+        let mut subscriptions = HashMap::new();
+        subscriptions.insert(23784, "58oQChx4yWmvKdwLLZzBi4ChoCc2fqCUWBkwMihLYQo2".to_string());
+
+        let event = parse_account_notification(account_notification, "provider_0", &subscriptions, 42).unwrap();
+        assert_eq!(event.pubkey, "58oQChx4yWmvKdwLLZzBi4ChoCc2fqCUWBkwMihLYQo2");
+        assert_eq!(event.slot, 5199307);
+        assert_eq!(event.data, vec![1, 2, 3, 4, 5]);
+        assert_eq!(event.received_ns, 42);
+
+        // Unknown subscription ids and log notifications are ignored
+        assert!(parse_account_notification(account_notification, "provider_0", &HashMap::new(), 42).is_none());
+        assert!(parse_account_notification(&notification("sig1", 10), "provider_0", &subscriptions, 42).is_none());
+    }
+
+    #[test]
+    fn test_history_never_exceeds_cap() {
+        let mut tracker: RaceTracker<String> = RaceTracker::new(256);
+
+        for i in 0..5000u64 {
+            assert_eq!(tracker.observe(format!("sig{}", i), i), None);
+            assert!(tracker.first_seen_ns.len() <= 256);
+            assert!(tracker.order.len() <= 256);
+        }
+
+        // Most recent signatures are still deduped, the oldest were evicted
+        assert!(tracker.observe("sig4999".to_string(), 5000).is_some());
+        assert_eq!(tracker.observe("sig0".to_string(), 5001), None);
+    }
+
+    #[test]
+    fn test_provider_health_win_rate() {
+        let health = ProviderHealth::new(vec!["provider_0".to_string(), "provider_1".to_string()]);
+
+        for _ in 0..3 {
+            health.record("provider_0", true);
+            health.record("provider_1", false);
+        }
+        health.record("provider_1", true);
+        health.record("unknown", true);
+
+        let snapshot = health.snapshot();
+        assert_eq!(snapshot[0], ("provider_0".to_string(), 3, 0, 1.0));
+        assert_eq!(snapshot[1], ("provider_1".to_string(), 1, 3, 0.25));
+    }
+
+    #[test]
+    fn test_account_dedup_keys_on_pubkey_and_slot() {
+        let mut tracker: RaceTracker<(String, u64)> = RaceTracker::new(100);
+
+        assert_eq!(tracker.observe(("pool".to_string(), 10), 0), None);
+        assert!(tracker.observe(("pool".to_string(), 10), 5).is_some());
+        // Same account at a newer slot is a new update, not a duplicate
+        assert_eq!(tracker.observe(("pool".to_string(), 11), 6), None);
+    }
+
+    #[test]
+    fn test_ws_request_carries_auth_headers() {
+        let headers = vec![
+            ("Authorization".to_string(), "Bearer triton-token".to_string()),
+            ("x-api-key".to_string(), "helius-key".to_string()),
+        ];
+        let request = build_ws_request("wss://example.rpcpool.com/ws?commitment=processed", &headers).unwrap();
+
+        assert_eq!(request.uri().to_string(), "wss://example.rpcpool.com/ws?commitment=processed");
+        assert_eq!(request.headers()["authorization"], "Bearer triton-token");
+        assert_eq!(request.headers()["x-api-key"], "helius-key");
+        // Standard handshake headers are still generated
+        assert!(request.headers().contains_key("sec-websocket-key"));
+
+        assert!(build_ws_request("wss://example.com", &[("bad header".to_string(), "v".to_string())]).is_err());
+        assert!(build_ws_request("wss://example.com", &[("x-api-key".to_string(), "line\nbreak".to_string())]).is_err());
+    }
+
+    #[test]
+    fn test_slot_aware_dedup_forwards_higher_slot_as_correction() {
+        let mut tracker: RaceTracker<String> = RaceTracker::new(100);
+        assert_eq!(tracker.observe_at_slot("sig1".to_string(), 100, 0), Arrival::First);
+        assert_eq!(tracker.observe_at_slot("sig1".to_string(), 100, 1_000_000), Arrival::Duplicate(1.0));
+        assert_eq!(tracker.observe_at_slot("sig1".to_string(), 101, 2_000_000), Arrival::Newer(2.0));
+        // The higher slot is now the mark to beat
+        assert_eq!(tracker.observe_at_slot("sig1".to_string(), 100, 3_000_000), Arrival::Duplicate(3.0));
+
+        // End to end through the aggregator loop, in both modes
+        let forwarded = |slot_aware: bool| {
+            let runtime = Runtime::new().unwrap();
+            let (raw_tx, raw_rx) = bounded(16);
+            let (event_tx, event_rx) = bounded(16);
+            let (account_tx, _account_rx) = bounded(16);
+            let running = Arc::new(AtomicBool::new(true));
+            runtime.spawn(run_aggregator(
+                raw_rx,
+                event_tx,
+                account_tx,
+                running.clone(),
+                Arc::new(AtomicU64::new(0)),
+                Arc::new(AtomicU64::new(0)),
+                Arc::new(AtomicU64::new(0)),
+                Arc::new(AtomicU64::new(0)),
+                100,
+                slot_aware,
+                Arc::new(ProviderHealth::default()),
+            ));
+
+            for (provider, slot, received_ns) in [("provider_0", 100, 0), ("provider_1", 101, 1_000_000)] {
+                let event = parse_log_notification(&notification("sig1", slot), provider, &None, received_ns).unwrap();
+                raw_tx.send(RawEvent::Log(event)).unwrap();
+            }
+
+            let mut events = Vec::new();
+            while let Ok(event) = event_rx.recv_timeout(Duration::from_millis(100)) {
+                events.push(event);
+            }
+            running.store(false, Ordering::SeqCst);
+            runtime.shutdown_background();
+            events
+        };
+
+        let plain = forwarded(false);
+        assert_eq!(plain.len(), 1);
+        assert_eq!(plain[0].slot, 100);
+
+        let slot_aware = forwarded(true);
+        assert_eq!(slot_aware.len(), 2);
+        assert!(!slot_aware[0].is_correction);
+        let winner = slot_aware.last().unwrap();
+        assert_eq!(winner.slot, 101);
+        assert!(winner.is_correction);
+        assert!((winner.latency_ms - 1.0).abs() < 1e-9);
+    }
+}